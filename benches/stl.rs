@@ -0,0 +1,95 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use stl_io::mesh::{voxelize, ScalarGrid};
+use stl_io::Triangle;
+
+const BUNNY_99: &[u8] = include_bytes!("../src/testdata/bunny_99.stl");
+
+// Sphere of radius 1 sampled on a grid with n cells per diameter.
+fn sphere_grid(n: usize) -> ScalarGrid {
+    let spacing = 2.5 / n as f32;
+    let size = [n + 1; 3];
+    let origin = [-1.25; 3];
+    let mut values = Vec::with_capacity(size[0] * size[1] * size[2]);
+    for z in 0..size[2] {
+        for y in 0..size[1] {
+            for x in 0..size[0] {
+                let p = [x, y, z].map(|i| origin[0] + spacing * i as f32);
+                values.push((p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt() - 1.);
+            }
+        }
+    }
+    ScalarGrid {
+        origin,
+        spacing,
+        size,
+        values,
+    }
+}
+
+// Reference models: the bunny and spheres of increasing resolution.
+fn models() -> Vec<(String, Vec<Triangle>)> {
+    let mut models = vec![(
+        "bunny_99".to_string(),
+        stl_io::parse_stl_bytes(BUNNY_99).unwrap().triangles(),
+    )];
+    for &n in &[16, 64] {
+        let sphere = sphere_grid(n).isosurface(0.);
+        models.push((format!("sphere_{}", n), sphere.triangles()));
+    }
+    models
+}
+
+fn io(c: &mut Criterion) {
+    let mut group = c.benchmark_group("io");
+    for (name, mesh) in models() {
+        let mut binary = Vec::new();
+        stl_io::write_stl(&mut binary, mesh.iter()).unwrap();
+        let mut ascii = Vec::new();
+        stl_io::write_ascii_stl(&mut ascii, mesh.iter()).unwrap();
+        group.throughput(Throughput::Elements(mesh.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("write_binary", &name), &mesh, |b, mesh| {
+            b.iter(|| {
+                let mut out = Vec::with_capacity(binary.len());
+                stl_io::write_stl(&mut out, mesh.iter()).unwrap();
+                out
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("write_ascii", &name), &mesh, |b, mesh| {
+            b.iter(|| {
+                let mut out = Vec::with_capacity(ascii.len());
+                stl_io::write_ascii_stl(&mut out, mesh.iter()).unwrap();
+                out
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("read_binary", &name),
+            &binary,
+            |b, bytes| b.iter(|| stl_io::read_stl(&mut ::std::io::Cursor::new(bytes)).unwrap()),
+        );
+        group.bench_with_input(BenchmarkId::new("read_ascii", &name), &ascii, |b, bytes| {
+            b.iter(|| stl_io::read_stl(&mut ::std::io::Cursor::new(bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn grid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grid");
+    let bunny = stl_io::parse_stl_bytes(BUNNY_99).unwrap();
+    for &n in &[16, 32, 64] {
+        let grid = sphere_grid(n);
+        group.bench_with_input(BenchmarkId::new("isosurface", n), &grid, |b, grid| {
+            b.iter(|| grid.isosurface(0.))
+        });
+        // The bunny is about 0.15 units large.
+        let spacing = 0.15 / n as f32;
+        group.bench_with_input(BenchmarkId::new("voxelize_bunny", n), &spacing, |b, &s| {
+            b.iter(|| voxelize(&bunny, s).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, io, grid);
+criterion_main!(benches);
@@ -0,0 +1,245 @@
+//! Named per-vertex and per-face data, such as colors, materials or quality flags, attached to
+//! an [IndexedMesh](../struct.IndexedMesh.html).
+//!
+//! STL cannot store attributes, but [PLY](../formats/ply/index.html) can, and
+//! [convert](../formats/fn.convert.html) keeps them when converting PLY to PLY.
+
+use crate::IndexedMesh;
+use std::io::{Error, ErrorKind, Result};
+
+/// What the values of an [Attribute](struct.Attribute.html) belong to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Domain {
+    /// One value per vertex, in the order of `IndexedMesh::vertices`.
+    Vertex,
+    /// One value per face, in the order of `IndexedMesh::faces`.
+    Face,
+}
+
+/// The values of an [Attribute](struct.Attribute.html).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Values {
+    /// A scalar per element.
+    F32(Vec<f32>),
+    /// A 3D vector per element, e.g. a color.
+    Vec3(Vec<[f32; 3]>),
+    /// An integer per element, e.g. a material index or flags.
+    U32(Vec<u32>),
+}
+
+impl Values {
+    /// Number of elements the values are given for.
+    pub fn len(&self) -> usize {
+        match self {
+            Values::F32(v) => v.len(),
+            Values::Vec3(v) => v.len(),
+            Values::U32(v) => v.len(),
+        }
+    }
+
+    /// Whether there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Values with the elements at the given indices, in that order.
+    pub(crate) fn select(&self, indices: &[usize]) -> Values {
+        match self {
+            Values::F32(v) => Values::F32(indices.iter().map(|&i| v[i]).collect()),
+            Values::Vec3(v) => Values::Vec3(indices.iter().map(|&i| v[i]).collect()),
+            Values::U32(v) => Values::U32(indices.iter().map(|&i| v[i]).collect()),
+        }
+    }
+}
+
+/// A named channel of values for every vertex or every face of a mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
+    /// Name of the attribute, unique per domain.
+    pub name: String,
+    /// Whether the values belong to vertices or faces.
+    pub domain: Domain,
+    /// The values.
+    pub values: Values,
+}
+
+/// An [IndexedMesh](../struct.IndexedMesh.html) with attributes.
+///
+/// ```
+/// use stl_io::attributes::{AttributedMesh, Domain, Values};
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let mut mesh = AttributedMesh::new(mesh);
+/// mesh.set_attribute("material", Domain::Face, Values::U32(vec![7])).unwrap();
+/// assert!(mesh.set_attribute("color", Domain::Vertex, Values::U32(vec![1])).is_err());
+/// assert_eq!(mesh.attribute("material", Domain::Face), Some(&Values::U32(vec![7])));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributedMesh {
+    /// The geometry.
+    pub mesh: IndexedMesh,
+    /// The attributes, each with a value for every vertex or face of mesh.
+    pub attributes: Vec<Attribute>,
+}
+
+impl AttributedMesh {
+    /// Wraps mesh without any attributes.
+    pub fn new(mesh: IndexedMesh) -> AttributedMesh {
+        AttributedMesh {
+            mesh,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// The values of the attribute with the given name and domain, if there is one.
+    pub fn attribute(&self, name: &str, domain: Domain) -> Option<&Values> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name && a.domain == domain)
+            .map(|a| &a.values)
+    }
+
+    /// Adds an attribute, replacing the one with the same name and domain, if any.
+    ///
+    /// Fails if there is not exactly one value per vertex or face, or if the name is not
+    /// [valid](fn.check_name.html).
+    pub fn set_attribute(&mut self, name: &str, domain: Domain, values: Values) -> Result<()> {
+        check_name(name, domain)?;
+        let expected = self.len(domain);
+        if values.len() != expected {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "attribute {:?} has {} values, but the mesh has {} {}",
+                    name,
+                    values.len(),
+                    expected,
+                    match domain {
+                        Domain::Vertex => "vertices",
+                        Domain::Face => "faces",
+                    }
+                ),
+            ));
+        }
+        let attribute = Attribute {
+            name: name.to_string(),
+            domain,
+            values,
+        };
+        match self
+            .attributes
+            .iter_mut()
+            .find(|a| a.name == name && a.domain == domain)
+        {
+            Some(existing) => *existing = attribute,
+            None => self.attributes.push(attribute),
+        }
+        Ok(())
+    }
+
+    /// Removes the attribute with the given name and domain and returns its values.
+    pub fn remove_attribute(&mut self, name: &str, domain: Domain) -> Option<Values> {
+        let index = self
+            .attributes
+            .iter()
+            .position(|a| a.name == name && a.domain == domain)?;
+        Some(self.attributes.remove(index).values)
+    }
+
+    fn len(&self, domain: Domain) -> usize {
+        match domain {
+            Domain::Vertex => self.mesh.vertices.len(),
+            Domain::Face => self.mesh.faces.len(),
+        }
+    }
+}
+
+impl From<IndexedMesh> for AttributedMesh {
+    fn from(mesh: IndexedMesh) -> AttributedMesh {
+        AttributedMesh::new(mesh)
+    }
+}
+
+/// Checks that name can be used for an attribute of the given domain, and fails with
+/// InvalidInput otherwise.
+///
+/// Names must be non-empty and consist of printable ascii characters other than space, so they
+/// can be written to file headers. `x`, `y` and `z` are reserved for the positions of
+/// vertices, and `vertex_indices` and `vertex_index` for the corners of faces.
+pub fn check_name(name: &str, domain: Domain) -> Result<()> {
+    let reserved: &[&str] = match domain {
+        Domain::Vertex => &["x", "y", "z"],
+        Domain::Face => &["vertex_indices", "vertex_index"],
+    };
+    if name.is_empty() || !name.bytes().all(|b| b.is_ascii_graphic()) || reserved.contains(&name) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid attribute name {:?}", name),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::IndexedTriangle;
+
+    #[test]
+    fn set_and_remove_attributes() {
+        let mut mesh = AttributedMesh::new(IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+            faces: vec![IndexedTriangle {
+                normal: [0., 0., 1.],
+                vertices: [0, 1, 2],
+            }],
+        });
+        let color = Values::Vec3(vec![[1., 0., 0.]; 3]);
+        mesh.set_attribute("color", Domain::Vertex, color.clone())
+            .unwrap();
+        mesh.set_attribute("color", Domain::Face, Values::F32(vec![0.5]))
+            .unwrap();
+        assert_eq!(mesh.attributes.len(), 2);
+        mesh.set_attribute("color", Domain::Face, Values::F32(vec![0.25]))
+            .unwrap();
+        assert_eq!(mesh.attributes.len(), 2);
+        assert_eq!(
+            mesh.attribute("color", Domain::Face),
+            Some(&Values::F32(vec![0.25]))
+        );
+        assert_eq!(mesh.remove_attribute("color", Domain::Vertex), Some(color));
+        assert_eq!(mesh.attribute("color", Domain::Vertex), None);
+        assert!(mesh
+            .set_attribute("quality", Domain::Face, Values::U32(Vec::new()))
+            .is_err());
+    }
+
+    #[test]
+    fn attribute_names() {
+        assert!(check_name("color", Domain::Vertex).is_ok());
+        assert!(check_name("x", Domain::Face).is_ok());
+        assert!(check_name("vertex_index", Domain::Vertex).is_ok());
+        for &(name, domain) in &[
+            ("", Domain::Vertex),
+            ("two words", Domain::Vertex),
+            ("line\nbreak", Domain::Face),
+            ("tab\t", Domain::Face),
+            ("ümlaut", Domain::Face),
+            ("y", Domain::Vertex),
+            ("vertex_indices", Domain::Face),
+        ] {
+            let error = check_name(name, domain).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput, "{:?}", name);
+        }
+        let mut mesh = AttributedMesh::new(IndexedMesh {
+            vertices: vec![[0., 0., 0.]],
+            faces: Vec::new(),
+        });
+        assert!(mesh
+            .set_attribute("z", Domain::Vertex, Values::F32(vec![1.]))
+            .is_err());
+        assert!(mesh.attributes.is_empty());
+    }
+}
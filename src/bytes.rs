@@ -0,0 +1,277 @@
+//! Zero-copy access to binary STL stored in a byte slice, e.g. a memory-mapped file.
+//!
+//! Everything in here only needs `core` and `alloc`, so it is available without the `std`
+//! feature.
+
+use crate::{BoundingBox, IndexedMesh, Triangle, Vertex, Winding};
+use alloc::vec::Vec;
+use byteorder::{ByteOrder, LittleEndian};
+use core::fmt;
+
+pub(crate) const HEADER_SIZE: usize = 84;
+pub(crate) const TRIANGLE_SIZE: usize = 50;
+
+// Decodes one 50 byte binary STL triangle record, ignoring the attribute byte count.
+pub(crate) fn decode_triangle(record: &[u8]) -> Triangle {
+    let mut normal = [0.; 3];
+    LittleEndian::read_f32_into(&record[0..12], &mut normal);
+    let mut vertices = [[0.; 3]; 3];
+    for (i, vertex) in vertices.iter_mut().enumerate() {
+        LittleEndian::read_f32_into(&record[12 + i * 12..24 + i * 12], vertex);
+    }
+    Triangle { normal, vertices }
+}
+
+// Encodes t as a 50 byte binary STL triangle record with zero attribute byte count.
+pub(crate) fn encode_triangle(t: &Triangle, winding: Winding, record: &mut [u8]) {
+    LittleEndian::write_f32_into(&t.normal, &mut record[0..12]);
+    for (i, &v) in winding.order().iter().enumerate() {
+        LittleEndian::write_f32_into(&t.vertices[v], &mut record[12 + i * 12..24 + i * 12]);
+    }
+    LittleEndian::write_u16(&mut record[48..TRIANGLE_SIZE], 0);
+}
+
+/// Binary STL borrowed from a byte slice.
+///
+/// Triangles are only decoded when they are accessed, so counting triangles or computing the
+/// bounding box of huge files is cheap.
+#[derive(Clone, Copy, Debug)]
+pub struct BinaryStlBytes<'a> {
+    header: &'a [u8],
+    data: &'a [u8],
+}
+
+/// Error returned by [from_bytes](fn.from_bytes.html) if the slice is shorter than the header
+/// and the triangle count in it require.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TruncatedError {
+    /// Number of bytes the header requires, or 84 if the header itself is incomplete.
+    pub expected: usize,
+    /// Number of bytes in the slice.
+    pub actual: usize,
+}
+
+impl fmt::Display for TruncatedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.actual < HEADER_SIZE {
+            write!(f, "binary STL is shorter than its 84 byte header")
+        } else {
+            write!(
+                f,
+                "binary STL header announces {} triangles, but only {} bytes follow",
+                (self.expected - HEADER_SIZE) / TRIANGLE_SIZE,
+                self.actual - HEADER_SIZE
+            )
+        }
+    }
+}
+
+impl core::error::Error for TruncatedError {}
+
+#[cfg(feature = "std")]
+impl From<TruncatedError> for ::std::io::Error {
+    fn from(e: TruncatedError) -> ::std::io::Error {
+        ::std::io::Error::new(::std::io::ErrorKind::UnexpectedEof, e)
+    }
+}
+
+/// Parses a binary STL from a byte slice without copying triangle data.
+///
+/// Fails if the slice is shorter than the triangle count in the header requires.
+///
+/// ```
+/// let mesh = [stl_io::Triangle { normal: [1.0, 0.0, 0.0],
+///                                vertices: [[0.0, -1.0, 0.0],
+///                                           [0.0, 1.0, 0.0],
+///                                           [0.0, 0.0, 0.5]]}];
+/// let mut binary_stl = Vec::<u8>::new();
+/// stl_io::write_stl(&mut binary_stl, mesh.iter()).unwrap();
+/// let stl = stl_io::from_bytes(&binary_stl).unwrap();
+/// assert_eq!(stl.len(), 1);
+/// assert_eq!(stl.get(0), Some(mesh[0].clone()));
+/// ```
+pub fn from_bytes(bytes: &[u8]) -> Result<BinaryStlBytes<'_>, TruncatedError> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(TruncatedError {
+            expected: HEADER_SIZE,
+            actual: bytes.len(),
+        });
+    }
+    let num_faces = LittleEndian::read_u32(&bytes[80..HEADER_SIZE]) as usize;
+    let data_len = num_faces
+        .checked_mul(TRIANGLE_SIZE)
+        .filter(|len| *len <= bytes.len() - HEADER_SIZE)
+        .ok_or(TruncatedError {
+            expected: num_faces
+                .saturating_mul(TRIANGLE_SIZE)
+                .saturating_add(HEADER_SIZE),
+            actual: bytes.len(),
+        })?;
+    Ok(BinaryStlBytes {
+        header: &bytes[..80],
+        data: &bytes[HEADER_SIZE..HEADER_SIZE + data_len],
+    })
+}
+
+impl<'a> BinaryStlBytes<'a> {
+    /// The 80 byte header.
+    pub fn header(&self) -> &'a [u8] {
+        self.header
+    }
+    /// Number of triangles.
+    pub fn len(&self) -> usize {
+        self.data.len() / TRIANGLE_SIZE
+    }
+    /// Whether there are no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    /// Decodes the triangle at index, if it exists.
+    pub fn get(&self, index: usize) -> Option<Triangle> {
+        if index >= self.len() {
+            return None;
+        }
+        Some(decode_triangle(
+            &self.data[index * TRIANGLE_SIZE..(index + 1) * TRIANGLE_SIZE],
+        ))
+    }
+    /// Iterates over all triangles, decoding them on the fly.
+    pub fn iter(&self) -> BinaryStlBytesIter<'a> {
+        BinaryStlBytesIter {
+            stl: *self,
+            index: 0,
+        }
+    }
+    /// Bounding box of all vertices, without decoding the normals.
+    /// Returns None if there are no triangles.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_vertices(self.data.chunks(TRIANGLE_SIZE).flat_map(|record| {
+            (0..3).map(move |i| {
+                let mut vertex: Vertex = [0.; 3];
+                LittleEndian::read_f32_into(&record[12 + i * 12..24 + i * 12], &mut vertex);
+                vertex
+            })
+        }))
+    }
+}
+
+/// Encodes triangles as binary STL with an empty header, like [write_stl](fn.write_stl.html)
+/// but into a new buffer.
+///
+/// ```
+/// let mesh = [stl_io::Triangle { normal: [1.0, 0.0, 0.0],
+///                                vertices: [[0.0, -1.0, 0.0],
+///                                           [0.0, 1.0, 0.0],
+///                                           [0.0, 0.0, 0.5]]}];
+/// let binary_stl = stl_io::to_bytes(mesh.iter());
+/// assert_eq!(stl_io::from_bytes(&binary_stl).unwrap().get(0), Some(mesh[0].clone()));
+/// ```
+pub fn to_bytes<'a, I>(mesh: I) -> Vec<u8>
+where
+    I: ExactSizeIterator<Item = &'a Triangle>,
+{
+    let mut bytes = alloc::vec![0u8; HEADER_SIZE + mesh.len() * TRIANGLE_SIZE];
+    LittleEndian::write_u32(&mut bytes[80..HEADER_SIZE], mesh.len() as u32);
+    for (t, record) in mesh.zip(bytes[HEADER_SIZE..].chunks_mut(TRIANGLE_SIZE)) {
+        encode_triangle(t, Winding::CounterClockwise, record);
+    }
+    bytes
+}
+
+/// Encodes an [IndexedMesh](struct.IndexedMesh.html) as binary STL, e.g. to hand it from
+/// WebAssembly to JavaScript.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let binary_stl = stl_io::mesh_to_stl_bytes(&mesh);
+/// assert_eq!(binary_stl.len(), 84 + 50);
+/// ```
+pub fn mesh_to_stl_bytes(mesh: &IndexedMesh) -> Vec<u8> {
+    let triangles = mesh.triangles();
+    to_bytes(triangles.iter())
+}
+
+/// Iterator over the triangles of a [BinaryStlBytes](struct.BinaryStlBytes.html).
+///
+/// Yields triangles rather than results, since the length of the data was validated by
+/// [from_bytes](fn.from_bytes.html).
+#[derive(Clone, Debug)]
+pub struct BinaryStlBytesIter<'a> {
+    stl: BinaryStlBytes<'a>,
+    index: usize,
+}
+
+impl<'a> Iterator for BinaryStlBytesIter<'a> {
+    type Item = Triangle;
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.stl.get(self.index)?;
+        self.index += 1;
+        Some(t)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.stl.len() - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for BinaryStlBytesIter<'a> {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+
+    #[test]
+    fn from_bytes_matches_binary_reader() {
+        let stl = from_bytes(BUNNY_99).unwrap();
+        assert_eq!(stl.len(), 99);
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let expected = crate::BinaryStlReader::create_triangle_iterator(&mut reader)
+            .unwrap()
+            .map(|t| t.unwrap())
+            .collect::<Vec<_>>();
+        let actual = stl.iter().collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn from_bytes_bounding_box() {
+        let stl = from_bytes(BUNNY_99).unwrap();
+        let bbox = stl.bounding_box().unwrap();
+        for t in stl.iter() {
+            for v in &t.vertices {
+                for ((min, max), c) in bbox.min.iter().zip(&bbox.max).zip(v) {
+                    assert!(min <= c && c <= max);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_bytes_truncated() {
+        let err = from_bytes(&BUNNY_99[..BUNNY_99.len() - 1]).unwrap_err();
+        assert_eq!(
+            err,
+            TruncatedError {
+                expected: BUNNY_99.len(),
+                actual: BUNNY_99.len() - 1
+            }
+        );
+        assert_eq!(
+            ::std::io::Error::from(err).kind(),
+            ::std::io::ErrorKind::UnexpectedEof
+        );
+        assert!(from_bytes(&BUNNY_99[..83]).is_err());
+    }
+
+    #[test]
+    fn to_bytes_matches_write_stl() {
+        let mesh = from_bytes(BUNNY_99).unwrap().iter().collect::<Vec<_>>();
+        let mut expected = Vec::new();
+        crate::write_stl(&mut expected, mesh.iter()).unwrap();
+        assert_eq!(to_bytes(mesh.iter()), expected);
+    }
+}
@@ -0,0 +1,253 @@
+//! Reading and writing compressed STL, e.g. `.stl.gz` files.
+//!
+//! Gzip and zstd support are enabled by the `gzip` and `zstd` features. Without them this module
+//! only passes uncompressed STL through, and rejects compressed input with a helpful error.
+//!
+//! ```
+//! let mesh = [stl_io::Triangle { normal: [1.0, 0.0, 0.0],
+//!                                vertices: [[0.0, -1.0, 0.0],
+//!                                           [0.0, 1.0, 0.0],
+//!                                           [0.0, 0.0, 0.5]]}];
+//! let mut stl = Vec::<u8>::new();
+//! stl_io::compression::write_stl(&mut stl, mesh.iter(), stl_io::compression::Compression::None)
+//!     .unwrap();
+//! let indexed_mesh = stl_io::compression::read_stl(&mut &stl[..]).unwrap();
+//! assert_eq!(indexed_mesh.faces.len(), 1);
+//! ```
+
+use crate::formats::invalid_data;
+use crate::{IndexedMesh, Triangle, Winding};
+use std::io::{Read, Result};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression applied to an STL stream.
+///
+/// Variants for the compression formats are only available with the matching feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Plain STL.
+    None,
+    /// Gzip, as in `.stl.gz` files.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard, as in `.stl.zst` files.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Reads ascii or binary STL from reader, decompressing it first if it starts with the magic
+/// bytes of gzip or zstd. Data that starts with the magic bytes but cannot be decompressed is
+/// parsed as is, since the header of a binary STL may start with them by chance.
+///
+/// The decompressed data is buffered completely before it is parsed, without any limit on its
+/// size. Use [read_stl_limited](fn.read_stl_limited.html) for untrusted input.
+pub fn read_stl<R: ::std::io::Read>(reader: &mut R) -> Result<IndexedMesh> {
+    read_stl_limited(reader, u64::MAX)
+}
+
+/// Like [read_stl](fn.read_stl.html), but fails with InvalidData instead of buffering more than
+/// limit bytes, before or after decompression. A small compressed file can decompress to
+/// gigabytes, so limit should be set when reading untrusted input.
+///
+/// ```
+/// let mesh = [stl_io::Triangle { normal: [1.0, 0.0, 0.0],
+///                                vertices: [[0.0, -1.0, 0.0],
+///                                           [0.0, 1.0, 0.0],
+///                                           [0.0, 0.0, 0.5]]}];
+/// let mut stl = Vec::<u8>::new();
+/// stl_io::write_stl(&mut stl, mesh.iter()).unwrap();
+/// assert!(stl_io::compression::read_stl_limited(&mut &stl[..], 134).is_ok());
+/// assert!(stl_io::compression::read_stl_limited(&mut &stl[..], 133).is_err());
+/// ```
+pub fn read_stl_limited<R: ::std::io::Read>(reader: &mut R, limit: u64) -> Result<IndexedMesh> {
+    let data = read_limited(reader, limit)?;
+    let decompressed = if data.starts_with(GZIP_MAGIC) {
+        Some(gunzip(&data, limit))
+    } else if data.starts_with(ZSTD_MAGIC) {
+        Some(unzstd(&data, limit))
+    } else {
+        None
+    };
+    match decompressed {
+        None => crate::parse_stl_bytes(&data),
+        Some(Ok(decompressed)) => crate::parse_stl_bytes(&decompressed),
+        Some(Err(e)) => crate::parse_stl_bytes(&data).map_err(|_| e),
+    }
+}
+
+// Reads all of reader, failing if it has more than limit bytes.
+fn read_limited<R: ::std::io::Read>(reader: R, limit: u64) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(invalid_data(format!("STL is larger than {} bytes", limit)));
+    }
+    Ok(data)
+}
+
+/// Writes binary STL to writer, compressed as requested.
+pub fn write_stl<'a, W, I>(writer: &mut W, mesh: I, compression: Compression) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::ExactSizeIterator<Item = &'a Triangle>,
+{
+    write_stl_with_winding(writer, mesh, compression, Winding::CounterClockwise)
+}
+
+/// Like [write_stl](fn.write_stl.html), but emits the vertices of each triangle in the given
+/// [Winding](../enum.Winding.html).
+pub fn write_stl_with_winding<'a, W, I>(
+    writer: &mut W,
+    mesh: I,
+    compression: Compression,
+    winding: Winding,
+) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::ExactSizeIterator<Item = &'a Triangle>,
+{
+    match compression {
+        Compression::None => crate::write_stl_with_winding(writer, mesh, winding),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            crate::write_stl_with_winding(&mut encoder, mesh, winding)?;
+            encoder.finish()?;
+            Ok(())
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(writer, 0)?;
+            crate::write_stl_with_winding(&mut encoder, mesh, winding)?;
+            encoder.finish()?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(data: &[u8], limit: u64) -> Result<Vec<u8>> {
+    read_limited(flate2::read::MultiGzDecoder::new(data), limit)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_: &[u8], _: u64) -> Result<Vec<u8>> {
+    Err(unsupported("gzip"))
+}
+
+#[cfg(feature = "zstd")]
+fn unzstd(data: &[u8], limit: u64) -> Result<Vec<u8>> {
+    read_limited(zstd::Decoder::new(data)?, limit)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn unzstd(_: &[u8], _: u64) -> Result<Vec<u8>> {
+    Err(unsupported("zstd"))
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn unsupported(feature: &str) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::Unsupported,
+        format!(
+            "STL is {} compressed, which requires the {:?} feature of stl_io",
+            feature, feature
+        ),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+    const BUNNY_99_ASCII: &[u8] = include_bytes!("testdata/bunny_99_ascii.stl");
+
+    #[test]
+    fn read_uncompressed() {
+        let expected = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        assert_eq!(read_stl(&mut &BUNNY_99[..]).unwrap(), expected);
+        assert_eq!(read_stl(&mut &BUNNY_99_ASCII[..]).unwrap().faces.len(), 99);
+    }
+
+    #[test]
+    fn magic_bytes_in_binary_header() {
+        let expected = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        for magic in &[GZIP_MAGIC, ZSTD_MAGIC] {
+            let mut stl = BUNNY_99.to_vec();
+            stl[..magic.len()].copy_from_slice(magic);
+            assert_eq!(read_stl(&mut &stl[..]).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn read_limited_size() {
+        let len = BUNNY_99.len() as u64;
+        assert!(read_stl_limited(&mut &BUNNY_99[..], len).is_ok());
+        let err = read_stl_limited(&mut &BUNNY_99[..], len - 1).unwrap_err();
+        assert_eq!(err.kind(), ::std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn write_clockwise() {
+        let bunny = crate::testing::bunny().triangles();
+        let mut expected = Vec::new();
+        crate::write_stl_with_winding(&mut expected, bunny.iter(), Winding::Clockwise).unwrap();
+        let mut stl = Vec::new();
+        write_stl_with_winding(
+            &mut stl,
+            bunny.iter(),
+            Compression::None,
+            Winding::Clockwise,
+        )
+        .unwrap();
+        assert_eq!(stl, expected);
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn read_gzip_without_feature() {
+        let err = read_stl(&mut &[0x1f, 0x8b, 8, 0][..]).unwrap_err();
+        assert_eq!(err.kind(), ::std::io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip() {
+        let mut compressed = Vec::new();
+        write_stl(
+            &mut compressed,
+            crate::testing::bunny().triangles().iter(),
+            Compression::Gzip,
+        )
+        .unwrap();
+        assert!(compressed.starts_with(GZIP_MAGIC));
+        assert!(compressed.len() < BUNNY_99.len());
+        let expected = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        assert_eq!(read_stl(&mut &compressed[..]).unwrap(), expected);
+
+        // The limit applies to the decompressed size.
+        let limit = BUNNY_99.len() as u64 - 1;
+        let err = read_stl_limited(&mut &compressed[..], limit).unwrap_err();
+        assert_eq!(err.kind(), ::std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        let mut compressed = Vec::new();
+        write_stl(
+            &mut compressed,
+            crate::testing::bunny().triangles().iter(),
+            Compression::Zstd,
+        )
+        .unwrap();
+        assert!(compressed.starts_with(ZSTD_MAGIC));
+        let expected = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        assert_eq!(read_stl(&mut &compressed[..]).unwrap(), expected);
+    }
+}
@@ -0,0 +1,66 @@
+//! Ascii [DXF](https://en.wikipedia.org/wiki/AutoCAD_DXF) output of 2D contours, e.g. from
+//! [slice](../../mesh/fn.slice.html).
+
+use crate::mesh::Polyline;
+use std::io::{BufWriter, Result, Write};
+
+/// Writes the x and y coordinates of contours as DXF R12 polylines, which laser cutter and CAD
+/// software commonly import.
+///
+/// ```
+/// let square = stl_io::mesh::Polyline {
+///     points: vec![[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]],
+///     closed: true,
+/// };
+/// let mut dxf = Vec::<u8>::new();
+/// stl_io::formats::dxf::write_dxf(&mut dxf, &[square]).unwrap();
+/// assert!(String::from_utf8(dxf).unwrap().ends_with("0\nEOF\n"));
+/// ```
+pub fn write_dxf<W: ::std::io::Write>(writer: &mut W, contours: &[Polyline]) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    // DXF consists of pairs of lines: a group code and a value.
+    let mut pair = |code: u16, value: &dyn ::std::fmt::Display| -> Result<()> {
+        writeln!(writer, "{}", code)?;
+        writeln!(writer, "{}", value)
+    };
+    pair(0, &"SECTION")?;
+    pair(2, &"ENTITIES")?;
+    for contour in contours {
+        pair(0, &"POLYLINE")?;
+        pair(8, &0)?;
+        // Vertices follow.
+        pair(66, &1)?;
+        pair(70, &if contour.closed { 1 } else { 0 })?;
+        for p in &contour.points {
+            pair(0, &"VERTEX")?;
+            pair(8, &0)?;
+            pair(10, &p[0])?;
+            pair(20, &p[1])?;
+        }
+        pair(0, &"SEQEND")?;
+    }
+    pair(0, &"ENDSEC")?;
+    pair(0, &"EOF")?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write_dxf_polyline() {
+        let contours = [Polyline {
+            points: vec![[0., 0., 3.], [1.5, -2., 3.]],
+            closed: false,
+        }];
+        let mut dxf = Vec::<u8>::new();
+        write_dxf(&mut dxf, &contours).unwrap();
+        assert_eq!(
+            String::from_utf8(dxf).unwrap(),
+            "0\nSECTION\n2\nENTITIES\n0\nPOLYLINE\n8\n0\n66\n1\n70\n0\n\
+             0\nVERTEX\n8\n0\n10\n0\n20\n0\n0\nVERTEX\n8\n0\n10\n1.5\n20\n-2\n\
+             0\nSEQEND\n0\nENDSEC\n0\nEOF\n"
+        );
+    }
+}
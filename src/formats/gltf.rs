@@ -0,0 +1,240 @@
+//! Binary [glTF](https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html) (GLB) output.
+
+use crate::math::{add, area_normal, normalize, to_f32, to_f64};
+use crate::{BoundingBox, IndexedMesh};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::{BufWriter, Result, Write};
+
+const MAGIC: u32 = 0x4654_6C67;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Writes mesh as a GLB file containing a single mesh with positions, normals and indices.
+///
+/// Vertices stay shared between faces, so the mesh appears smooth shaded. Vertex normals are the
+/// area weighted average of the normals of the adjacent faces. Faces are always written
+/// counter-clockwise, since glTF defines front faces that way, so unlike the other writers
+/// there is no variant taking a [Winding](../../enum.Winding.html).
+///
+/// Fails with InvalidInput if a vertex is not finite, which JSON cannot represent in the bounds
+/// glTF requires, or if the mesh does not fit into the 32 bit sizes and indices of GLB.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let mut glb = Vec::<u8>::new();
+/// stl_io::formats::gltf::write_glb(&mut glb, &mesh).unwrap();
+/// assert_eq!(&glb[..4], b"glTF");
+/// ```
+pub fn write_glb<W: ::std::io::Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    if let Some(i) = mesh
+        .vertices
+        .iter()
+        .position(|v| !v.iter().all(|c| c.is_finite()))
+    {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("vertex {} is not finite", i),
+        ));
+    }
+    let mut bin = Vec::new();
+    let json = match BoundingBox::from_vertices(mesh.vertices.iter().cloned()) {
+        Some(bbox) if !mesh.faces.is_empty() => {
+            let bin_len = mesh
+                .vertices
+                .len()
+                .checked_mul(24)
+                .zip(mesh.faces.len().checked_mul(12))
+                .and_then(|(a, b)| a.checked_add(b))
+                .ok_or_else(too_large)?;
+            glb_length(0, bin_len)?;
+            let indices = mesh.index_buffer::<u32>()?;
+            bin.reserve(bin_len);
+            for v in &mesh.vertices {
+                for &c in v {
+                    bin.write_f32::<LittleEndian>(c)?;
+                }
+            }
+            for n in &vertex_normals(mesh) {
+                for &c in n {
+                    bin.write_f32::<LittleEndian>(c)?;
+                }
+            }
+            for i in indices {
+                bin.write_u32::<LittleEndian>(i)?;
+            }
+            mesh_json(mesh, &bbox, bin.len())
+        }
+        // Accessors must not be empty, so a mesh without faces becomes an empty scene.
+        _ => r#"{"asset":{"version":"2.0"},"scene":0,"scenes":[{"nodes":[]}]}"#.to_string(),
+    };
+    let mut json = json.into_bytes();
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+    while bin.len() % 4 != 0 {
+        bin.push(0);
+    }
+
+    let length = glb_length(json.len(), bin.len())?;
+
+    let mut writer = BufWriter::new(writer);
+    writer.write_u32::<LittleEndian>(MAGIC)?;
+    writer.write_u32::<LittleEndian>(2)?;
+    writer.write_u32::<LittleEndian>(length)?;
+    writer.write_u32::<LittleEndian>(json.len() as u32)?;
+    writer.write_u32::<LittleEndian>(CHUNK_JSON)?;
+    writer.write_all(&json)?;
+    if !bin.is_empty() {
+        writer.write_u32::<LittleEndian>(bin.len() as u32)?;
+        writer.write_u32::<LittleEndian>(CHUNK_BIN)?;
+        writer.write_all(&bin)?;
+    }
+    writer.flush()
+}
+
+// Total length of a GLB file with the given chunk contents, which must fit into a u32 like the
+// lengths of the chunks themselves.
+fn glb_length(json_len: usize, bin_len: usize) -> Result<u32> {
+    let bin_chunk_len = if bin_len == 0 { 0 } else { 8 + bin_len as u64 };
+    let length = 12 + 8 + json_len as u64 + bin_chunk_len;
+    if length > u64::from(u32::MAX) {
+        return Err(too_large());
+    }
+    Ok(length as u32)
+}
+
+fn too_large() -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::InvalidInput,
+        "mesh is too large for GLB",
+    )
+}
+
+fn mesh_json(mesh: &IndexedMesh, bbox: &BoundingBox, bin_len: usize) -> String {
+    let vertex_bytes = mesh.vertices.len() * 12;
+    let index_bytes = mesh.faces.len() * 12;
+    format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"stl_io"}},"#,
+            r#""scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0}}],"#,
+            r#""meshes":[{{"primitives":[{{"attributes":{{"POSITION":0,"NORMAL":1}},"indices":2}}]}}],"#,
+            r#""buffers":[{{"byteLength":{bin_len}}}],"#,
+            r#""bufferViews":["#,
+            r#"{{"buffer":0,"byteOffset":0,"byteLength":{vertex_bytes},"target":34962}},"#,
+            r#"{{"buffer":0,"byteOffset":{vertex_bytes},"byteLength":{vertex_bytes},"target":34962}},"#,
+            r#"{{"buffer":0,"byteOffset":{index_offset},"byteLength":{index_bytes},"target":34963}}],"#,
+            r#""accessors":["#,
+            r#"{{"bufferView":0,"componentType":5126,"count":{vertices},"type":"VEC3","min":{min:?},"max":{max:?}}},"#,
+            r#"{{"bufferView":1,"componentType":5126,"count":{vertices},"type":"VEC3"}},"#,
+            r#"{{"bufferView":2,"componentType":5125,"count":{indices},"type":"SCALAR"}}]}}"#
+        ),
+        bin_len = bin_len,
+        vertex_bytes = vertex_bytes,
+        index_offset = 2 * vertex_bytes,
+        index_bytes = index_bytes,
+        vertices = mesh.vertices.len(),
+        indices = mesh.faces.len() * 3,
+        min = bbox.min,
+        max = bbox.max,
+    )
+}
+
+// Area weighted vertex normals. Vertices without adjacent area fall back to the normal stored
+// in an adjacent face, or +z for unreferenced vertices, since glTF requires unit normals.
+fn vertex_normals(mesh: &IndexedMesh) -> Vec<[f32; 3]> {
+    let mut sums = vec![[0.; 3]; mesh.vertices.len()];
+    let mut fallback = vec![[0., 0., 1.]; mesh.vertices.len()];
+    for f in &mesh.faces {
+        let n = area_normal(&[
+            to_f64(&mesh.vertices[f.vertices[0]]),
+            to_f64(&mesh.vertices[f.vertices[1]]),
+            to_f64(&mesh.vertices[f.vertices[2]]),
+        ]);
+        for &v in &f.vertices {
+            sums[v] = add(&sums[v], &n);
+            if let Some(n) = normalize(&to_f64(&f.normal)) {
+                fallback[v] = n;
+            }
+        }
+    }
+    sums.iter()
+        .zip(&fallback)
+        .map(|(sum, fallback)| to_f32(&normalize(sum).unwrap_or(*fallback)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use byteorder::{ByteOrder, LittleEndian};
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn write_glb_bunny() {
+        let mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let mut glb = Vec::<u8>::new();
+        write_glb(&mut glb, &mesh).unwrap();
+        assert_eq!(LittleEndian::read_u32(&glb[0..4]), MAGIC);
+        assert_eq!(LittleEndian::read_u32(&glb[8..12]) as usize, glb.len());
+
+        let json_len = LittleEndian::read_u32(&glb[12..16]) as usize;
+        assert_eq!(json_len % 4, 0);
+        assert_eq!(LittleEndian::read_u32(&glb[16..20]), CHUNK_JSON);
+        let json = ::std::str::from_utf8(&glb[20..20 + json_len]).unwrap();
+        assert!(json.contains(&format!(r#""count":{},"type":"SCALAR""#, 99 * 3)));
+
+        let bin = &glb[20 + json_len..];
+        let bin_len = LittleEndian::read_u32(&bin[0..4]) as usize;
+        assert_eq!(LittleEndian::read_u32(&bin[4..8]), CHUNK_BIN);
+        assert_eq!(bin_len, bin.len() - 8);
+        assert_eq!(bin_len, mesh.vertices.len() * 24 + 99 * 12);
+        let normals = &bin[8 + mesh.vertices.len() * 12..];
+        for i in 0..mesh.vertices.len() {
+            let mut n = [0.; 3];
+            LittleEndian::read_f32_into(&normals[i * 12..i * 12 + 12], &mut n);
+            let len = n.iter().map(|c| c * c).sum::<f32>().sqrt();
+            assert!((len - 1.).abs() < 1e-5, "{:?}", n);
+        }
+    }
+
+    #[test]
+    fn write_glb_invalid() {
+        let mut mesh = IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., f32::NAN]],
+            faces: vec![crate::IndexedTriangle {
+                normal: [0., 0., 1.],
+                vertices: [0, 1, 2],
+            }],
+        };
+        let mut glb = Vec::<u8>::new();
+        let error = write_glb(&mut glb, &mesh).unwrap_err();
+        assert_eq!(error.kind(), ::std::io::ErrorKind::InvalidInput);
+        mesh.vertices[2][2] = f32::INFINITY;
+        assert!(write_glb(&mut glb, &mesh).is_err());
+        assert!(glb.is_empty());
+
+        assert_eq!(glb_length(4, 0).unwrap(), 24);
+        assert_eq!(glb_length(4, 12).unwrap(), 44);
+        assert!(glb_length(4, u32::MAX as usize).is_err());
+        assert!(glb_length(u32::MAX as usize - 20, 0).is_ok());
+        assert!(glb_length(u32::MAX as usize - 19, 0).is_err());
+    }
+
+    #[test]
+    fn write_glb_empty() {
+        let mesh = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        let mut glb = Vec::<u8>::new();
+        write_glb(&mut glb, &mesh).unwrap();
+        assert_eq!(LittleEndian::read_u32(&glb[8..12]) as usize, glb.len());
+        assert_eq!(
+            LittleEndian::read_u32(&glb[12..16]) as usize,
+            glb.len() - 20
+        );
+    }
+}
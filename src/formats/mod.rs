@@ -0,0 +1,203 @@
+//! Readers and writers for mesh formats other than STL, conversion between them, and writers
+//! for 2D contours.
+
+pub mod dxf;
+pub mod gltf;
+pub mod obj;
+pub mod off;
+pub mod ply;
+pub mod svg;
+
+use crate::{IndexedMesh, IndexedTriangle, Triangle};
+use std::io::{Read, Result, SeekFrom};
+
+/// A mesh file format recognized by [detect](fn.detect.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Binary STL.
+    BinaryStl,
+    /// Ascii STL.
+    AsciiStl,
+    /// Wavefront OBJ.
+    Obj,
+    /// PLY, ascii or binary.
+    Ply,
+    /// Object File Format.
+    Off,
+}
+
+/// Guesses the format of the mesh in reader from its first bytes and its length.
+/// The reader is rewound to the start afterwards.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n");
+/// assert_eq!(stl_io::formats::detect(&mut reader).unwrap(), stl_io::formats::Format::Off);
+/// ```
+pub fn detect<R: ::std::io::Read + ::std::io::Seek>(reader: &mut R) -> Result<Format> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut head = Vec::new();
+    let maybe_read_error = (&mut *reader).take(512).read_to_end(&mut head);
+    // Seek back to start before evaluating potential read errors.
+    reader.seek(SeekFrom::Start(0))?;
+    maybe_read_error?;
+
+    let text = String::from_utf8_lossy(&head);
+    let first_word = text.split_whitespace().next().unwrap_or("");
+    let binary_stl_len = if head.len() >= 84 {
+        let num_faces = u64::from(u32::from_le_bytes([head[80], head[81], head[82], head[83]]));
+        Some(84 + 50 * num_faces)
+    } else {
+        None
+    };
+    if first_word == "ply" {
+        Ok(Format::Ply)
+    } else if first_word.ends_with("OFF") {
+        Ok(Format::Off)
+    } else if first_word == "solid" && (text.contains("facet") || text.contains("endsolid")) {
+        // Binary STL headers may start with "solid" too, but are not followed by facets.
+        Ok(Format::AsciiStl)
+    } else if binary_stl_len == Some(len) {
+        Ok(Format::BinaryStl)
+    } else if first_word == "solid" {
+        Ok(Format::AsciiStl)
+    } else if !head.contains(&0) && text.lines().any(is_obj_statement) {
+        Ok(Format::Obj)
+    } else if binary_stl_len.is_some_and(|expected| expected <= len) {
+        Ok(Format::BinaryStl)
+    } else {
+        Err(invalid_data("unrecognized mesh format"))
+    }
+}
+
+fn is_obj_statement(line: &str) -> bool {
+    matches!(
+        line.split_whitespace().next(),
+        Some("v") | Some("vn") | Some("vt") | Some("f") | Some("o") | Some("g") | Some("mtllib")
+    )
+}
+
+/// Reads a mesh in any format recognized by [detect](fn.detect.html) and writes it to writer in
+/// the target format.
+///
+/// Normals of faces read from OBJ, PLY and OFF are computed from their vertices, and polygons
+/// are split into triangles. When converting PLY to PLY, vertex and face properties are kept as
+/// [attributes](../attributes/index.html).
+///
+/// ```
+/// use stl_io::formats::{convert, Format};
+/// let mut reader = ::std::io::Cursor::new(b"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n");
+/// let mut stl = Vec::<u8>::new();
+/// convert(&mut reader, &mut stl, Format::BinaryStl).unwrap();
+/// assert_eq!(stl_io::from_bytes(&stl).unwrap().len(), 2);
+/// ```
+pub fn convert<R, W>(reader: &mut R, writer: &mut W, target: Format) -> Result<()>
+where
+    R: ::std::io::Read + ::std::io::Seek,
+    W: ::std::io::Write,
+{
+    let attributed = match detect(reader)? {
+        Format::BinaryStl | Format::AsciiStl => crate::read_stl(reader)?.into(),
+        Format::Obj => obj::read_obj(reader)?.into(),
+        Format::Ply => ply::read_ply_attributed(reader)?,
+        Format::Off => off::read_off(reader)?.into(),
+    };
+    let mesh = &attributed.mesh;
+    match target {
+        Format::BinaryStl => {
+            writer.write_all(&crate::mesh_to_stl_bytes(mesh))?;
+            writer.flush()
+        }
+        Format::AsciiStl => crate::write_ascii_stl(writer, mesh.triangles().iter()),
+        Format::Obj => obj::write_obj(writer, mesh),
+        Format::Ply => ply::write_ply_attributed(writer, &attributed),
+        Format::Off => off::write_off(writer, mesh),
+    }
+}
+
+pub(crate) fn invalid_data<E>(message: E) -> ::std::io::Error
+where
+    E: Into<Box<dyn ::std::error::Error + Send + Sync>>,
+{
+    ::std::io::Error::new(::std::io::ErrorKind::InvalidData, message)
+}
+
+// Appends polygon to mesh as a triangle fan, with normals computed from the vertices.
+pub(crate) fn push_polygon(mesh: &mut IndexedMesh, polygon: &[usize]) -> Result<()> {
+    if polygon.len() < 3 {
+        return Err(invalid_data(format!(
+            "face with {} vertices",
+            polygon.len()
+        )));
+    }
+    if let Some(v) = polygon.iter().find(|&&v| v >= mesh.vertices.len()) {
+        return Err(invalid_data(format!(
+            "face refers to vertex #{}, but there are only {} vertices",
+            v,
+            mesh.vertices.len()
+        )));
+    }
+    for i in 1..polygon.len() - 1 {
+        let vertices = [polygon[0], polygon[i], polygon[i + 1]];
+        let normal = Triangle {
+            normal: [0.; 3],
+            vertices: vertices.map(|v| mesh.vertices[v]),
+        }
+        .normal()
+        .unwrap_or([0.; 3]);
+        mesh.faces.push(IndexedTriangle { normal, vertices });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::assert_meshes_eq;
+    use std::io::Cursor;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+    const BUNNY_99_ASCII: &[u8] = include_bytes!("../testdata/bunny_99_ascii.stl");
+
+    #[test]
+    fn detect_and_convert_round_trip() {
+        let mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        assert_eq!(
+            detect(&mut Cursor::new(BUNNY_99)).unwrap(),
+            Format::BinaryStl
+        );
+        assert_eq!(
+            detect(&mut Cursor::new(BUNNY_99_ASCII)).unwrap(),
+            Format::AsciiStl
+        );
+        let formats = [
+            Format::BinaryStl,
+            Format::AsciiStl,
+            Format::Obj,
+            Format::Ply,
+            Format::Off,
+        ];
+        for &format in &formats {
+            let mut converted = Vec::new();
+            convert(&mut Cursor::new(BUNNY_99), &mut converted, format).unwrap();
+            let mut reader = Cursor::new(&converted);
+            assert_eq!(detect(&mut reader).unwrap(), format);
+            assert_eq!(reader.position(), 0);
+            let mut stl = Vec::new();
+            convert(&mut reader, &mut stl, Format::BinaryStl).unwrap();
+            assert_meshes_eq(&mesh, &crate::parse_stl_bytes(&stl).unwrap(), 0.);
+        }
+    }
+
+    #[test]
+    fn detect_binary_stl_starting_with_solid() {
+        let mut stl = crate::mesh_to_stl_bytes(&crate::parse_stl_bytes(BUNNY_99).unwrap());
+        stl[..6].copy_from_slice(b"solid ");
+        assert_eq!(detect(&mut Cursor::new(&stl)).unwrap(), Format::BinaryStl);
+    }
+
+    #[test]
+    fn detect_unknown() {
+        assert!(detect(&mut Cursor::new(b"\x00\x01\x02")).is_err());
+        assert!(detect(&mut Cursor::new(b"")).is_err());
+    }
+}
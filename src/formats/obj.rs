@@ -0,0 +1,199 @@
+//! [Wavefront OBJ](https://en.wikipedia.org/wiki/Wavefront_.obj_file) input and output.
+
+use super::{invalid_data, push_polygon};
+use crate::mesh::Edge;
+use crate::{IndexedMesh, Winding};
+use std::convert::TryFrom;
+use std::io::{BufRead, BufReader, BufWriter, Result, Write};
+
+/// Reads the vertices and faces of an OBJ file. Everything else, like texture coordinates,
+/// normals, groups and materials, is ignored.
+/// Polygons are split into triangles and face normals are computed from the vertices.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1/1 2/2 -1/3\n");
+/// let mesh = stl_io::formats::obj::read_obj(&mut reader).unwrap();
+/// assert_eq!(mesh.faces[0].vertices, [0, 1, 2]);
+/// assert_eq!(mesh.faces[0].normal, [0., 0., 1.]);
+/// ```
+pub fn read_obj<R: ::std::io::Read>(reader: &mut R) -> Result<IndexedMesh> {
+    let mut mesh = IndexedMesh {
+        vertices: Vec::new(),
+        faces: Vec::new(),
+    };
+    let mut polygon = Vec::new();
+    for (line_number, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+        let error = |what: &str| {
+            invalid_data(format!(
+                "invalid {} in line {}: {:?}",
+                what,
+                line_number + 1,
+                line
+            ))
+        };
+        match tokens.next() {
+            Some("v") => {
+                let mut vertex = [0.; 3];
+                for c in &mut vertex {
+                    *c = tokens
+                        .next()
+                        .and_then(|t| t.parse().ok())
+                        .ok_or_else(|| error("vertex"))?;
+                }
+                mesh.vertices.push(vertex);
+            }
+            Some("f") => {
+                polygon.clear();
+                for t in tokens {
+                    // Only the vertex index matters, not texture coordinate or normal indices.
+                    let index = t
+                        .split('/')
+                        .next()
+                        .and_then(|i| i.parse::<i64>().ok())
+                        .ok_or_else(|| error("face"))?;
+                    // Indices start at 1, negative indices count back from the last vertex.
+                    let index = match index {
+                        i if i > 0 => i - 1,
+                        i if i < 0 => mesh.vertices.len() as i64 + i,
+                        _ => return Err(error("face")),
+                    };
+                    polygon.push(usize::try_from(index).map_err(|_| error("face"))?);
+                }
+                push_polygon(&mut mesh, &polygon)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(mesh)
+}
+
+/// Writes the vertices and faces of mesh as OBJ.
+pub fn write_obj<W: ::std::io::Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    write_obj_with_winding(writer, mesh, Winding::CounterClockwise)
+}
+
+/// Like [write_obj](fn.write_obj.html), but emits the vertices of each face in the given
+/// [Winding](../../enum.Winding.html).
+pub fn write_obj_with_winding<W: ::std::io::Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    winding: Winding,
+) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    write_vertices(&mut writer, mesh)?;
+    let [a, b, c] = winding.order();
+    for f in &mesh.faces {
+        // OBJ indices start at 1.
+        writeln!(
+            writer,
+            "f {} {} {}",
+            f.vertices[a] + 1,
+            f.vertices[b] + 1,
+            f.vertices[c] + 1
+        )?;
+    }
+    writer.flush()
+}
+
+/// Writes the vertices of mesh and edges as OBJ line elements, for viewing wireframes.
+/// Edges are grouped into `boundary`, `crease` and `interior`.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let mut obj = Vec::<u8>::new();
+/// stl_io::formats::obj::write_obj_edges(&mut obj, &mesh, &mesh.edges(0.5)).unwrap();
+/// assert!(String::from_utf8(obj).unwrap().contains("g boundary\nl 1 2\nl 1 3\nl 2 3\n"));
+/// ```
+pub fn write_obj_edges<W: ::std::io::Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    edges: &[Edge],
+) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    write_vertices(&mut writer, mesh)?;
+    for name in &["boundary", "crease", "interior"] {
+        let mut members = edges.iter().filter(|e| group(e) == *name).peekable();
+        if members.peek().is_none() {
+            continue;
+        }
+        writeln!(writer, "g {}", name)?;
+        for e in members {
+            writeln!(writer, "l {} {}", e.vertices[0] + 1, e.vertices[1] + 1)?;
+        }
+    }
+    writer.flush()
+}
+
+fn group(e: &Edge) -> &'static str {
+    if e.boundary {
+        "boundary"
+    } else if e.crease {
+        "crease"
+    } else {
+        "interior"
+    }
+}
+
+fn write_vertices<W: Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    for v in &mesh.vertices {
+        writeln!(writer, "v {} {} {}", v[0], v[1], v[2])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::IndexedTriangle;
+
+    #[test]
+    fn read_obj_quad_and_comments() {
+        let mut reader = ::std::io::Cursor::new(
+            "# square\no square\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0 1\nvn 0 0 1\n\
+             f 1//1 2//1 3//1 4//1\n",
+        );
+        let mesh = read_obj(&mut reader).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(
+            mesh.faces.iter().map(|f| f.vertices).collect::<Vec<_>>(),
+            vec![[0, 1, 2], [0, 2, 3]]
+        );
+        for bad in &[
+            "v 0 0\n",
+            "v 0 0 0\nf 1 2 3\n",
+            "f 0 1 2\n",
+            "v 0 0 0\nf 1 -2 1\n",
+        ] {
+            assert!(
+                read_obj(&mut ::std::io::Cursor::new(bad)).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn write_obj_triangle() {
+        let mesh = IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1.5, 0., 0.], [0., 1., -2.]],
+            faces: vec![IndexedTriangle {
+                normal: [0., 0., 1.],
+                vertices: [0, 1, 2],
+            }],
+        };
+        let mut obj = Vec::<u8>::new();
+        write_obj(&mut obj, &mesh).unwrap();
+        assert_eq!(
+            String::from_utf8(obj).unwrap(),
+            "v 0 0 0\nv 1.5 0 0\nv 0 1 -2\nf 1 2 3\n"
+        );
+        let mut obj = Vec::<u8>::new();
+        write_obj_with_winding(&mut obj, &mesh, Winding::Clockwise).unwrap();
+        assert!(String::from_utf8(obj).unwrap().ends_with("f 1 3 2\n"));
+    }
+}
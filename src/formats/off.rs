@@ -0,0 +1,170 @@
+//! [Object File Format](https://en.wikipedia.org/wiki/OFF_(file_format)) input and output.
+
+use super::{invalid_data, push_polygon};
+use crate::{IndexedMesh, Winding};
+use std::io::{BufRead, BufReader, BufWriter, Result, Write};
+
+/// Reads the vertices and faces of an OFF file. Colors are ignored.
+/// Polygons are split into triangles and face normals are computed from the vertices.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n");
+/// let mesh = stl_io::formats::off::read_off(&mut reader).unwrap();
+/// assert_eq!(mesh.faces[0].normal, [0., 0., 1.]);
+/// ```
+pub fn read_off<R: ::std::io::Read>(reader: &mut R) -> Result<IndexedMesh> {
+    // Non-empty lines with comments removed, split into tokens.
+    let mut lines = BufReader::new(reader)
+        .lines()
+        .map(|line| {
+            line.map(|line| {
+                let content = line.split('#').next().unwrap_or("");
+                content
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+        })
+        .filter(|tokens| tokens.as_ref().map_or(true, |t| !t.is_empty()));
+    let mut next_line = |what: &str| {
+        lines.next().unwrap_or_else(|| {
+            Err(::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                format!("EOF while expecting {}", what),
+            ))
+        })
+    };
+
+    let mut header = next_line("OFF header")?;
+    if !header[0].ends_with("OFF") {
+        return Err(invalid_data("OFF does not start with \"OFF\""));
+    }
+    // The counts may follow the keyword on the same line.
+    let counts = if header.len() > 1 {
+        header.split_off(1)
+    } else {
+        next_line("vertex and face counts")?
+    };
+    let count = |i: usize| {
+        counts
+            .get(i)
+            .and_then(|c| c.parse::<usize>().ok())
+            .ok_or_else(|| invalid_data(format!("invalid OFF counts {:?}", counts)))
+    };
+    let (num_vertices, num_faces) = (count(0)?, count(1)?);
+
+    // Do not reserve memory based on the counts, because they might be bogus.
+    let mut mesh = IndexedMesh {
+        vertices: Vec::new(),
+        faces: Vec::new(),
+    };
+    for _ in 0..num_vertices {
+        let tokens = next_line("vertex")?;
+        let error = || invalid_data(format!("invalid OFF vertex {:?}", tokens));
+        if tokens.len() < 3 {
+            return Err(error());
+        }
+        let mut vertex = [0.; 3];
+        for (c, t) in vertex.iter_mut().zip(&tokens) {
+            *c = t.parse().map_err(|_| error())?;
+        }
+        mesh.vertices.push(vertex);
+    }
+    for _ in 0..num_faces {
+        let tokens = next_line("face")?;
+        let error = || invalid_data(format!("invalid OFF face {:?}", tokens));
+        let n = tokens[0].parse::<usize>().map_err(|_| error())?;
+        if tokens.len() <= n {
+            return Err(error());
+        }
+        let polygon = tokens[1..=n]
+            .iter()
+            .map(|t| t.parse::<usize>().map_err(|_| error()))
+            .collect::<Result<Vec<_>>>()?;
+        push_polygon(&mut mesh, &polygon)?;
+    }
+    Ok(mesh)
+}
+
+/// Writes the vertices and faces of mesh as OFF.
+pub fn write_off<W: ::std::io::Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    write_off_with_winding(writer, mesh, Winding::CounterClockwise)
+}
+
+/// Like [write_off](fn.write_off.html), but emits the vertices of each face in the given
+/// [Winding](../../enum.Winding.html).
+pub fn write_off_with_winding<W: ::std::io::Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    winding: Winding,
+) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "OFF")?;
+    writeln!(writer, "{} {} 0", mesh.vertices.len(), mesh.faces.len())?;
+    for v in &mesh.vertices {
+        writeln!(writer, "{} {} {}", v[0], v[1], v[2])?;
+    }
+    let [a, b, c] = winding.order();
+    for f in &mesh.faces {
+        writeln!(
+            writer,
+            "3 {} {} {}",
+            f.vertices[a], f.vertices[b], f.vertices[c]
+        )?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::IndexedTriangle;
+
+    #[test]
+    fn off_round_trip() {
+        let mesh = IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1.5, 0., 0.], [0., 1., -2.]],
+            faces: vec![IndexedTriangle {
+                normal: [0., 0., 0.],
+                vertices: [0, 1, 2],
+            }],
+        };
+        let mut off = Vec::<u8>::new();
+        write_off(&mut off, &mesh).unwrap();
+        assert_eq!(
+            String::from_utf8(off.clone()).unwrap(),
+            "OFF\n3 1 0\n0 0 0\n1.5 0 0\n0 1 -2\n3 0 1 2\n"
+        );
+        let read = read_off(&mut ::std::io::Cursor::new(off)).unwrap();
+        assert_eq!(read.vertices, mesh.vertices);
+        assert_eq!(read.faces[0].vertices, [0, 1, 2]);
+
+        let mut off = Vec::<u8>::new();
+        write_off_with_winding(&mut off, &mesh, Winding::Clockwise).unwrap();
+        assert!(String::from_utf8(off).unwrap().ends_with(
+            "3 0 2 1
+"
+        ));
+    }
+
+    #[test]
+    fn read_off_comments_colors_and_errors() {
+        let mut reader = ::std::io::Cursor::new(
+            "COFF 4 1 0 # counts on the header line\n\n0 0 0 255 0 0\n1 0 0 255 0 0\n\
+             1 1 0 255 0 0\n0 1 0 255 0 0\n4 0 1 2 3 255 0 0\n",
+        );
+        assert_eq!(read_off(&mut reader).unwrap().faces.len(), 2);
+        for bad in &[
+            "OFF\n3 1 0\n0 0 0\n",
+            "OFF\n1 1 0\n0 0 0\n3 0 1 2\n",
+            "3 1 0\n",
+            "OFF\n18446744073709551615 18446744073709551615 0\n0 0 0\n",
+        ] {
+            assert!(
+                read_off(&mut ::std::io::Cursor::new(bad)).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+}
@@ -0,0 +1,748 @@
+//! [PLY](https://en.wikipedia.org/wiki/PLY_(file_format)) input, ascii PLY output.
+
+use super::{invalid_data, push_polygon};
+use crate::attributes::{check_name, Attribute, AttributedMesh, Domain, Values};
+use crate::mesh::Edge;
+use crate::{IndexedMesh, Winding};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, BufWriter, Read, Result, Write};
+
+/// Reads the vertex positions and faces of an ascii or binary PLY file. Other properties and
+/// elements are skipped.
+/// Polygons are split into triangles and face normals are computed from the vertices.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"ply
+/// format ascii 1.0
+/// element vertex 3
+/// property float x
+/// property float y
+/// property float z
+/// element face 1
+/// property list uchar int vertex_indices
+/// end_header
+/// 0 0 0
+/// 1 0 0
+/// 0 1 0
+/// 3 0 1 2
+/// ");
+/// let mesh = stl_io::formats::ply::read_ply(&mut reader).unwrap();
+/// assert_eq!(mesh.faces[0].normal, [0., 0., 1.]);
+/// ```
+pub fn read_ply<R: ::std::io::Read>(reader: &mut R) -> Result<IndexedMesh> {
+    read_ply_attributed(reader).map(|mesh| mesh.mesh)
+}
+
+/// Like [read_ply](fn.read_ply.html), but keeps the other scalar properties of vertices and
+/// faces as [attributes](../../attributes/index.html). Unsigned integer properties become
+/// `Values::U32`, all others `Values::F32`, and properties named `<name>_x`, `<name>_y` and
+/// `<name>_z` are joined into one `Values::Vec3`. Attributes of polygons are repeated for each
+/// of their triangles.
+///
+/// ```
+/// use stl_io::attributes::{Domain, Values};
+/// let mut reader = ::std::io::Cursor::new(b"ply
+/// format ascii 1.0
+/// element vertex 3
+/// property float x
+/// property float y
+/// property float z
+/// element face 1
+/// property list uchar int vertex_indices
+/// property uchar material
+/// end_header
+/// 0 0 0
+/// 1 0 0
+/// 0 1 0
+/// 3 0 1 2 4
+/// ");
+/// let mesh = stl_io::formats::ply::read_ply_attributed(&mut reader).unwrap();
+/// assert_eq!(mesh.attribute("material", Domain::Face), Some(&Values::U32(vec![4])));
+/// ```
+pub fn read_ply_attributed<R: ::std::io::Read>(reader: &mut R) -> Result<AttributedMesh> {
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.trim_end() != "ply" {
+        return Err(invalid_data("PLY does not start with \"ply\""));
+    }
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                "EOF in PLY header",
+            ));
+        }
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let error = || invalid_data(format!("invalid PLY header line {:?}", line.trim_end()));
+        match tokens.as_slice() {
+            ["format", format, _] => {
+                encoding = Some(match *format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::LittleEndian,
+                    "binary_big_endian" => Encoding::BigEndian,
+                    _ => return Err(error()),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| error())?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => elements
+                .last_mut()
+                .ok_or_else(error)?
+                .properties
+                .push(Property {
+                    name: name.to_string(),
+                    count: Some(scalar(count).ok_or_else(error)?),
+                    item: scalar(item).ok_or_else(error)?,
+                }),
+            ["property", item, name] => {
+                elements
+                    .last_mut()
+                    .ok_or_else(error)?
+                    .properties
+                    .push(Property {
+                        name: name.to_string(),
+                        count: None,
+                        item: scalar(item).ok_or_else(error)?,
+                    })
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error()),
+        }
+    }
+    let mut body = Body {
+        encoding: encoding.ok_or_else(|| invalid_data("PLY header has no format"))?,
+        bytes: Vec::new(),
+        position: 0,
+    };
+    reader.read_to_end(&mut body.bytes)?;
+
+    let mut mesh = IndexedMesh {
+        vertices: Vec::new(),
+        faces: Vec::new(),
+    };
+    let mut polygon = Vec::new();
+    // Raw values of other scalar vertex and face properties, and the PLY face of each triangle.
+    let mut raw: Vec<(Domain, &Property, Vec<f64>)> = Vec::new();
+    let mut face_sources = Vec::new();
+    for element in &elements {
+        let domain = match element.name.as_str() {
+            "vertex" => Some(Domain::Vertex),
+            "face" => Some(Domain::Face),
+            _ => None,
+        };
+        let first_raw = raw.len();
+        if let Some(domain) = domain {
+            for property in &element.properties {
+                if property.count.is_none() && !is_position(element, property) {
+                    // Do not reserve memory based on the count, because it might be bogus.
+                    raw.push((domain, property, Vec::new()));
+                }
+            }
+        }
+        if element.properties.is_empty() {
+            // Elements without properties take no space in the body, so their count is
+            // unchecked.
+            if domain.is_some() && element.count > 0 {
+                return Err(invalid_data(format!(
+                    "PLY element {} has no properties",
+                    element.name
+                )));
+            }
+            continue;
+        }
+        for ei in 0..element.count {
+            let mut vertex = [0.; 3];
+            let mut next_raw = first_raw;
+            for property in &element.properties {
+                let count = match property.count {
+                    Some(count) => body.next(count)? as usize,
+                    None => 1,
+                };
+                polygon.clear();
+                for _ in 0..count {
+                    let value = body.next(property.item)?;
+                    match (element.name.as_str(), property.name.as_str()) {
+                        ("vertex", "x") => vertex[0] = value as f32,
+                        ("vertex", "y") => vertex[1] = value as f32,
+                        ("vertex", "z") => vertex[2] = value as f32,
+                        ("face", "vertex_indices") | ("face", "vertex_index") => {
+                            if value < 0. {
+                                return Err(invalid_data("negative PLY vertex index"));
+                            }
+                            polygon.push(value as usize)
+                        }
+                        _ => {
+                            if domain.is_some() && property.count.is_none() {
+                                raw[next_raw].2.push(value);
+                                next_raw += 1;
+                            }
+                        }
+                    }
+                }
+                if !polygon.is_empty() {
+                    let before = mesh.faces.len();
+                    push_polygon(&mut mesh, &polygon)?;
+                    face_sources.resize(face_sources.len() + mesh.faces.len() - before, ei);
+                }
+            }
+            if element.name == "vertex" {
+                mesh.vertices.push(vertex);
+            }
+        }
+    }
+
+    let find = |domain: Domain, name: &str| {
+        raw.iter()
+            .find(|(d, p, _)| *d == domain && p.name == name)
+            .map(|(_, _, values)| values)
+    };
+    // The name of the vector a property is a component of, with its x, y and z values.
+    let vector = |domain: Domain, name: &str| {
+        let prefix = ["_x", "_y", "_z"]
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))?;
+        let xyz = ["_x", "_y", "_z"].map(|suffix| find(domain, &format!("{}{}", prefix, suffix)));
+        match xyz {
+            [Some(x), Some(y), Some(z)] => Some((prefix.to_string(), [x, y, z])),
+            _ => None,
+        }
+    };
+    let mut attributes = Vec::new();
+    for (domain, property, values) in &raw {
+        let (name, values) = match vector(*domain, &property.name) {
+            Some((prefix, [x, y, z])) => {
+                if !property.name.ends_with("_x") {
+                    continue;
+                }
+                let values = (0..x.len())
+                    .map(|k| [x[k] as f32, y[k] as f32, z[k] as f32])
+                    .collect();
+                (prefix, Values::Vec3(values))
+            }
+            None => {
+                let values = match property.item {
+                    Scalar::U8 | Scalar::U16 | Scalar::U32 => {
+                        Values::U32(values.iter().map(|&v| v as u32).collect())
+                    }
+                    _ => Values::F32(values.iter().map(|&v| v as f32).collect()),
+                };
+                (property.name.clone(), values)
+            }
+        };
+        let values = match domain {
+            Domain::Face => values.select(&face_sources),
+            Domain::Vertex => values,
+        };
+        attributes.push(Attribute {
+            name,
+            domain: *domain,
+            values,
+        });
+    }
+    Ok(AttributedMesh { mesh, attributes })
+}
+
+fn is_position(element: &Element, property: &Property) -> bool {
+    element.name == "vertex" && ["x", "y", "z"].contains(&property.name.as_str())
+}
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+#[derive(Clone, Copy)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+// A scalar property, or a list property if count is set.
+struct Property {
+    name: String,
+    count: Option<Scalar>,
+    item: Scalar,
+}
+
+fn scalar(name: &str) -> Option<Scalar> {
+    Some(match name {
+        "char" | "int8" => Scalar::I8,
+        "uchar" | "uint8" => Scalar::U8,
+        "short" | "int16" => Scalar::I16,
+        "ushort" | "uint16" => Scalar::U16,
+        "int" | "int32" => Scalar::I32,
+        "uint" | "uint32" => Scalar::U32,
+        "float" | "float32" => Scalar::F32,
+        "double" | "float64" => Scalar::F64,
+        _ => return None,
+    })
+}
+
+// The data following the PLY header.
+struct Body {
+    encoding: Encoding,
+    bytes: Vec<u8>,
+    position: usize,
+}
+
+impl Body {
+    fn next(&mut self, scalar: Scalar) -> Result<f64> {
+        let eof = || {
+            ::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                "PLY data ends before all elements were read",
+            )
+        };
+        let size = match scalar {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        };
+        let bytes = &self.bytes[self.position..];
+        match self.encoding {
+            Encoding::Ascii => {
+                let start = bytes
+                    .iter()
+                    .position(|b| !b.is_ascii_whitespace())
+                    .ok_or_else(eof)?;
+                let len = bytes[start..]
+                    .iter()
+                    .position(|b| b.is_ascii_whitespace())
+                    .unwrap_or(bytes.len() - start);
+                let token = String::from_utf8_lossy(&bytes[start..start + len]);
+                self.position += start + len;
+                token
+                    .parse()
+                    .map_err(|_| invalid_data(format!("invalid PLY value {:?}", token)))
+            }
+            Encoding::LittleEndian | Encoding::BigEndian if bytes.len() < size => Err(eof()),
+            Encoding::LittleEndian => {
+                self.position += size;
+                Ok(decode::<LittleEndian>(scalar, bytes))
+            }
+            Encoding::BigEndian => {
+                self.position += size;
+                Ok(decode::<BigEndian>(scalar, bytes))
+            }
+        }
+    }
+}
+
+fn decode<B: ByteOrder>(scalar: Scalar, bytes: &[u8]) -> f64 {
+    match scalar {
+        Scalar::I8 => f64::from(bytes[0] as i8),
+        Scalar::U8 => f64::from(bytes[0]),
+        Scalar::I16 => f64::from(B::read_i16(bytes)),
+        Scalar::U16 => f64::from(B::read_u16(bytes)),
+        Scalar::I32 => f64::from(B::read_i32(bytes)),
+        Scalar::U32 => f64::from(B::read_u32(bytes)),
+        Scalar::F32 => f64::from(B::read_f32(bytes)),
+        Scalar::F64 => B::read_f64(bytes),
+    }
+}
+
+/// Writes the vertices and faces of mesh as ascii PLY.
+pub fn write_ply<W: ::std::io::Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    write_ply_with_winding(writer, mesh, Winding::CounterClockwise)
+}
+
+/// Like [write_ply](fn.write_ply.html), but emits the vertices of each face in the given
+/// [Winding](../../enum.Winding.html).
+pub fn write_ply_with_winding<W: ::std::io::Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    winding: Winding,
+) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    write_header(&mut writer, mesh)?;
+    writeln!(writer, "element face {}", mesh.faces.len())?;
+    writeln!(writer, "property list uchar int vertex_indices")?;
+    writeln!(writer, "end_header")?;
+    write_vertices(&mut writer, mesh)?;
+    let [a, b, c] = winding.order();
+    for f in &mesh.faces {
+        writeln!(
+            writer,
+            "3 {} {} {}",
+            f.vertices[a], f.vertices[b], f.vertices[c]
+        )?;
+    }
+    writer.flush()
+}
+
+/// Like [write_ply](fn.write_ply.html), but also writes the attributes of mesh as properties
+/// of vertices and faces, as read back by [read_ply_attributed](fn.read_ply_attributed.html).
+/// `Values::Vec3` attributes are written as three properties named `<name>_x`, `<name>_y` and
+/// `<name>_z`.
+///
+/// Fails with InvalidInput if an attribute does not have a value for every vertex or face, its
+/// name is not [valid](../../attributes/fn.check_name.html), or two attributes of the same
+/// domain give the same property name.
+pub fn write_ply_attributed<W: ::std::io::Write>(
+    writer: &mut W,
+    mesh: &AttributedMesh,
+) -> Result<()> {
+    write_ply_attributed_with_winding(writer, mesh, Winding::CounterClockwise)
+}
+
+/// Like [write_ply_attributed](fn.write_ply_attributed.html), but emits the vertices of each
+/// face in the given [Winding](../../enum.Winding.html).
+pub fn write_ply_attributed_with_winding<W: ::std::io::Write>(
+    writer: &mut W,
+    mesh: &AttributedMesh,
+    winding: Winding,
+) -> Result<()> {
+    let mut names = HashSet::new();
+    for a in &mesh.attributes {
+        check_name(&a.name, a.domain)?;
+        let properties = match a.values {
+            Values::Vec3(_) => ["_x", "_y", "_z"]
+                .iter()
+                .map(|suffix| format!("{}{}", a.name, suffix))
+                .collect(),
+            _ => vec![a.name.clone()],
+        };
+        for name in properties {
+            if !names.insert((a.domain, name)) {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidInput,
+                    format!("attribute {:?} clashes with another attribute", a.name),
+                ));
+            }
+        }
+        let expected = match a.domain {
+            Domain::Vertex => mesh.mesh.vertices.len(),
+            Domain::Face => mesh.mesh.faces.len(),
+        };
+        if a.values.len() != expected {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                format!("attribute {:?} has {} values", a.name, a.values.len()),
+            ));
+        }
+    }
+    let of = |domain: Domain| mesh.attributes.iter().filter(move |a| a.domain == domain);
+    let mut writer = BufWriter::new(writer);
+    write_header(&mut writer, &mesh.mesh)?;
+    write_property_header(&mut writer, of(Domain::Vertex))?;
+    writeln!(writer, "element face {}", mesh.mesh.faces.len())?;
+    writeln!(writer, "property list uchar int vertex_indices")?;
+    write_property_header(&mut writer, of(Domain::Face))?;
+    writeln!(writer, "end_header")?;
+    for (i, v) in mesh.mesh.vertices.iter().enumerate() {
+        write!(writer, "{} {} {}", v[0], v[1], v[2])?;
+        write_properties(&mut writer, of(Domain::Vertex), i)?;
+    }
+    let [a, b, c] = winding.order();
+    for (i, f) in mesh.mesh.faces.iter().enumerate() {
+        write!(
+            writer,
+            "3 {} {} {}",
+            f.vertices[a], f.vertices[b], f.vertices[c]
+        )?;
+        write_properties(&mut writer, of(Domain::Face), i)?;
+    }
+    writer.flush()
+}
+
+/// Writes the vertices of mesh and edges as ascii PLY edge elements, for viewing wireframes.
+/// Each edge carries `boundary` and `crease` flags.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let mut ply = Vec::<u8>::new();
+/// stl_io::formats::ply::write_ply_edges(&mut ply, &mesh, &mesh.edges(0.5)).unwrap();
+/// assert!(String::from_utf8(ply).unwrap().ends_with("0 1 1 0\n0 2 1 0\n1 2 1 0\n"));
+/// ```
+pub fn write_ply_edges<W: ::std::io::Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    edges: &[Edge],
+) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    write_header(&mut writer, mesh)?;
+    writeln!(writer, "element edge {}", edges.len())?;
+    writeln!(writer, "property int vertex1")?;
+    writeln!(writer, "property int vertex2")?;
+    writeln!(writer, "property uchar boundary")?;
+    writeln!(writer, "property uchar crease")?;
+    writeln!(writer, "end_header")?;
+    write_vertices(&mut writer, mesh)?;
+    for e in edges {
+        writeln!(
+            writer,
+            "{} {} {} {}",
+            e.vertices[0], e.vertices[1], e.boundary as u8, e.crease as u8
+        )?;
+    }
+    writer.flush()
+}
+
+fn write_header<W: Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format ascii 1.0")?;
+    writeln!(writer, "element vertex {}", mesh.vertices.len())?;
+    writeln!(writer, "property float x")?;
+    writeln!(writer, "property float y")?;
+    writeln!(writer, "property float z")
+}
+
+fn write_property_header<'a, W, I>(writer: &mut W, attributes: I) -> Result<()>
+where
+    W: Write,
+    I: Iterator<Item = &'a Attribute>,
+{
+    for a in attributes {
+        match a.values {
+            Values::F32(_) => writeln!(writer, "property float {}", a.name)?,
+            Values::U32(_) => writeln!(writer, "property uint {}", a.name)?,
+            Values::Vec3(_) => {
+                for axis in &["x", "y", "z"] {
+                    writeln!(writer, "property float {}_{}", a.name, axis)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Completes the line of element i with its attribute values.
+fn write_properties<'a, W, I>(writer: &mut W, attributes: I, i: usize) -> Result<()>
+where
+    W: Write,
+    I: Iterator<Item = &'a Attribute>,
+{
+    for a in attributes {
+        match &a.values {
+            Values::F32(v) => write!(writer, " {}", v[i])?,
+            Values::U32(v) => write!(writer, " {}", v[i])?,
+            Values::Vec3(v) => write!(writer, " {} {} {}", v[i][0], v[i][1], v[i][2])?,
+        }
+    }
+    writeln!(writer)
+}
+
+fn write_vertices<W: Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    for v in &mesh.vertices {
+        writeln!(writer, "{} {} {}", v[0], v[1], v[2])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::IndexedTriangle;
+
+    #[test]
+    fn read_binary_ply() {
+        let mut ply = b"ply
+format binary_big_endian 1.0
+comment two triangles and a skipped element
+element vertex 4
+property double x
+property double y
+property double z
+property uchar red
+element face 1
+property list uchar uint vertex_indices
+element extra 1
+property list uchar short values
+end_header
+"
+        .to_vec();
+        for v in &[[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]] {
+            for &c in v {
+                ply.extend_from_slice(&f64::to_be_bytes(c));
+            }
+            ply.push(255);
+        }
+        ply.push(4);
+        for i in 0..4u32 {
+            ply.extend_from_slice(&i.to_be_bytes());
+        }
+        ply.extend_from_slice(&[2, 0, 1, 0, 2]);
+        let mesh = read_ply(&mut ::std::io::Cursor::new(&ply)).unwrap();
+        assert_eq!(mesh.vertices[2], [1., 1., 0.]);
+        assert_eq!(
+            mesh.faces.iter().map(|f| f.vertices).collect::<Vec<_>>(),
+            vec![[0, 1, 2], [0, 2, 3]]
+        );
+        ply.pop();
+        assert!(read_ply(&mut ::std::io::Cursor::new(&ply)).is_err());
+    }
+
+    #[test]
+    fn read_bogus_counts() {
+        let kind = |ply: &str| {
+            read_ply(&mut ::std::io::Cursor::new(ply))
+                .unwrap_err()
+                .kind()
+        };
+        assert_eq!(
+            kind("ply\nformat ascii 1.0\nelement vertex 50000000\nend_header\n"),
+            ::std::io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            kind(
+                "ply\nformat ascii 1.0\nelement vertex 18446744073709551615\n\
+                 property float x\nproperty uchar red\nend_header\n0 0\n"
+            ),
+            ::std::io::ErrorKind::UnexpectedEof
+        );
+        // Other elements without properties are skipped.
+        let ply = "ply\nformat ascii 1.0\nelement extra 18446744073709551615\nend_header\n";
+        assert!(read_ply(&mut ::std::io::Cursor::new(ply)).is_ok());
+    }
+
+    #[test]
+    fn attributes_round_trip() {
+        let mut mesh = AttributedMesh::new(IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+            faces: vec![IndexedTriangle {
+                normal: [0., 0., 1.],
+                vertices: [0, 1, 2],
+            }],
+        });
+        let color = Values::Vec3(vec![[1., 0., 0.], [0., 1., 0.], [0., 0., 0.5]]);
+        mesh.set_attribute("color", Domain::Vertex, color).unwrap();
+        mesh.set_attribute("quality", Domain::Vertex, Values::F32(vec![0.5, 1., -2.]))
+            .unwrap();
+        mesh.set_attribute("material", Domain::Face, Values::U32(vec![3]))
+            .unwrap();
+        let mut ply = Vec::<u8>::new();
+        write_ply_attributed(&mut ply, &mesh).unwrap();
+        let text = String::from_utf8(ply.clone()).unwrap();
+        assert!(text.ends_with("0 1 0 0 0 0.5 -2\n3 0 1 2 3\n"), "{}", text);
+        let read = read_ply_attributed(&mut ::std::io::Cursor::new(&ply)).unwrap();
+        assert_eq!(read, mesh);
+
+        let mut ply = Vec::<u8>::new();
+        write_ply_attributed_with_winding(&mut ply, &mesh, Winding::Clockwise).unwrap();
+        assert!(String::from_utf8(ply).unwrap().ends_with("\n3 0 2 1 3\n"));
+    }
+
+    #[test]
+    fn write_invalid_attribute_names() {
+        let mesh = AttributedMesh::new(IndexedMesh {
+            vertices: vec![[0., 0., 0.]],
+            faces: Vec::new(),
+        });
+        let attribute = |name: &str, values: Values| Attribute {
+            name: name.to_string(),
+            domain: Domain::Vertex,
+            values,
+        };
+        let cases = [
+            vec![attribute("bad name", Values::F32(vec![1.]))],
+            vec![attribute("x", Values::F32(vec![1.]))],
+            vec![
+                attribute("normal", Values::Vec3(vec![[0., 0., 1.]])),
+                attribute("normal_x", Values::F32(vec![1.])),
+            ],
+            vec![
+                attribute("quality", Values::F32(vec![1.])),
+                attribute("quality", Values::U32(vec![1])),
+            ],
+        ];
+        for attributes in &cases {
+            let mesh = AttributedMesh {
+                attributes: attributes.clone(),
+                ..mesh.clone()
+            };
+            let error = write_ply_attributed(&mut Vec::new(), &mesh).unwrap_err();
+            assert_eq!(error.kind(), ::std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn read_polygon_attributes() {
+        let ply = b"ply
+format ascii 1.0
+element vertex 4
+property float x
+property float y
+property float z
+property short weight
+element face 1
+property list uchar int vertex_indices
+property ushort material
+end_header
+0 0 0 -1
+1 0 0 -2
+1 1 0 -3
+0 1 0 -4
+4 0 1 2 3 9
+";
+        let read = read_ply_attributed(&mut ::std::io::Cursor::new(&ply[..])).unwrap();
+        assert_eq!(read.mesh.faces.len(), 2);
+        assert_eq!(
+            read.attribute("material", Domain::Face),
+            Some(&Values::U32(vec![9, 9]))
+        );
+        assert_eq!(
+            read.attribute("weight", Domain::Vertex),
+            Some(&Values::F32(vec![-1., -2., -3., -4.]))
+        );
+    }
+
+    #[test]
+    fn write_ply_triangle() {
+        let mesh = IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1.5, 0., 0.], [0., 1., -2.]],
+            faces: vec![IndexedTriangle {
+                normal: [0., 0., 1.],
+                vertices: [0, 1, 2],
+            }],
+        };
+        let mut ply = Vec::<u8>::new();
+        write_ply(&mut ply, &mesh).unwrap();
+        assert_eq!(
+            String::from_utf8(ply).unwrap(),
+            "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+1.5 0 0
+0 1 -2
+3 0 1 2
+"
+        );
+        let mut ply = Vec::<u8>::new();
+        write_ply_with_winding(&mut ply, &mesh, Winding::Clockwise).unwrap();
+        assert!(String::from_utf8(ply).unwrap().ends_with("3 0 2 1\n"));
+    }
+}
@@ -0,0 +1,125 @@
+//! [SVG](https://www.w3.org/TR/SVG11/) output of 2D contours, e.g. from
+//! [slice](../../mesh/fn.slice.html).
+
+use crate::mesh::Polyline;
+use crate::BoundingBox;
+use std::io::{BufWriter, Result, Write};
+
+/// Writes the x and y coordinates of contours as SVG paths.
+///
+/// One unit is one millimeter, and y points up as in the mesh. Closed polylines are written as
+/// the subpaths of a single path, filled with the even-odd rule, so holes stay empty. Open
+/// polylines are written as paths of their own.
+///
+/// ```
+/// let square = stl_io::mesh::Polyline {
+///     points: vec![[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]],
+///     closed: true,
+/// };
+/// let mut svg = Vec::<u8>::new();
+/// stl_io::formats::svg::write_svg(&mut svg, &[square]).unwrap();
+/// assert!(String::from_utf8(svg).unwrap().contains("d=\"M0 0 L1 0 L1 1 L0 1 Z\""));
+/// ```
+pub fn write_svg<W: ::std::io::Write>(writer: &mut W, contours: &[Polyline]) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    let bbox = BoundingBox::from_vertices(contours.iter().flat_map(|c| c.points.iter().cloned()))
+        .unwrap_or(BoundingBox {
+            min: [0.; 3],
+            max: [0.; 3],
+        });
+    let (width, height) = (bbox.max[0] - bbox.min[0], bbox.max[1] - bbox.min[1]);
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}mm" height="{}mm" viewBox="{} {} {} {}">"#,
+        width, height, bbox.min[0], bbox.min[1], width, height
+    )?;
+    // Mirror y around the middle of the view box, so that it points up.
+    writeln!(
+        writer,
+        r#"<g transform="matrix(1 0 0 -1 0 {})" fill="none" stroke="black" stroke-width="0.1">"#,
+        bbox.min[1] + bbox.max[1]
+    )?;
+    let (closed, open): (Vec<_>, Vec<_>) = contours
+        .iter()
+        .filter(|c| !c.points.is_empty())
+        .partition(|c| c.closed);
+    if !closed.is_empty() {
+        write!(writer, r#"<path d=""#)?;
+        for (i, contour) in closed.iter().enumerate() {
+            if i > 0 {
+                write!(writer, " ")?;
+            }
+            write_points(&mut writer, contour)?;
+            write!(writer, " Z")?;
+        }
+        writeln!(writer, r#"" fill="lightgray" fill-rule="evenodd"/>"#)?;
+    }
+    for contour in open {
+        write!(writer, r#"<path d=""#)?;
+        write_points(&mut writer, contour)?;
+        writeln!(writer, r#""/>"#)?;
+    }
+    writeln!(writer, "</g>")?;
+    writeln!(writer, "</svg>")?;
+    writer.flush()
+}
+
+fn write_points<W: Write>(writer: &mut W, contour: &Polyline) -> Result<()> {
+    for (i, p) in contour.points.iter().enumerate() {
+        let command = if i == 0 { "M" } else { " L" };
+        write!(writer, "{}{} {}", command, p[0], p[1])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write_svg_open_and_closed() {
+        let contours = [
+            Polyline {
+                points: vec![[0., 0., 0.], [4., 0., 0.], [4., 2., 0.]],
+                closed: true,
+            },
+            Polyline {
+                points: vec![[1., 1., 0.], [2., 1.5, 0.]],
+                closed: false,
+            },
+        ];
+        let mut svg = Vec::<u8>::new();
+        write_svg(&mut svg, &contours).unwrap();
+        assert_eq!(
+            String::from_utf8(svg).unwrap(),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="4mm" height="2mm" viewBox="0 0 4 2">
+<g transform="matrix(1 0 0 -1 0 2)" fill="none" stroke="black" stroke-width="0.1">
+<path d="M0 0 L4 0 L4 2 Z" fill="lightgray" fill-rule="evenodd"/>
+<path d="M1 1 L2 1.5"/>
+</g>
+</svg>
+"#
+        );
+    }
+
+    #[test]
+    fn write_svg_with_hole() {
+        let square = |min: f32, max: f32| Polyline {
+            points: vec![
+                [min, min, 0.],
+                [max, min, 0.],
+                [max, max, 0.],
+                [min, max, 0.],
+            ],
+            closed: true,
+        };
+        let mut svg = Vec::<u8>::new();
+        write_svg(&mut svg, &[square(0., 3.), square(1., 2.)]).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        // The hole must be in the same path as the outline for the even-odd rule to apply.
+        assert_eq!(svg.matches("<path").count(), 1);
+        assert!(svg.contains(
+            r#"<path d="M0 0 L3 0 L3 3 L0 3 Z M1 1 L2 1 L2 2 L1 2 Z" fill="lightgray" fill-rule="evenodd"/>"#
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+//! Entry points for the cargo-fuzz targets in fuzz/. Each feeds arbitrary bytes to a group of
+//! readers and only fails by panicking, so errors for malformed input are ignored.
+
+use crate::{AsciiStlReader, BinaryStlReader, ParseMode, SizePolicy, TriangleIterator};
+use std::io::Cursor;
+
+/// Reads data as binary STL, through the streaming reader with every
+/// [SizePolicy](../enum.SizePolicy.html), the byte slice view and the salvaging reader.
+pub fn binary(data: &[u8]) {
+    let mut read = data;
+    if let Ok(mut stl) = BinaryStlReader::create_triangle_iterator(&mut read) {
+        check(stl.to_indexed_triangles());
+    }
+    for &policy in &[
+        SizePolicy::TrustHeader,
+        SizePolicy::Strict,
+        SizePolicy::Truncate,
+    ] {
+        // The reader borrows cursor, so the statement below ends with a semicolon to drop it
+        // first.
+        let mut cursor = Cursor::new(data);
+        if let Ok(mut stl) = BinaryStlReader::with_size_policy(&mut cursor, policy) {
+            check(stl.to_indexed_triangles());
+        };
+    }
+    if let Ok(stl) = crate::from_bytes(data) {
+        let _ = stl.bounding_box();
+        assert_eq!(stl.iter().count(), stl.len());
+    }
+    let _ = crate::salvage_stl(&mut Cursor::new(data));
+}
+
+/// Reads data as ascii STL in both [ParseModes](../enum.ParseMode.html), and through the
+/// format detection of [read_stl](../fn.read_stl.html).
+pub fn ascii(data: &[u8]) {
+    for &mode in &[ParseMode::Strict, ParseMode::Lenient] {
+        // As above, the reader is dropped before read.
+        let mut read = data;
+        if let Ok(mut stl) = AsciiStlReader::with_mode(&mut read, mode) {
+            check(stl.to_indexed_triangles());
+        };
+    }
+    check(crate::read_stl(&mut Cursor::new(data)));
+}
+
+// Meshes that were read successfully must be internally consistent.
+fn check(mesh: ::std::io::Result<crate::IndexedMesh>) {
+    if let Ok(mesh) = mesh {
+        let vertices = mesh.vertices.len();
+        assert!(mesh
+            .faces
+            .iter()
+            .all(|f| f.vertices.iter().all(|&v| v < vertices)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+    const BUNNY_99_ASCII: &[u8] = include_bytes!("testdata/bunny_99_ascii.stl");
+
+    // Truncates and corrupts data in many ways, with a fixed xorshift sequence.
+    fn mutations(data: &[u8]) -> Vec<Vec<u8>> {
+        let mut mutations = Vec::new();
+        for len in (0..data.len()).step_by(37) {
+            mutations.push(data[..len].to_vec());
+        }
+        let mut state = 0x2545_f491_u32;
+        for _ in 0..64 {
+            let mut mutated = data.to_vec();
+            for _ in 0..8 {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let i = state as usize % mutated.len();
+                mutated[i] = (state >> 24) as u8;
+            }
+            mutations.push(mutated);
+        }
+        mutations
+    }
+
+    #[test]
+    fn fuzz_entry_points_survive_mutations() {
+        for data in mutations(BUNNY_99) {
+            binary(&data);
+            ascii(&data);
+        }
+        for data in mutations(BUNNY_99_ASCII) {
+            binary(&data);
+            ascii(&data);
+        }
+    }
+
+    #[test]
+    fn huge_triangle_count() {
+        let mut data = vec![0u8; 84];
+        data[80..].copy_from_slice(&u32::MAX.to_le_bytes());
+        binary(&data);
+    }
+}
@@ -0,0 +1,862 @@
+//! Solids defined by signed distance functions, and their tessellation into meshes.
+//!
+//! Objects are sampled on a regular grid and tessellated with
+//! [isosurface](../mesh/struct.ScalarGrid.html#method.isosurface), so anything that implements
+//! [Object](trait.Object.html) can be turned into a closed mesh. The trait has no
+//! dependencies beyond this crate, so implicit functions from other libraries can be
+//! tessellated by implementing it on a thin wrapper type.
+//!
+//! ```
+//! use stl_io::implicit::{tessellate, Cuboid, Difference, Sphere};
+//!
+//! let cube = Cuboid { center: [0., 0., 0.], size: [2., 2., 2.] };
+//! let ball = Sphere { center: [1., 1., 1.], radius: 1. };
+//! let notched = Difference { a: cube, b: ball };
+//! let mesh = tessellate(&notched, 0.1).unwrap();
+//! assert!(!mesh.faces.is_empty());
+//! ```
+
+use crate::formats::invalid_data;
+use crate::math::{normalize, to_f32};
+use crate::mesh::ScalarGrid;
+use crate::{BoundingBox, IndexedMesh, Normal, Vertex};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::convert::TryFrom;
+use std::io::{BufWriter, Read, Result, Write};
+use std::sync::Arc;
+
+const CHECKPOINT_MAGIC: &[u8; 4] = b"SDFG";
+
+/// Solid given by a signed distance function.
+///
+/// Only [value](#tymethod.value) and [bbox](#tymethod.bbox) are required. The other methods
+/// have default implementations in terms of value, which objects can override with cheaper or
+/// exact ones.
+pub trait Object {
+    /// Signed distance from p to the surface, negative inside. Combinations of objects may
+    /// return a lower bound of the distance instead, which is exact on the surface.
+    fn value(&self, p: &Vertex) -> f32;
+    /// Box enclosing everything inside the object.
+    fn bbox(&self) -> BoundingBox;
+    /// Value at p where it is within slack of 0. Elsewhere any value of the same sign with a
+    /// magnitude of at least slack may be returned, which lets objects skip expensive parts far
+    /// from the surface.
+    fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+        let _ = slack;
+        self.value(p)
+    }
+    /// Outward unit normal of the surface at p, i.e. the normalized gradient of the value, or
+    /// zero where the gradient vanishes.
+    ///
+    /// The default estimates the gradient with central differences, with a step of a
+    /// millionth of the bounding box diagonal.
+    fn normal(&self, p: &Vertex) -> Normal {
+        let bbox = self.bbox();
+        let diagonal = length(&[0, 1, 2].map(|i| bbox.max[i] - bbox.min[i]));
+        let h = (diagonal * 1e-6)
+            .max(f32::EPSILON * length(p))
+            .max(f32::MIN_POSITIVE);
+        let gradient = [0, 1, 2].map(|axis| {
+            let (mut a, mut b) = (*p, *p);
+            a[axis] += h;
+            b[axis] -= h;
+            f64::from(self.value(&a)) - f64::from(self.value(&b))
+        });
+        normalize(&gradient).map_or([0.; 3], |n| to_f32(&n))
+    }
+}
+
+fn length(v: &[f32]) -> f32 {
+    v.iter().map(|c| c * c).sum::<f32>().sqrt()
+}
+
+fn around(center: &Vertex, extent: &[f32; 3]) -> BoundingBox {
+    BoundingBox {
+        min: [0, 1, 2].map(|i| center[i] - extent[i]),
+        max: [0, 1, 2].map(|i| center[i] + extent[i]),
+    }
+}
+
+/// Sphere.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+    /// Center of the sphere.
+    pub center: Vertex,
+    /// Radius of the sphere.
+    pub radius: f32,
+}
+
+impl Object for Sphere {
+    fn value(&self, p: &Vertex) -> f32 {
+        length(&[0, 1, 2].map(|i| p[i] - self.center[i])) - self.radius
+    }
+    fn normal(&self, p: &Vertex) -> Normal {
+        let d = [0, 1, 2].map(|i| f64::from(p[i]) - f64::from(self.center[i]));
+        normalize(&d).map_or([0.; 3], |n| to_f32(&n))
+    }
+    fn bbox(&self) -> BoundingBox {
+        around(&self.center, &[self.radius; 3])
+    }
+}
+
+/// Axis aligned box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cuboid {
+    /// Center of the box.
+    pub center: Vertex,
+    /// Edge lengths along x, y and z.
+    pub size: [f32; 3],
+}
+
+impl Object for Cuboid {
+    fn value(&self, p: &Vertex) -> f32 {
+        let q = [0, 1, 2].map(|i| (p[i] - self.center[i]).abs() - self.size[i] / 2.);
+        let outside = length(&q.map(|c| c.max(0.)));
+        let inside = q[0].max(q[1]).max(q[2]).min(0.);
+        outside + inside
+    }
+    fn bbox(&self) -> BoundingBox {
+        around(&self.center, &self.size.map(|s| s / 2.))
+    }
+}
+
+/// Cylinder with its axis along z.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cylinder {
+    /// Center of the cylinder, halfway along its axis.
+    pub center: Vertex,
+    /// Radius of the cylinder.
+    pub radius: f32,
+    /// Length of the cylinder along z.
+    pub height: f32,
+}
+
+impl Object for Cylinder {
+    fn value(&self, p: &Vertex) -> f32 {
+        let q = [0, 1, 2].map(|i| p[i] - self.center[i]);
+        let d = [length(&q[..2]) - self.radius, q[2].abs() - self.height / 2.];
+        d[0].max(d[1]).min(0.) + length(&d.map(|c| c.max(0.)))
+    }
+    fn bbox(&self) -> BoundingBox {
+        around(&self.center, &[self.radius, self.radius, self.height / 2.])
+    }
+}
+
+/// Torus around an axis along z.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Torus {
+    /// Center of the torus.
+    pub center: Vertex,
+    /// Distance from the axis to the center of the tube.
+    pub major_radius: f32,
+    /// Radius of the tube.
+    pub minor_radius: f32,
+}
+
+impl Object for Torus {
+    fn value(&self, p: &Vertex) -> f32 {
+        let q = [0, 1, 2].map(|i| p[i] - self.center[i]);
+        length(&[length(&q[..2]) - self.major_radius, q[2]]) - self.minor_radius
+    }
+    fn bbox(&self) -> BoundingBox {
+        let outer = self.major_radius + self.minor_radius;
+        around(&self.center, &[outer, outer, self.minor_radius])
+    }
+}
+
+/// Volume inside a or b.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Union<A, B> {
+    /// First object.
+    pub a: A,
+    /// Second object.
+    pub b: B,
+}
+
+impl<A: Object, B: Object> Object for Union<A, B> {
+    fn value(&self, p: &Vertex) -> f32 {
+        self.a.value(p).min(self.b.value(p))
+    }
+    fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+        self.a
+            .approx_value(p, slack)
+            .min(self.b.approx_value(p, slack))
+    }
+    fn bbox(&self) -> BoundingBox {
+        let mut bbox = self.a.bbox();
+        let other = self.b.bbox();
+        bbox.extend(&other.min);
+        bbox.extend(&other.max);
+        bbox
+    }
+}
+
+/// Volume inside both a and b.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Intersection<A, B> {
+    /// First object.
+    pub a: A,
+    /// Second object.
+    pub b: B,
+}
+
+impl<A: Object, B: Object> Object for Intersection<A, B> {
+    fn value(&self, p: &Vertex) -> f32 {
+        self.a.value(p).max(self.b.value(p))
+    }
+    fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+        self.a
+            .approx_value(p, slack)
+            .max(self.b.approx_value(p, slack))
+    }
+    /// The overlap of both boxes, with min above max along some axis if they are disjoint.
+    fn bbox(&self) -> BoundingBox {
+        let (a, b) = (self.a.bbox(), self.b.bbox());
+        BoundingBox {
+            min: [0, 1, 2].map(|i| a.min[i].max(b.min[i])),
+            max: [0, 1, 2].map(|i| a.max[i].min(b.max[i])),
+        }
+    }
+}
+
+/// Volume inside a but not inside b.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Difference<A, B> {
+    /// Object to subtract from.
+    pub a: A,
+    /// Object to subtract.
+    pub b: B,
+}
+
+impl<A: Object, B: Object> Object for Difference<A, B> {
+    fn value(&self, p: &Vertex) -> f32 {
+        self.a.value(p).max(-self.b.value(p))
+    }
+    fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+        self.a
+            .approx_value(p, slack)
+            .max(-self.b.approx_value(p, slack))
+    }
+    fn bbox(&self) -> BoundingBox {
+        self.a.bbox()
+    }
+}
+
+/// Union of a and b with the seam rounded off, by a polynomial smooth minimum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmoothUnion<A, B> {
+    /// First object.
+    pub a: A,
+    /// Second object.
+    pub b: B,
+    /// Distance over which the surfaces blend, 0 gives a sharp union.
+    pub radius: f32,
+}
+
+impl<A: Object, B: Object> Object for SmoothUnion<A, B> {
+    fn value(&self, p: &Vertex) -> f32 {
+        let (a, b) = (self.a.value(p), self.b.value(p));
+        if self.radius <= 0. {
+            return a.min(b);
+        }
+        let h = (0.5 + 0.5 * (b - a) / self.radius).clamp(0., 1.);
+        b + (a - b) * h - self.radius * h * (1. - h)
+    }
+    /// The union of both boxes, grown by a quarter of the radius, the most the blend can add.
+    fn bbox(&self) -> BoundingBox {
+        let mut bbox = Union {
+            a: &self.a,
+            b: &self.b,
+        }
+        .bbox();
+        let grow = self.radius.max(0.) / 4.;
+        bbox.min = bbox.min.map(|c| c - grow);
+        bbox.max = bbox.max.map(|c| c + grow);
+        bbox
+    }
+}
+
+impl<O: Object + ?Sized> Object for &O {
+    fn value(&self, p: &Vertex) -> f32 {
+        (**self).value(p)
+    }
+    fn bbox(&self) -> BoundingBox {
+        (**self).bbox()
+    }
+    fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+        (**self).approx_value(p, slack)
+    }
+    fn normal(&self, p: &Vertex) -> Normal {
+        (**self).normal(p)
+    }
+}
+
+impl<O: Object + ?Sized> Object for Box<O> {
+    fn value(&self, p: &Vertex) -> f32 {
+        (**self).value(p)
+    }
+    fn bbox(&self) -> BoundingBox {
+        (**self).bbox()
+    }
+    fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+        (**self).approx_value(p, slack)
+    }
+    fn normal(&self, p: &Vertex) -> Normal {
+        (**self).normal(p)
+    }
+}
+
+impl<O: Object + ?Sized> Object for Arc<O> {
+    fn value(&self, p: &Vertex) -> f32 {
+        (**self).value(p)
+    }
+    fn bbox(&self) -> BoundingBox {
+        (**self).bbox()
+    }
+    fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+        (**self).approx_value(p, slack)
+    }
+    fn normal(&self, p: &Vertex) -> Normal {
+        (**self).normal(p)
+    }
+}
+
+/// Samples object on a grid with the given spacing, covering its bounding box and one more
+/// sample on every side, and tessellates the surface where the value is 0.
+///
+/// The object is only borrowed, so it can be tessellated at several resolutions, or shared
+/// between threads in an `Arc<dyn Object + Send + Sync>`.
+///
+/// The result is closed. Details smaller than spacing are lost. Samples are taken with
+/// [approx_value](trait.Object.html#method.approx_value) and a slack of spacing, as the surface
+/// only passes between samples that are both within spacing of it.
+///
+/// Returns None if spacing is not positive and finite, the bounding box is empty or not
+/// finite, or the grid would not fit in memory.
+pub fn tessellate<O: Object + ?Sized>(object: &O, spacing: f32) -> Option<IndexedMesh> {
+    let mut grid = sampling_grid(object, spacing)?;
+    sample(object, &mut grid, usize::MAX);
+    Some(grid.isosurface(0.))
+}
+
+/// The grid that [tessellate](fn.tessellate.html) samples object on, without any samples yet.
+///
+/// Together with [sample](fn.sample.html) and [write_checkpoint](fn.write_checkpoint.html), this
+/// splits a long tessellation into steps that can be saved and resumed.
+///
+/// Returns None if spacing is not positive and finite, the bounding box is empty or not
+/// finite, or the values of the grid would not fit in memory.
+pub fn sampling_grid<O: Object + ?Sized>(object: &O, spacing: f32) -> Option<ScalarGrid> {
+    if !spacing.is_finite() || spacing <= 0. {
+        return None;
+    }
+    let bbox = finite_bbox(object)?;
+    grid_from(&bbox, bbox.min.map(|c| c - spacing), spacing)
+}
+
+/// Tessellates object like [tessellate](fn.tessellate.html) once for each of spacings, as
+/// levels of detail, and returns the meshes in the same order.
+///
+/// The grids of all levels start at the same corner, one of the largest spacings beyond the
+/// bounding box. Going from coarse to fine, a level whose spacing divides that of the next
+/// coarser level exactly takes the samples the two grids share from it, instead of sampling
+/// object again. With spacings halving from level to level, this saves an eighth of the
+/// samples of every level but the coarsest.
+///
+/// Returns None under the same conditions as tessellate, for any of the spacings.
+///
+/// ```
+/// use stl_io::implicit::{tessellate_lods, Sphere};
+///
+/// let sphere = Sphere { center: [0., 0., 0.], radius: 1. };
+/// let lods = tessellate_lods(&sphere, &[0.4, 0.2, 0.1]).unwrap();
+/// assert_eq!(lods.len(), 3);
+/// assert!(lods[0].faces.len() < lods[2].faces.len());
+/// ```
+pub fn tessellate_lods<O: Object + ?Sized>(
+    object: &O,
+    spacings: &[f32],
+) -> Option<Vec<IndexedMesh>> {
+    if spacings.iter().any(|s| !s.is_finite() || *s <= 0.) {
+        return None;
+    }
+    let bbox = finite_bbox(object)?;
+    let mut order = (0..spacings.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| spacings[b].total_cmp(&spacings[a]));
+    let largest = order.first().map_or(0., |&i| spacings[i]);
+    let origin = bbox.min.map(|c| c - largest);
+    let grids = order
+        .iter()
+        .map(|&i| grid_from(&bbox, origin, spacings[i]))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut meshes = vec![None; spacings.len()];
+    let mut coarser: Option<ScalarGrid> = None;
+    for (mut grid, &i) in grids.into_iter().zip(&order) {
+        let [nx, ny, _] = grid.size;
+        let ratio = coarser.as_ref().and_then(|c| {
+            let k = (c.spacing / grid.spacing).round();
+            (k >= 1. && k * grid.spacing == c.spacing).then_some(k as usize)
+        });
+        let total = grid.size.iter().product::<usize>();
+        grid.values.reserve(total);
+        for index in 0..total {
+            let (x, y, z) = (index % nx, index / nx % ny, index / (nx * ny));
+            let shared = match (&coarser, ratio) {
+                (Some(c), Some(k)) if x % k == 0 && y % k == 0 && z % k == 0 => {
+                    let (x, y, z) = (x / k, y / k, z / k);
+                    if x < c.size[0] && y < c.size[1] && z < c.size[2] {
+                        Some(c.value(x, y, z))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+            let value = shared
+                .unwrap_or_else(|| object.approx_value(&grid.position(x, y, z), grid.spacing));
+            grid.values.push(value);
+        }
+        meshes[i] = Some(grid.isosurface(0.));
+        coarser = Some(grid);
+    }
+    meshes.into_iter().collect()
+}
+
+// Bounding box of object, if it is not empty and finite.
+fn finite_bbox<O: Object + ?Sized>(object: &O) -> Option<BoundingBox> {
+    let bbox = object.bbox();
+    if (0..3).any(|i| !(bbox.min[i].is_finite() && bbox.max[i].is_finite()))
+        || (0..3).any(|i| bbox.min[i] > bbox.max[i])
+    {
+        return None;
+    }
+    Some(bbox)
+}
+
+// Empty grid from origin to at least one spacing beyond the far corner of bbox, if its values
+// fit in memory.
+fn grid_from(bbox: &BoundingBox, origin: Vertex, spacing: f32) -> Option<ScalarGrid> {
+    let mut size = [0; 3];
+    for i in 0..3 {
+        let n = (f64::from(bbox.max[i]) - f64::from(origin[i])) / f64::from(spacing);
+        let n = n.ceil() + 2.;
+        if n.is_nan() || n > usize::MAX as f64 {
+            return None;
+        }
+        size[i] = n as usize;
+    }
+    ScalarGrid::samples(&size)?;
+    Some(ScalarGrid {
+        origin,
+        spacing,
+        size,
+        values: Vec::new(),
+    })
+}
+
+/// Appends up to count samples of object to the values of grid, in the order of the grid, and
+/// returns whether the grid is complete. Nothing is sampled if the values of the grid would
+/// not fit in memory.
+///
+/// The grid is usually made by [sampling_grid](fn.sampling_grid.html) or read back by
+/// [read_checkpoint](fn.read_checkpoint.html), and a complete grid is tessellated with
+/// [isosurface](../mesh/struct.ScalarGrid.html#method.isosurface) at level 0.
+pub fn sample<O: Object + ?Sized>(object: &O, grid: &mut ScalarGrid, count: usize) -> bool {
+    let [nx, ny, _] = grid.size;
+    let total = match ScalarGrid::samples(&grid.size) {
+        Some(total) => total,
+        None => return false,
+    };
+    let end = total.min(grid.values.len().saturating_add(count));
+    grid.values.reserve(end.saturating_sub(grid.values.len()));
+    for i in grid.values.len()..end {
+        let p = grid.position(i % nx, i / nx % ny, i / (nx * ny));
+        let value = object.approx_value(&p, grid.spacing);
+        grid.values.push(value);
+    }
+    grid.values.len() >= total
+}
+
+/// Writes a possibly incomplete grid, to be resumed with [read_checkpoint](fn.read_checkpoint.html).
+///
+/// The format is the magic bytes `SDFG`, the origin and spacing as little endian f32, the size
+/// and the number of values as little endian u64, and the values as little endian f32.
+///
+/// ```
+/// use stl_io::implicit::{read_checkpoint, sample, sampling_grid, write_checkpoint, Sphere};
+///
+/// let sphere = Sphere { center: [0., 0., 0.], radius: 1. };
+/// let mut grid = sampling_grid(&sphere, 0.1).unwrap();
+/// assert!(!sample(&sphere, &mut grid, 1000));
+/// let mut checkpoint = Vec::new();
+/// write_checkpoint(&mut checkpoint, &grid).unwrap();
+///
+/// let mut resumed = read_checkpoint(&mut checkpoint.as_slice()).unwrap();
+/// assert!(sample(&sphere, &mut resumed, usize::MAX));
+/// let mesh = resumed.isosurface(0.);
+/// assert!(!mesh.faces.is_empty());
+/// ```
+pub fn write_checkpoint<W: ::std::io::Write>(writer: &mut W, grid: &ScalarGrid) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writer.write_all(CHECKPOINT_MAGIC)?;
+    for &c in grid.origin.iter().chain(Some(&grid.spacing)) {
+        writer.write_f32::<LittleEndian>(c)?;
+    }
+    for &n in grid.size.iter().chain(Some(&grid.values.len())) {
+        writer.write_u64::<LittleEndian>(n as u64)?;
+    }
+    for &value in &grid.values {
+        writer.write_f32::<LittleEndian>(value)?;
+    }
+    writer.flush()
+}
+
+/// Reads a grid written by [write_checkpoint](fn.write_checkpoint.html).
+///
+/// Fails with InvalidData if the magic bytes are wrong, the origin or spacing is not finite,
+/// the spacing is not positive, or there are more values than the size of the grid, and with
+/// UnexpectedEof if the data is truncated.
+pub fn read_checkpoint<R: ::std::io::Read>(reader: &mut R) -> Result<ScalarGrid> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != *CHECKPOINT_MAGIC {
+        return Err(invalid_data("not a checkpoint"));
+    }
+    let mut floats = [0.; 4];
+    reader.read_f32_into::<LittleEndian>(&mut floats)?;
+    let mut counts = [0; 4];
+    reader.read_u64_into::<LittleEndian>(&mut counts)?;
+    let counts = counts
+        .iter()
+        .map(|&n| usize::try_from(n).map_err(|_| invalid_data("checkpoint too large")))
+        .collect::<Result<Vec<_>>>()?;
+    let total = counts[..3]
+        .iter()
+        .try_fold(1usize, |total, &n| total.checked_mul(n))
+        .ok_or_else(|| invalid_data("checkpoint too large"))?;
+    if counts[3] > total {
+        return Err(invalid_data("more values than grid samples"));
+    }
+    if !floats.iter().all(|c| c.is_finite()) || floats[3] <= 0. {
+        return Err(invalid_data("invalid checkpoint origin or spacing"));
+    }
+    // Read through take, so a corrupt count does not allocate before the data runs out.
+    let mut bytes = Vec::new();
+    let len = counts[3]
+        .checked_mul(4)
+        .ok_or_else(|| invalid_data("checkpoint too large"))?;
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::UnexpectedEof,
+            "truncated checkpoint",
+        ));
+    }
+    let mut values = vec![0.; counts[3]];
+    LittleEndian::read_f32_into(&bytes, &mut values);
+    Ok(ScalarGrid {
+        origin: [floats[0], floats[1], floats[2]],
+        spacing: floats[3],
+        size: [counts[0], counts[1], counts[2]],
+        values,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::{norm, sub, to_f64};
+    use crate::testing::{assert_closed, volume};
+    use std::cell::Cell;
+    use std::f64::consts::PI;
+    use std::io::ErrorKind;
+
+    fn assert_volume<O: Object>(object: &O, expected: f64) {
+        let mesh = tessellate(object, 0.05).unwrap();
+        assert_closed(&mesh);
+        let v = volume(&mesh);
+        assert!((v - expected).abs() < 0.02 * expected, "{} {}", v, expected);
+    }
+
+    #[test]
+    fn primitive_values() {
+        let sphere = Sphere {
+            center: [1., 0., 0.],
+            radius: 1.,
+        };
+        assert_eq!(sphere.value(&[1., 0., 0.]), -1.);
+        assert_eq!(sphere.value(&[4., 0., 0.]), 2.);
+        let cube = Cuboid {
+            center: [0., 0., 0.],
+            size: [2., 2., 2.],
+        };
+        assert_eq!(cube.value(&[0., 0., 0.5]), -0.5);
+        assert_eq!(cube.value(&[4., 5., 0.]), 5.);
+        let cylinder = Cylinder {
+            center: [0., 0., 0.],
+            radius: 1.,
+            height: 2.,
+        };
+        assert_eq!(cylinder.value(&[0., 3., 0.]), 2.);
+        assert_eq!(cylinder.value(&[0., 0., -3.]), 2.);
+        let torus = Torus {
+            center: [0., 0., 1.],
+            major_radius: 2.,
+            minor_radius: 0.5,
+        };
+        assert_eq!(torus.value(&[0., 2., 1.]), -0.5);
+        assert_eq!(torus.value(&[0., 0., 1.]), 1.5);
+        assert_eq!(torus.bbox().max, [2.5, 2.5, 1.5]);
+    }
+
+    #[test]
+    fn tessellated_volumes() {
+        let ball = |x: f32| Sphere {
+            center: [x, 0., 0.],
+            radius: 1.,
+        };
+        let sphere_volume = 4. / 3. * PI;
+        assert_volume(&ball(0.), sphere_volume);
+        assert_volume(
+            &Cylinder {
+                center: [0., 0., 0.],
+                radius: 1.,
+                height: 2.,
+            },
+            2. * PI,
+        );
+        assert_volume(
+            &Torus {
+                center: [0., 0., 0.],
+                major_radius: 1.,
+                minor_radius: 0.4,
+            },
+            2. * PI * PI * 0.16,
+        );
+        // Two balls overlapping in a lens of volume 5π / 12.
+        let lens = 5. * PI / 12.;
+        let (a, b) = (ball(0.), ball(1.));
+        assert_volume(&Union { a, b }, 2. * sphere_volume - lens);
+        assert_volume(&Intersection { a, b }, lens);
+        assert_volume(&Difference { a, b }, sphere_volume - lens);
+        let sharp = volume(&tessellate(&Union { a, b }, 0.05).unwrap());
+        let smooth = volume(&tessellate(&SmoothUnion { a, b, radius: 0.5 }, 0.05).unwrap());
+        assert!(smooth > sharp);
+    }
+
+    #[test]
+    fn empty_objects() {
+        let a = Sphere {
+            center: [0., 0., 0.],
+            radius: 1.,
+        };
+        let b = Sphere {
+            center: [5., 0., 0.],
+            radius: 1.,
+        };
+        assert_eq!(tessellate(&Intersection { a, b }, 0.1), None);
+        assert_eq!(tessellate(&a, 0.), None);
+        assert_eq!(tessellate(&a, f32::INFINITY), None);
+        let huge = Sphere {
+            center: [0., 0., 0.],
+            radius: 1e30,
+        };
+        assert_eq!(sampling_grid(&huge, 1e-3), None);
+        assert_eq!(sampling_grid(&huge, 1e29).unwrap().size, [23; 3]);
+        let mut grid = sampling_grid(&a, 0.5).unwrap();
+        grid.size = [usize::MAX, 2, 1];
+        assert!(!sample(&a, &mut grid, 10));
+        assert!(grid.values.is_empty());
+        let boxed: Box<dyn Object> = Box::new(a);
+        assert!(!tessellate(&boxed, 0.2).unwrap().faces.is_empty());
+    }
+
+    #[test]
+    fn levels_of_detail() {
+        struct Counting {
+            sphere: Sphere,
+            samples: Cell<usize>,
+        }
+        impl Object for Counting {
+            fn value(&self, p: &Vertex) -> f32 {
+                self.samples.set(self.samples.get() + 1);
+                self.sphere.value(p)
+            }
+            fn bbox(&self) -> BoundingBox {
+                self.sphere.bbox()
+            }
+        }
+        let object = Counting {
+            sphere: Sphere {
+                center: [0., 0., 0.],
+                radius: 1.,
+            },
+            samples: Cell::new(0),
+        };
+        let spacings = [0.1, 0.4, 0.2];
+        let lods = tessellate_lods(&object, &spacings).unwrap();
+
+        // Each level matches sampling its grid directly, with fewer samples in total.
+        let bbox = object.bbox();
+        let mut total = 0;
+        for (mesh, &spacing) in lods.iter().zip(&spacings) {
+            assert_closed(mesh);
+            let mut grid = grid_from(&bbox, bbox.min.map(|c| c - 0.4), spacing).unwrap();
+            assert!(sample(&object.sphere, &mut grid, usize::MAX));
+            assert_eq!(*mesh, grid.isosurface(0.));
+            total += grid.values.len();
+        }
+        let shared = total - object.samples.get();
+        assert!(shared > total / 10, "{} of {}", shared, total);
+
+        assert_eq!(
+            tessellate_lods(&object.sphere, &[0.1]).unwrap(),
+            vec![tessellate(&object.sphere, 0.1).unwrap()]
+        );
+        assert_eq!(tessellate_lods(&object.sphere, &[]), Some(Vec::new()));
+        assert_eq!(tessellate_lods(&object.sphere, &[0.1, 0.]), None);
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let object = Torus {
+            center: [0., 0., 0.],
+            major_radius: 1.,
+            minor_radius: 0.3,
+        };
+        let mut grid = sampling_grid(&object, 0.1).unwrap();
+        let mut checkpoints = 0;
+        while !sample(&object, &mut grid, 500) {
+            let mut bytes = Vec::new();
+            write_checkpoint(&mut bytes, &grid).unwrap();
+            let resumed = read_checkpoint(&mut bytes.as_slice()).unwrap();
+            assert_eq!(resumed, grid);
+            grid = resumed;
+            checkpoints += 1;
+        }
+        assert!(checkpoints > 2);
+        assert!(sample(&object, &mut grid, 0));
+        assert_eq!(grid.isosurface(0.), tessellate(&object, 0.1).unwrap());
+    }
+
+    #[test]
+    fn invalid_checkpoints() {
+        let mut grid = sampling_grid(
+            &Sphere {
+                center: [0., 0., 0.],
+                radius: 1.,
+            },
+            0.5,
+        )
+        .unwrap();
+        grid.values = vec![1.; 10];
+        let mut bytes = Vec::new();
+        write_checkpoint(&mut bytes, &grid).unwrap();
+        assert_eq!(bytes.len(), 4 + 16 + 32 + 40);
+
+        let kind = |bytes: &[u8]| read_checkpoint(&mut &bytes[..]).unwrap_err().kind();
+        assert_eq!(kind(&bytes[..bytes.len() - 1]), ErrorKind::UnexpectedEof);
+        assert_eq!(kind(&bytes[..30]), ErrorKind::UnexpectedEof);
+        let mut wrong = bytes.clone();
+        wrong[0] = b'X';
+        assert_eq!(kind(&wrong), ErrorKind::InvalidData);
+        // A value count beyond the grid size.
+        let mut wrong = bytes.clone();
+        wrong[44..52].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(kind(&wrong), ErrorKind::InvalidData);
+        // A value count within the grid size, but too large to read.
+        let mut wrong = bytes[..52].to_vec();
+        let size = u64::from(u32::MAX).to_le_bytes();
+        wrong[20..28].copy_from_slice(&size);
+        wrong[28..36].copy_from_slice(&size);
+        wrong[44..52].copy_from_slice(&(1u64 << 62).to_le_bytes());
+        assert_eq!(kind(&wrong), ErrorKind::InvalidData);
+        for spacing in &[0., -1., f32::NAN, f32::INFINITY] {
+            let mut wrong = bytes.clone();
+            wrong[16..20].copy_from_slice(&spacing.to_le_bytes());
+            assert_eq!(kind(&wrong), ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let shared: Arc<dyn Object + Send + Sync> = Arc::new(Union {
+            a: Sphere {
+                center: [0., 0., 0.],
+                radius: 1.,
+            },
+            b: Cuboid {
+                center: [1., 0., 0.],
+                size: [1., 1., 1.],
+            },
+        });
+        let meshes = [0.2, 0.1, 0.05]
+            .iter()
+            .map(|&spacing| {
+                let object = Arc::clone(&shared);
+                std::thread::spawn(move || tessellate(&object, spacing).unwrap())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        assert!(meshes[0].faces.len() < meshes[1].faces.len());
+        assert!(meshes[1].faces.len() < meshes[2].faces.len());
+        assert_eq!(meshes[2], tessellate(&*shared, 0.05).unwrap());
+    }
+
+    // Skips the expensive part of a value far from the surface, as the trait allows.
+    struct Coarse(Sphere);
+
+    impl Object for Coarse {
+        fn value(&self, p: &Vertex) -> f32 {
+            self.0.value(p)
+        }
+        fn bbox(&self) -> BoundingBox {
+            self.0.bbox()
+        }
+        fn approx_value(&self, p: &Vertex, slack: f32) -> f32 {
+            let bbox = self.bbox();
+            let outside = (0..3)
+                .map(|i| (bbox.min[i] - p[i]).max(p[i] - bbox.max[i]))
+                .fold(f32::NEG_INFINITY, f32::max);
+            if outside > slack {
+                outside
+            } else {
+                self.value(p)
+            }
+        }
+    }
+
+    #[test]
+    fn normals_and_approximate_values() {
+        let sphere = Sphere {
+            center: [1., 0., 0.],
+            radius: 1.,
+        };
+        assert_eq!(sphere.normal(&[1., 3., 0.]), [0., 1., 0.]);
+        assert_eq!(sphere.normal(&[1., 0., 0.]), [0., 0., 0.]);
+        let cube = Cuboid {
+            center: [0., 0., 0.],
+            size: [2., 2., 2.],
+        };
+        // Central differences through the default implementation.
+        let n = to_f64(&cube.normal(&[0.2, -1., 0.3]));
+        assert!(norm(&sub(&n, &[0., -1., 0.])) < 1e-3, "{:?}", n);
+        let n = to_f64(&Union { a: cube, b: sphere }.normal(&[0., 0., 1.5]));
+        assert!(norm(&sub(&n, &[0., 0., 1.])) < 1e-3, "{:?}", n);
+
+        let coarse = Union {
+            a: Coarse(sphere),
+            b: cube,
+        };
+        assert_eq!(coarse.approx_value(&[5., 0., 0.], 0.5), 3.);
+        assert_eq!(
+            coarse.approx_value(&[2.2, 0., 0.], 0.5),
+            sphere.value(&[2.2, 0., 0.])
+        );
+        let exact = tessellate(&Union { a: sphere, b: cube }, 0.05).unwrap();
+        assert_eq!(tessellate(&coarse, 0.05).unwrap(), exact);
+    }
+}
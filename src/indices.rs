@@ -0,0 +1,120 @@
+//! Compact index buffers, e.g. for uploading meshes to a GPU.
+//!
+//! Everything in here only needs `core` and `alloc`, so it is available without the `std`
+//! feature.
+
+use crate::IndexedMesh;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/// Error returned by [index_buffer](struct.IndexedMesh.html#method.index_buffer) if a vertex
+/// index does not fit into the requested index type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexOverflowError {
+    /// The first vertex index that did not fit.
+    pub index: usize,
+    /// Name of the requested index type.
+    pub index_type: &'static str,
+}
+
+impl fmt::Display for IndexOverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "vertex index {} does not fit into {}",
+            self.index, self.index_type
+        )
+    }
+}
+
+impl core::error::Error for IndexOverflowError {}
+
+#[cfg(feature = "std")]
+impl From<IndexOverflowError> for ::std::io::Error {
+    fn from(e: IndexOverflowError) -> ::std::io::Error {
+        ::std::io::Error::new(::std::io::ErrorKind::InvalidInput, e)
+    }
+}
+
+impl IndexedMesh {
+    /// Vertex indices of all faces, three per face in order, converted to a compact type like
+    /// u32 or u16.
+    ///
+    /// Fails if any index does not fit into T. Meshes with more vertices may still succeed if
+    /// the faces do not use the vertices with large indices.
+    ///
+    /// ```
+    /// let mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+    ///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+    /// };
+    /// assert_eq!(mesh.index_buffer::<u16>().unwrap(), vec![0, 1, 2]);
+    /// ```
+    pub fn index_buffer<T: TryFrom<usize>>(&self) -> Result<Vec<T>, IndexOverflowError> {
+        let mut buffer = Vec::with_capacity(3 * self.faces.len());
+        for f in &self.faces {
+            for &index in &f.vertices {
+                buffer.push(T::try_from(index).map_err(|_| IndexOverflowError {
+                    index,
+                    index_type: core::any::type_name::<T>(),
+                })?);
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::IndexedTriangle;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+
+    #[test]
+    fn bunny_index_buffers() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let expected = bunny
+            .faces
+            .iter()
+            .flat_map(|f| f.vertices.to_vec())
+            .collect::<Vec<_>>();
+        let u32s = bunny.index_buffer::<u32>().unwrap();
+        let u8s = bunny.index_buffer::<u8>().unwrap();
+        assert_eq!(u32s.len(), 3 * 99);
+        assert!(u32s.iter().zip(&expected).all(|(&a, &b)| a as usize == b));
+        assert!(u8s.iter().zip(&expected).all(|(&a, &b)| a as usize == b));
+    }
+
+    #[test]
+    fn index_overflow() {
+        let mesh = IndexedMesh {
+            vertices: vec![[0., 0., 0.]; 70000],
+            faces: vec![
+                IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [0, 1, 2],
+                },
+                IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [3, 65535, 65536],
+                },
+            ],
+        };
+        assert_eq!(mesh.index_buffer::<u32>().unwrap()[5], 65536);
+        let error = mesh.index_buffer::<u16>().unwrap_err();
+        assert_eq!(
+            error,
+            IndexOverflowError {
+                index: 65536,
+                index_type: "u16"
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "vertex index 65536 does not fit into u16"
+        );
+        let error = ::std::io::Error::from(error);
+        assert_eq!(error.kind(), ::std::io::ErrorKind::InvalidInput);
+    }
+}
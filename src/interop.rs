@@ -0,0 +1,105 @@
+//! Conversions between meshes and the vector types of math crates.
+//!
+//! [Vertex](type.Vertex.html) and [Normal](type.Normal.html) are plain `[f32; 3]` arrays, which
+//! glam, nalgebra, cgmath and mint all convert from and into with `From`. The helpers here
+//! apply these conversions to whole triangles and meshes, so no feature has to be enabled.
+
+use crate::{IndexedMesh, Triangle, Vertex};
+use alloc::vec::Vec;
+
+impl Triangle {
+    /// Vertices converted to another vector or point type.
+    ///
+    /// ```
+    /// let t = stl_io::Triangle {
+    ///     normal: [0., 0., 1.],
+    ///     vertices: [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+    /// };
+    /// let [a, b, c] = t.vertices_as::<mint::Point3<f32>>();
+    /// assert_eq!(b, mint::Point3 { x: 1., y: 0., z: 0. });
+    /// ```
+    pub fn vertices_as<V: From<Vertex>>(&self) -> [V; 3] {
+        self.vertices.map(V::from)
+    }
+}
+
+impl IndexedMesh {
+    /// Vertices converted to another vector or point type, e.g. `glam::Vec3`,
+    /// `nalgebra::Point3<f32>`, `cgmath::Point3<f32>` or `mint::Point3<f32>`.
+    ///
+    /// ```
+    /// let mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+    ///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+    /// };
+    /// let vertices = mesh.vertices_as::<glam::Vec3>();
+    /// assert_eq!(vertices[1], glam::Vec3::X);
+    /// ```
+    pub fn vertices_as<V: From<Vertex>>(&self) -> Vec<V> {
+        self.vertices.iter().map(|&v| V::from(v)).collect()
+    }
+
+    /// Builds a mesh from vertices of another vector or point type and counter-clockwise faces
+    /// given as vertex index triples. Normals are computed from the winding, and are zero for
+    /// faces without area.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a face refers to a vertex that does not exist.
+    ///
+    /// ```
+    /// let mesh = stl_io::IndexedMesh::from_vertices(
+    ///     vec![glam::Vec3::ZERO, glam::Vec3::X, glam::Vec3::Y],
+    ///     vec![[0, 1, 2]],
+    /// );
+    /// assert_eq!(mesh.faces[0].normal, [0., 0., 1.]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_vertices<V, I, F>(vertices: I, faces: F) -> IndexedMesh
+    where
+        V: Into<Vertex>,
+        I: IntoIterator<Item = V>,
+        F: IntoIterator<Item = [usize; 3]>,
+    {
+        let vertices = vertices.into_iter().map(Into::into).collect::<Vec<_>>();
+        let faces = faces
+            .into_iter()
+            .map(|f| crate::IndexedTriangle {
+                normal: Triangle {
+                    normal: [0.; 3],
+                    vertices: f.map(|v| vertices[v]),
+                }
+                .normal()
+                .unwrap_or([0.; 3]),
+                vertices: f,
+            })
+            .collect();
+        IndexedMesh { vertices, faces }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::IndexedMesh;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+
+    #[test]
+    fn bunny_through_glam_and_mint() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let faces = bunny.faces.iter().map(|f| f.vertices).collect::<Vec<_>>();
+
+        let glam = bunny.vertices_as::<glam::Vec3>();
+        let from_glam = IndexedMesh::from_vertices(glam, faces.clone());
+        assert_eq!(from_glam.vertices, bunny.vertices);
+        for (a, b) in from_glam.faces.iter().zip(&bunny.faces) {
+            assert_eq!(a.vertices, b.vertices);
+            let agreement = (0..3).map(|i| a.normal[i] * b.normal[i]).sum::<f32>();
+            assert!(agreement > 0.99);
+        }
+
+        let mint = bunny.vertices_as::<mint::Point3<f32>>();
+        assert_eq!(mint[7].y, bunny.vertices[7][1]);
+        let from_mint = IndexedMesh::from_vertices(mint, faces);
+        assert_eq!(from_mint, from_glam);
+    }
+}
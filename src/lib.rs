@@ -1,6 +1,6 @@
 //! ```stl_io``` is a crate for reading and writing [STL (STereoLithography)](https://en.wikipedia.org/wiki/STL_(file_format)) files.
 //! It can read both, binary and ascii STL in a safe manner.
-//! Writing is limited to binary STL, which is more compact anyway.
+//! It writes binary STL, which is more compact, and ascii STL.
 //! # Examples
 //!
 //! Read STL file:
@@ -30,21 +30,116 @@
 //! let mut file = OpenOptions::new().write(true).create_new(true).open("mesh.stl").unwrap();
 //! stl_io::write_stl(&mut file, mesh.iter()).unwrap();
 //! ```
+//!
+//! # `no_std`
+//!
+//! With the default `std` feature disabled the crate only needs `core` and `alloc`. Binary STL
+//! can then still be parsed from and encoded to byte slices with [from_bytes](fn.from_bytes.html)
+//! and [to_bytes](fn.to_bytes.html), while the readers, writers and mesh algorithms that depend
+//! on `std::io` or floating point functions from `std` are left out.
 
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
 extern crate byteorder;
 
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "std")]
 use std::io::{BufRead, BufReader, BufWriter};
+#[cfg(feature = "std")]
 use std::io::{Read, Result, Write};
-use std::iter::Iterator;
+
+#[cfg(feature = "std")]
+pub mod attributes;
+mod bytes;
+#[cfg(feature = "std")]
+pub mod compression;
+#[cfg(feature = "std")]
+pub mod formats;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod implicit;
+mod indices;
+mod interop;
+#[cfg(feature = "std")]
+mod math;
+#[cfg(feature = "std")]
+pub mod mesh;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "std")]
+mod normals;
+mod precision;
+#[cfg(feature = "std")]
+mod salvage;
+#[cfg(feature = "std")]
+pub mod structures;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "async")]
+pub mod tokio;
+#[cfg(feature = "std")]
+mod triangle;
+#[cfg(feature = "std")]
+mod writer;
+
+pub use crate::bytes::{
+    from_bytes, mesh_to_stl_bytes, to_bytes, BinaryStlBytes, BinaryStlBytesIter, TruncatedError,
+};
+pub use crate::indices::IndexOverflowError;
+#[cfg(feature = "mmap")]
+pub use crate::mmap::read_stl_mmap;
+#[cfg(feature = "std")]
+pub use crate::normals::{NormalChecker, NormalPolicy};
+pub use crate::precision::{IndexedMesh64, IndexedTriangle64, Vertex64};
+#[cfg(feature = "std")]
+pub use crate::salvage::{salvage_stl, Corruption, Salvaged};
+#[cfg(feature = "std")]
+pub use crate::writer::{NormalSource, StlFormat, StlWriter, StlWriterBuilder, WriteStats};
 
 /// STL vertex - a corner of a Triangle in a 3D Mesh.
 pub type Vertex = [f32; 3];
 /// STL Normal - a vector perpendicular to a Triangle in a 3D Mesh.
 pub type Normal = [f32; 3];
 
+/// Axis aligned box enclosing a set of vertices.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    /// Corner with the smallest coordinates.
+    pub min: Vertex,
+    /// Corner with the largest coordinates.
+    pub max: Vertex,
+}
+
+impl BoundingBox {
+    /// Smallest box containing all vertices, or None if there are no vertices.
+    pub fn from_vertices<I: IntoIterator<Item = Vertex>>(vertices: I) -> Option<BoundingBox> {
+        let mut vertices = vertices.into_iter();
+        let first = vertices.next()?;
+        let mut bbox = BoundingBox {
+            min: first,
+            max: first,
+        };
+        for v in vertices {
+            bbox.extend(&v);
+        }
+        Some(bbox)
+    }
+    /// Grows the box to contain vertex.
+    pub fn extend(&mut self, vertex: &Vertex) {
+        for ((min, max), c) in self.min.iter_mut().zip(&mut self.max).zip(vertex) {
+            *min = min.min(*c);
+            *max = max.max(*c);
+        }
+    }
+}
+
 /// STL Triangle, consisting of a normal and three vertices.
 /// This is the format Triangles are usually stored in STL files.
 #[derive(Clone, Debug, PartialEq)]
@@ -55,6 +150,27 @@ pub struct Triangle {
     pub vertices: [Vertex; 3],
 }
 
+/// Order in which the vertices of a triangle appear when it is seen from the side its normal
+/// points to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Winding {
+    /// Counter-clockwise. This is what STL specifies and what this crate uses internally.
+    #[default]
+    CounterClockwise,
+    /// Clockwise.
+    Clockwise,
+}
+
+impl Winding {
+    // Order in which to emit the vertices of a counter-clockwise triangle.
+    pub(crate) fn order(self) -> [usize; 3] {
+        match self {
+            Winding::CounterClockwise => [0, 1, 2],
+            Winding::Clockwise => [0, 2, 1],
+        }
+    }
+}
+
 /// STL Triangle in indexed form, consisting of a normal and three indices to vertices in the
 /// vertex list.
 /// This format is more compact, since in real world Meshes Triangles usually share vertices with
@@ -78,6 +194,30 @@ pub struct IndexedMesh {
     pub faces: Vec<IndexedTriangle>,
 }
 
+impl IndexedMesh {
+    /// Expands the mesh into one [Triangle](struct.Triangle.html) per face, e.g. to write it with
+    /// [write_stl](fn.write_stl.html).
+    ///
+    /// ```
+    /// let mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+    ///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+    /// };
+    /// let triangles = mesh.triangles();
+    /// assert_eq!(triangles[0].vertices, [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]);
+    /// ```
+    pub fn triangles(&self) -> Vec<Triangle> {
+        self.faces
+            .iter()
+            .map(|f| Triangle {
+                normal: f.normal,
+                vertices: f.vertices.map(|v| self.vertices[v]),
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
 impl IndexedMesh {
     /// Checks that the Mesh has no holes and no zero-area faces.
     /// Also makes sure that all triangles are faced in the same direction.
@@ -136,6 +276,11 @@ impl IndexedMesh {
 /// Write to std::io::Write as documented in
 /// [Wikipedia](https://en.wikipedia.org/wiki/STL_(file_format)#Binary_STL).
 ///
+/// The output only depends on the triangles and their order: the header is all zeros and the
+/// attribute byte count of every triangle is zero. Together with
+/// [IndexedMesh::canonicalize](struct.IndexedMesh.html#method.canonicalize) this gives
+/// byte-identical files for identical geometry.
+///
 /// ```
 /// let mesh = [stl_io::Triangle { normal: [1.0, 0.0, 0.0],
 ///                                vertices: [[0.0, -1.0, 0.0],
@@ -144,7 +289,19 @@ impl IndexedMesh {
 /// let mut binary_stl = Vec::<u8>::new();
 /// stl_io::write_stl(&mut binary_stl, mesh.iter()).unwrap();
 /// ```
+#[cfg(feature = "std")]
 pub fn write_stl<'a, W, I>(writer: &mut W, mesh: I) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::ExactSizeIterator<Item = &'a Triangle>,
+{
+    write_stl_with_winding(writer, mesh, Winding::CounterClockwise)
+}
+
+/// Like [write_stl](fn.write_stl.html), but emits the vertices of each triangle in the given
+/// [Winding](enum.Winding.html). Normals are written unchanged.
+#[cfg(feature = "std")]
+pub fn write_stl_with_winding<'a, W, I>(writer: &mut W, mesh: I, winding: Winding) -> Result<()>
 where
     W: ::std::io::Write,
     I: ::std::iter::ExactSizeIterator<Item = &'a Triangle>,
@@ -154,18 +311,63 @@ where
     // Write 80 byte header
     writer.write_all(&[0u8; 80])?;
     writer.write_u32::<LittleEndian>(mesh.len() as u32)?;
+    let mut record = [0u8; bytes::TRIANGLE_SIZE];
     for t in mesh {
-        for f in &t.normal {
-            writer.write_f32::<LittleEndian>(*f as f32)?;
-        }
-        for &p in &t.vertices {
-            for c in &p {
-                writer.write_f32::<LittleEndian>(*c as f32)?;
-            }
+        bytes::encode_triangle(t, winding, &mut record);
+        writer.write_all(&record)?;
+    }
+    writer.flush()
+}
+
+/// Write ascii STL to std::io::Write. Binary STL written by [write_stl](fn.write_stl.html) is
+/// more compact, but ascii STL is easier to inspect.
+///
+/// ```
+/// let mesh = [stl_io::Triangle { normal: [1.0, 0.0, 0.0],
+///                                vertices: [[0.0, -1.0, 0.0],
+///                                           [0.0, 1.0, 0.0],
+///                                           [0.0, 0.0, 0.5]]}];
+/// let mut ascii_stl = Vec::<u8>::new();
+/// stl_io::write_ascii_stl(&mut ascii_stl, mesh.iter()).unwrap();
+/// let mut reader = ::std::io::Cursor::new(ascii_stl);
+/// assert_eq!(stl_io::read_stl(&mut reader).unwrap().faces.len(), 1);
+/// ```
+#[cfg(feature = "std")]
+pub fn write_ascii_stl<'a, W, I>(writer: &mut W, mesh: I) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::Iterator<Item = &'a Triangle>,
+{
+    write_ascii_stl_with_winding(writer, mesh, Winding::CounterClockwise)
+}
+
+/// Like [write_ascii_stl](fn.write_ascii_stl.html), but emits the vertices of each triangle in
+/// the given [Winding](enum.Winding.html). Normals are written unchanged.
+#[cfg(feature = "std")]
+pub fn write_ascii_stl_with_winding<'a, W, I>(
+    writer: &mut W,
+    mesh: I,
+    winding: Winding,
+) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::Iterator<Item = &'a Triangle>,
+{
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "solid mesh")?;
+    for t in mesh {
+        // Exponent notation keeps every f32 exact while staying short.
+        let [x, y, z] = t.normal;
+        writeln!(writer, "facet normal {:e} {:e} {:e}", x, y, z)?;
+        writeln!(writer, "  outer loop")?;
+        for &v in &winding.order() {
+            let [x, y, z] = t.vertices[v];
+            writeln!(writer, "    vertex {:e} {:e} {:e}", x, y, z)?;
         }
-        // Attribute byte count
-        writer.write_u16::<LittleEndian>(0)?;
+        writeln!(writer, "  endloop")?;
+        writeln!(writer, "endfacet")?;
     }
+    writeln!(writer, "endsolid mesh")?;
     writer.flush()
 }
 
@@ -184,6 +386,7 @@ where
 ///       endsolid foobar".to_vec());
 /// let mesh = stl_io::read_stl(&mut reader).unwrap();
 /// ```
+#[cfg(feature = "std")]
 pub fn read_stl<R>(read: &mut R) -> Result<IndexedMesh>
 where
     R: ::std::io::Read + ::std::io::Seek,
@@ -191,6 +394,98 @@ where
     create_stl_reader(read)?.to_indexed_triangles()
 }
 
+/// Like [read_stl](fn.read_stl.html), but handles binary STL whose triangle count in the header
+/// does not match the length of the data according to policy.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let mut stl = stl_io::mesh_to_stl_bytes(&mesh);
+/// // Announce a second triangle that is missing.
+/// stl[80] = 2;
+/// let mut reader = ::std::io::Cursor::new(&stl);
+/// assert!(stl_io::read_stl_with_size_policy(&mut reader, stl_io::SizePolicy::Strict).is_err());
+/// let mut reader = ::std::io::Cursor::new(&stl);
+/// let truncated =
+///     stl_io::read_stl_with_size_policy(&mut reader, stl_io::SizePolicy::Truncate).unwrap();
+/// assert_eq!(truncated, mesh);
+/// ```
+#[cfg(feature = "std")]
+pub fn read_stl_with_size_policy<R>(read: &mut R, policy: SizePolicy) -> Result<IndexedMesh>
+where
+    R: ::std::io::Read + ::std::io::Seek,
+{
+    match AsciiStlReader::probe(read) {
+        Ok(()) => AsciiStlReader::create_triangle_iterator(read)?.to_indexed_triangles(),
+        Err(_) => BinaryStlReader::with_size_policy(read, policy)?.to_indexed_triangles(),
+    }
+}
+
+/// Like [read_stl](fn.read_stl.html), but only keeps the triangles for which keep returns true,
+/// e.g. those within a region of interest. Other triangles are dropped as soon as they are
+/// read, so they and the vertices only they use never take up memory in the mesh.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"solid foobar
+/// facet normal 0 0 1
+///     outer loop
+///         vertex 0 0 0
+///         vertex 1 0 0
+///         vertex 0 1 0
+///     endloop
+/// endfacet
+/// facet normal 0 0 1
+///     outer loop
+///         vertex 5 0 0
+///         vertex 6 0 0
+///         vertex 5 1 0
+///     endloop
+/// endfacet
+/// endsolid foobar".to_vec());
+/// let mesh = stl_io::read_stl_filtered(&mut reader, |t| {
+///     t.vertices.iter().all(|v| v[0] < 2.)
+/// }).unwrap();
+/// assert_eq!(mesh.faces.len(), 1);
+/// assert_eq!(mesh.vertices.len(), 3);
+/// ```
+#[cfg(feature = "std")]
+pub fn read_stl_filtered<R, F>(read: &mut R, mut keep: F) -> Result<IndexedMesh>
+where
+    R: ::std::io::Read + ::std::io::Seek,
+    F: FnMut(&Triangle) -> bool,
+{
+    let mut builder = IndexedMeshBuilder::default();
+    for t in create_stl_reader(read)? {
+        let t = t?;
+        if keep(&t) {
+            builder.push(&t);
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Reads either ascii or binary STL from a byte slice, e.g. a file handed from JavaScript to
+/// WebAssembly.
+///
+/// ```
+/// let mesh = stl_io::parse_stl_bytes(b"solid foobar
+/// facet normal 0 0 1
+///     outer loop
+///         vertex 0 0 0
+///         vertex 1 0 0
+///         vertex 0 1 0
+///     endloop
+/// endfacet
+/// endsolid foobar").unwrap();
+/// assert_eq!(stl_io::parse_stl_bytes(&stl_io::mesh_to_stl_bytes(&mesh)).unwrap(), mesh);
+/// ```
+#[cfg(feature = "std")]
+pub fn parse_stl_bytes(bytes: &[u8]) -> Result<IndexedMesh> {
+    read_stl(&mut ::std::io::Cursor::new(bytes))
+}
+
 /// Attempts to create a [TriangleIterator](trait.TriangleIterator.html) for either ascii or binary
 /// STL from std::io::Read.
 ///
@@ -206,9 +501,10 @@ where
 /// endsolid foobar".to_vec());
 /// let stl = stl_io::create_stl_reader(&mut reader).unwrap();
 /// ```
+#[cfg(feature = "std")]
 pub fn create_stl_reader<'a, R>(
     read: &'a mut R,
-) -> Result<Box<TriangleIterator<Item = Result<Triangle>> + 'a>>
+) -> Result<Box<dyn TriangleIterator<Item = Result<Triangle>> + 'a>>
 where
     R: ::std::io::Read + ::std::io::Seek,
 {
@@ -218,18 +514,34 @@ where
     }
 }
 
+/// How the binary STL reader treats a triangle count in the header that does not match the
+/// length of the data, as in truncated uploads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SizePolicy {
+    /// Read as many triangles as the header announces, and fail only when the data ends
+    /// before. Trailing data is ignored. This is the default.
+    #[default]
+    TrustHeader,
+    /// Fail before reading any triangle unless the data holds exactly the announced triangles.
+    Strict,
+    /// Read the announced triangles, but at most as many as the data holds completely.
+    Truncate,
+}
+
 /// Struct for binary STL reader.
+#[cfg(feature = "std")]
 pub struct BinaryStlReader<'a> {
-    reader: Box<::std::io::Read + 'a>,
+    reader: Box<dyn Read + 'a>,
     index: usize,
     size: usize,
 }
 
+#[cfg(feature = "std")]
 impl<'a> BinaryStlReader<'a> {
     /// Factory to create a new BinaryStlReader from read.
     pub fn create_triangle_iterator(
-        read: &'a mut ::std::io::Read,
-    ) -> Result<Box<TriangleIterator<Item = Result<Triangle>> + 'a>> {
+        read: &'a mut dyn Read,
+    ) -> Result<Box<dyn TriangleIterator<Item = Result<Triangle>> + 'a>> {
         let mut reader = Box::new(BufReader::new(read));
         reader.read_exact(&mut [0u8; 80])?;
         let num_faces = reader.read_u32::<LittleEndian>()? as usize;
@@ -237,7 +549,44 @@ impl<'a> BinaryStlReader<'a> {
             reader,
             index: 0,
             size: num_faces,
-        }) as Box<TriangleIterator<Item = Result<Triangle>>>)
+        })
+            as Box<dyn TriangleIterator<Item = Result<Triangle>>>)
+    }
+
+    /// Creates a new binary STL reader from read, checking the triangle count in the header
+    /// against the length of the data according to policy.
+    pub fn with_size_policy<R>(read: &'a mut R, policy: SizePolicy) -> Result<BinaryStlReader<'a>>
+    where
+        R: ::std::io::Read + ::std::io::Seek,
+    {
+        let start = read.stream_position()?;
+        let len = read.seek(::std::io::SeekFrom::End(0))? - start;
+        read.seek(::std::io::SeekFrom::Start(start))?;
+        let mut reader = Box::new(BufReader::new(read));
+        reader.read_exact(&mut [0u8; 80])?;
+        let num_faces = reader.read_u32::<LittleEndian>()? as usize;
+        let available = (len - 84) / 50;
+        let size = match policy {
+            SizePolicy::TrustHeader => num_faces,
+            SizePolicy::Truncate => num_faces.min(available as usize),
+            SizePolicy::Strict if len == 84 + 50 * num_faces as u64 => num_faces,
+            SizePolicy::Strict => {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    format!(
+                    "binary STL header announces {} triangles, but the data has {} bytes for {}",
+                    num_faces,
+                    len - 84,
+                    available
+                ),
+                ))
+            }
+        };
+        Ok(BinaryStlReader {
+            reader,
+            index: 0,
+            size,
+        })
     }
 
     fn next_face(&mut self) -> Result<Triangle> {
@@ -259,6 +608,7 @@ impl<'a> BinaryStlReader<'a> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'a> ::std::iter::Iterator for BinaryStlReader<'a> {
     type Item = Result<Triangle>;
     fn next(&mut self) -> Option<Self::Item> {
@@ -274,6 +624,7 @@ impl<'a> ::std::iter::Iterator for BinaryStlReader<'a> {
 }
 
 /// Iterates over all Triangles in a STL.
+#[cfg(feature = "std")]
 pub trait TriangleIterator: ::std::iter::Iterator<Item = Result<Triangle>> {
     /// Consumes this iterator and generates an [indexed Mesh](struct.IndexedMesh.html).
     ///
@@ -291,47 +642,114 @@ pub trait TriangleIterator: ::std::iter::Iterator<Item = Result<Triangle>> {
     /// let indexed_mesh = stl.to_indexed_triangles().unwrap();
     /// ```
     fn to_indexed_triangles(&mut self) -> Result<IndexedMesh> {
-        let mut vertices = Vec::new();
-        let mut triangles = Vec::new();
-        let mut vertex_to_index = ::std::collections::HashMap::new();
-        // Do not reserve memory in those structures based on size_hint, because we might have just
-        // read bogus data.
-        let mut vertex_indices = [0; 3];
+        // Do not reserve memory based on size_hint, because we might have just read bogus data.
+        let mut builder = IndexedMeshBuilder::default();
         for t in self {
-            let t = t?;
-            for (i, vertex) in t.vertices.iter().enumerate() {
-                // This is ugly, but f32 has no Eq and no Hash.
-                let bitpattern = unsafe { std::mem::transmute::<[f32; 3], [u32; 3]>(*vertex) };
-                let index = *vertex_to_index
-                    .entry(bitpattern)
-                    .or_insert_with(|| vertices.len());
-                if index == vertices.len() {
-                    vertices.push(*vertex);
-                }
-                vertex_indices[i] = index;
+            builder.push(&t?);
+        }
+        Ok(builder.build())
+    }
+}
+
+// Collects Triangles into an IndexedMesh, merging vertices with identical coordinates.
+#[derive(Default)]
+#[cfg(feature = "std")]
+pub(crate) struct IndexedMeshBuilder {
+    vertices: Vec<Vertex>,
+    faces: Vec<IndexedTriangle>,
+    vertex_to_index: ::std::collections::HashMap<[u32; 3], usize>,
+}
+
+#[cfg(feature = "std")]
+impl IndexedMeshBuilder {
+    pub(crate) fn push(&mut self, t: &Triangle) {
+        let mut vertex_indices = [0; 3];
+        for (i, vertex) in t.vertices.iter().enumerate() {
+            // f32 has no Eq and no Hash, so compare bit patterns instead.
+            let bitpattern = [
+                vertex[0].to_bits(),
+                vertex[1].to_bits(),
+                vertex[2].to_bits(),
+            ];
+            let vertices = &mut self.vertices;
+            let index = *self
+                .vertex_to_index
+                .entry(bitpattern)
+                .or_insert_with(|| vertices.len());
+            if index == vertices.len() {
+                vertices.push(*vertex);
             }
-            triangles.push(IndexedTriangle {
-                normal: t.normal,
-                vertices: vertex_indices,
-            });
+            vertex_indices[i] = index;
         }
-        vertices.shrink_to_fit();
-        triangles.shrink_to_fit();
-        Ok(IndexedMesh {
-            vertices,
-            faces: triangles,
-        })
+        self.faces.push(IndexedTriangle {
+            normal: t.normal,
+            vertices: vertex_indices,
+        });
     }
+
+    pub(crate) fn build(mut self) -> IndexedMesh {
+        self.vertices.shrink_to_fit();
+        self.faces.shrink_to_fit();
+        IndexedMesh {
+            vertices: self.vertices,
+            faces: self.faces,
+        }
+    }
+}
+
+/// How strictly the ascii STL reader adheres to the format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Reject every deviation from the format. This is the default.
+    #[default]
+    Strict,
+    /// Accept common real-world deviations and report them as
+    /// [ParseWarnings](struct.ParseWarning.html).
+    Lenient,
+}
+
+/// Kind of deviation from the ascii STL format tolerated in lenient mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseWarningKind {
+    /// The file ended without `endsolid`.
+    MissingEndsolid,
+    /// A keyword was not lowercase, e.g. `FACET NORMAL`.
+    UppercaseKeyword,
+    /// `solid` was not followed by a name.
+    UnnamedSolid,
+    /// A number used a comma as decimal separator, e.g. `1,5`.
+    CommaDecimal,
+    /// A number used a Fortran style exponent, e.g. `1.5D+02`.
+    FortranExponent,
+    /// A facet normal did not have three components and was replaced by `[0, 0, 0]`.
+    MalformedNormal,
+}
+
+/// A deviation from the ascii STL format tolerated in lenient mode.
+/// Each kind of deviation is reported once, at the line it was first seen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Line number (starting at 1) of the first occurrence.
+    pub line: usize,
+    /// What was tolerated.
+    pub kind: ParseWarningKind,
 }
 
 /// Struct for ascii STL reader.
+#[cfg(feature = "std")]
 pub struct AsciiStlReader<'a> {
-    lines: Box<::std::iter::Iterator<Item = Result<Vec<String>>> + 'a>,
+    lines: Box<dyn Iterator<Item = (usize, Result<Vec<String>>)> + 'a>,
+    mode: ParseMode,
+    line: usize,
+    warnings: Vec<ParseWarning>,
 }
 
+#[cfg(feature = "std")]
 impl<'a> TriangleIterator for BinaryStlReader<'a> {}
+#[cfg(feature = "std")]
 impl<'a> TriangleIterator for AsciiStlReader<'a> {}
 
+#[cfg(feature = "std")]
 impl<'a> ::std::iter::Iterator for AsciiStlReader<'a> {
     type Item = Result<Triangle>;
     fn next(&mut self) -> Option<Self::Item> {
@@ -343,6 +761,7 @@ impl<'a> ::std::iter::Iterator for AsciiStlReader<'a> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'a> AsciiStlReader<'a> {
     /// Test whether or not read is an ascii STL file.
     pub fn probe<F: ::std::io::Read + ::std::io::Seek>(read: &mut F) -> Result<()> {
@@ -362,12 +781,64 @@ impl<'a> AsciiStlReader<'a> {
     }
     /// Factory to create a new ascii STL Reader from read.
     pub fn create_triangle_iterator(
-        read: &'a mut ::std::io::Read,
-    ) -> Result<Box<TriangleIterator<Item = Result<Triangle>> + 'a>> {
+        read: &'a mut dyn Read,
+    ) -> Result<Box<dyn TriangleIterator<Item = Result<Triangle>> + 'a>> {
+        Ok(
+            Box::new(AsciiStlReader::with_mode(read, ParseMode::Strict)?)
+                as Box<dyn TriangleIterator<Item = Result<Triangle>>>,
+        )
+    }
+    /// Creates a new ascii STL Reader from read using the given
+    /// [ParseMode](enum.ParseMode.html).
+    ///
+    /// ```
+    /// use stl_io::TriangleIterator;
+    /// let mut reader = ::std::io::Cursor::new(b"SOLID
+    /// FACET NORMAL 0 0 1
+    ///     OUTER LOOP
+    ///         VERTEX 0 0 0
+    ///         VERTEX 1,5 0 0
+    ///         VERTEX 0 1 0
+    ///     ENDLOOP
+    /// ENDFACET".to_vec());
+    /// let mut stl =
+    ///     stl_io::AsciiStlReader::with_mode(&mut reader, stl_io::ParseMode::Lenient).unwrap();
+    /// let mesh = stl.to_indexed_triangles().unwrap();
+    /// assert_eq!(mesh.vertices[1], [1.5, 0., 0.]);
+    /// assert_eq!(stl.warnings().len(), 4);
+    /// ```
+    pub fn with_mode(read: &'a mut dyn Read, mode: ParseMode) -> Result<AsciiStlReader<'a>> {
         let mut lines = BufReader::new(read).lines();
+        let mut warnings = Vec::new();
         match lines.next() {
             Some(Err(e)) => return Err(e),
-            Some(Ok(ref line)) if !line.starts_with("solid ") => {
+            Some(Ok(ref line)) if line.starts_with("solid ") => {}
+            Some(Ok(ref line)) if mode == ParseMode::Lenient => {
+                let mut tokens = line.split_whitespace();
+                match tokens.next() {
+                    Some(solid) if solid.eq_ignore_ascii_case("solid") => {
+                        if solid != "solid" {
+                            warnings.push(ParseWarning {
+                                line: 1,
+                                kind: ParseWarningKind::UppercaseKeyword,
+                            });
+                        }
+                        if tokens.next().is_none() {
+                            warnings.push(ParseWarning {
+                                line: 1,
+                                kind: ParseWarningKind::UnnamedSolid,
+                            });
+                        }
+                    }
+                    _ => {
+                        return Err(::std::io::Error::new(
+                            ::std::io::ErrorKind::InvalidData,
+                            "ascii STL does not start with \"solid\"",
+                        ))
+                    }
+                }
+            }
+            Some(Ok(_)) => {
                 return Err(::std::io::Error::new(
                     ::std::io::ErrorKind::InvalidData,
                     "ascii STL does not start with \"solid \"",
@@ -379,62 +850,112 @@ impl<'a> AsciiStlReader<'a> {
                     "empty file?",
                 ))
             }
-            _ => {}
         }
         let lines = lines
-            .map(|result| {
-                     result.map(|l| {
-                                    // Make lines into iterator over vectors of tokens
-                                    l.split_whitespace()
-                                        .map(|t| t.to_string())
-                                        .collect::<Vec<_>>()
-                                })
-                 })
-             // filter empty lines.
-            .filter(|result| result.is_err() || (!result.as_ref().unwrap().is_empty()));
-        Ok(Box::new(AsciiStlReader {
+            .enumerate()
+            .map(|(i, result)| {
+                // The header was line 1.
+                (
+                    i + 2,
+                    result.map(|l| {
+                        // Make lines into iterator over vectors of tokens
+                        l.split_whitespace()
+                            .map(|t| t.to_string())
+                            .collect::<Vec<_>>()
+                    }),
+                )
+            })
+            // filter empty lines.
+            .filter(|(_, result)| result.is_err() || (!result.as_ref().unwrap().is_empty()));
+        Ok(AsciiStlReader {
             lines: Box::new(lines),
-        }) as Box<TriangleIterator<Item = Result<Triangle>>>)
+            mode,
+            line: 1,
+            warnings,
+        })
+    }
+    /// Deviations from the format tolerated so far. Always empty in strict mode.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+    fn warn(&mut self, kind: ParseWarningKind) {
+        if self.warnings.iter().all(|w| w.kind != kind) {
+            self.warnings.push(ParseWarning {
+                line: self.line,
+                kind,
+            });
+        }
+    }
+    fn next_line(&mut self) -> Option<Result<Vec<String>>> {
+        let (line, tokens) = self.lines.next()?;
+        self.line = line;
+        Some(tokens)
+    }
+    // Compares tokens to keywords, ignoring case in lenient mode.
+    fn is_keywords(&mut self, tokens: &[String], keywords: &[&str]) -> bool {
+        if tokens == keywords {
+            return true;
+        }
+        if self.mode == ParseMode::Lenient
+            && tokens.len() == keywords.len()
+            && tokens
+                .iter()
+                .zip(keywords)
+                .all(|(t, k)| t.eq_ignore_ascii_case(k))
+        {
+            self.warn(ParseWarningKind::UppercaseKeyword);
+            return true;
+        }
+        false
     }
     // Tries to read a triangle.
     fn next_face(&mut self) -> Result<Option<Triangle>> {
-        let face_header: Option<Result<Vec<String>>> = self.lines.next();
-        if face_header.is_none() {
-            return Err(::std::io::Error::new(
-                ::std::io::ErrorKind::UnexpectedEof,
-                "EOF while expecting facet or endsolid.",
-            ));
-        }
-        let face_header = try!(face_header.unwrap());
-        if !face_header.is_empty() && face_header[0] == "endsolid" {
+        let face_header = match self.next_line() {
+            Some(face_header) => face_header?,
+            None if self.mode == ParseMode::Lenient => {
+                self.warn(ParseWarningKind::MissingEndsolid);
+                return Ok(None);
+            }
+            None => {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::UnexpectedEof,
+                    "EOF while expecting facet or endsolid.",
+                ))
+            }
+        };
+        if !face_header.is_empty() && self.is_keywords(&face_header[..1], &["endsolid"]) {
             return Ok(None);
         }
-        if face_header.len() != 5 || face_header[0] != "facet" || face_header[1] != "normal" {
+        let mut result_normal = [0.; 3];
+        if face_header.len() >= 2 && self.is_keywords(&face_header[..2], &["facet", "normal"]) {
+            if face_header.len() == 5 {
+                self.tokens_to_f32(&face_header[2..5], &mut result_normal[0..3])?;
+            } else if self.mode == ParseMode::Lenient {
+                self.warn(ParseWarningKind::MalformedNormal);
+            } else {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    format!("invalid facet header: {:?}", face_header),
+                ));
+            }
+        } else {
             return Err(::std::io::Error::new(
                 ::std::io::ErrorKind::InvalidData,
                 format!("invalid facet header: {:?}", face_header),
             ));
         }
-        let mut result_normal = [0.; 3];
-        try!(AsciiStlReader::tokens_to_f32(
-            &face_header[2..5],
-            &mut result_normal[0..3]
-        ));
-        try!(self.expect_static(&["outer", "loop"]));
+        self.expect_static(&["outer", "loop"])?;
         let mut result_vertices = [[0.; 3]; 3];
         for vertex_result in &mut result_vertices {
-            if let Some(line) = self.lines.next() {
-                let line = try!(line);
-                if line.len() != 4 || line[0] != "vertex" {
+            if let Some(line) = self.next_line() {
+                let line = line?;
+                if line.len() != 4 || !self.is_keywords(&line[..1], &["vertex"]) {
                     return Err(::std::io::Error::new(
                         ::std::io::ErrorKind::InvalidData,
                         format!("vertex f32 f32 f32, got {:?}", line),
                     ));
                 }
-                try!(AsciiStlReader::tokens_to_f32(
-                    &line[1..4],
-                    &mut vertex_result[0..3]
-                ));
+                self.tokens_to_f32(&line[1..4], &mut vertex_result[0..3])?;
             } else {
                 return Err(::std::io::Error::new(
                     ::std::io::ErrorKind::UnexpectedEof,
@@ -442,20 +963,17 @@ impl<'a> AsciiStlReader<'a> {
                 ));
             }
         }
-        try!(self.expect_static(&["endloop"]));
-        try!(self.expect_static(&["endfacet"]));
+        self.expect_static(&["endloop"])?;
+        self.expect_static(&["endfacet"])?;
         Ok(Some(Triangle {
             normal: result_normal,
             vertices: result_vertices,
         }))
     }
-    fn tokens_to_f32(tokens: &[String], output: &mut [f32]) -> Result<()> {
+    fn tokens_to_f32(&mut self, tokens: &[String], output: &mut [f32]) -> Result<()> {
         assert_eq!(tokens.len(), output.len());
         for i in 0..tokens.len() {
-            let f = try!(tokens[i].parse::<f32>().map_err(|e| ::std::io::Error::new(
-                ::std::io::ErrorKind::InvalidData,
-                e.to_string()
-            )));
+            let f = self.parse_f32(&tokens[i])?;
             if !f.is_finite() {
                 return Err(::std::io::Error::new(
                     ::std::io::ErrorKind::InvalidData,
@@ -466,10 +984,29 @@ impl<'a> AsciiStlReader<'a> {
         }
         Ok(())
     }
+    fn parse_f32(&mut self, token: &str) -> Result<f32> {
+        let error = match token.parse::<f32>() {
+            Ok(f) => return Ok(f),
+            Err(e) => ::std::io::Error::new(::std::io::ErrorKind::InvalidData, e.to_string()),
+        };
+        if self.mode == ParseMode::Strict {
+            return Err(error);
+        }
+        let mut token = token.to_string();
+        if token.contains(',') && !token.contains('.') {
+            token = token.replace(',', ".");
+            self.warn(ParseWarningKind::CommaDecimal);
+        }
+        if token.contains(['d', 'D']) {
+            token = token.replace(['d', 'D'], "e");
+            self.warn(ParseWarningKind::FortranExponent);
+        }
+        token.parse::<f32>().map_err(|_| error)
+    }
     fn expect_static(&mut self, expectation: &[&str]) -> Result<()> {
-        if let Some(line) = self.lines.next() {
-            let line = try!(line);
-            if line != expectation {
+        if let Some(line) = self.next_line() {
+            let line = line?;
+            if !self.is_keywords(&line, expectation) {
                 return Err(::std::io::Error::new(
                     ::std::io::ErrorKind::InvalidData,
                     format!("expected {:?}, got {:?}", expectation, line),
@@ -485,6 +1022,7 @@ impl<'a> AsciiStlReader<'a> {
     }
 }
 
+#[cfg(feature = "std")]
 #[cfg(test)]
 mod test {
     use super::*;
@@ -508,6 +1046,35 @@ mod test {
         mesh
     }
 
+    #[test]
+    fn read_bunny_filtered() {
+        let bunny = read_stl(&mut ::std::io::Cursor::new(BUNNY_99)).unwrap();
+        let bbox = BoundingBox::from_vertices(bunny.vertices.iter().cloned()).unwrap();
+        let middle = (bbox.min[0] + bbox.max[0]) / 2.;
+        let left = |t: &Triangle| t.vertices.iter().any(|v| v[0] < middle);
+
+        for bytes in &[BUNNY_99, BUNNY_99_ASCII] {
+            let mut reader = ::std::io::Cursor::new(bytes);
+            let filtered = read_stl_filtered(&mut reader, left).unwrap();
+            let expected = bunny
+                .faces
+                .iter()
+                .filter(|f| f.vertices.iter().any(|&v| bunny.vertices[v][0] < middle))
+                .count();
+            assert!(expected > 0 && expected < bunny.faces.len());
+            assert_eq!(filtered.faces.len(), expected);
+            assert!(filtered.vertices.len() < bunny.vertices.len());
+            assert!(filtered
+                .faces
+                .iter()
+                .all(|f| f.vertices.iter().any(|&v| filtered.vertices[v][0] < middle)));
+        }
+
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let all = read_stl_filtered(&mut reader, |_| true).unwrap();
+        assert_eq!(all, bunny);
+    }
+
     #[test]
     fn read_ascii_stl_simple_success() {
         let mut reader = ::std::io::Cursor::new(
@@ -696,6 +1263,42 @@ mod test {
         assert_eq!(stl.unwrap().to_indexed_triangles().unwrap().faces.len(), 99);
     }
 
+    #[test]
+    fn read_binary_stl_size_policies() {
+        let read = |bytes: &[u8], policy| {
+            BinaryStlReader::with_size_policy(&mut ::std::io::Cursor::new(bytes), policy)
+                .and_then(|mut stl| stl.to_indexed_triangles())
+                .map(|mesh| mesh.faces.len())
+        };
+        for &policy in &[
+            SizePolicy::TrustHeader,
+            SizePolicy::Strict,
+            SizePolicy::Truncate,
+        ] {
+            assert_eq!(read(BUNNY_99, policy).unwrap(), 99);
+        }
+
+        let truncated = &BUNNY_99[..BUNNY_99.len() - 10];
+        assert_eq!(
+            read(truncated, SizePolicy::TrustHeader).unwrap_err().kind(),
+            ::std::io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(
+            read(truncated, SizePolicy::Strict).unwrap_err().kind(),
+            ::std::io::ErrorKind::InvalidData
+        );
+        assert_eq!(read(truncated, SizePolicy::Truncate).unwrap(), 98);
+
+        let mut padded = BUNNY_99.to_vec();
+        padded.extend_from_slice(&[0; 60]);
+        assert_eq!(read(&padded, SizePolicy::TrustHeader).unwrap(), 99);
+        assert!(read(&padded, SizePolicy::Strict).is_err());
+        assert_eq!(read(&padded, SizePolicy::Truncate).unwrap(), 99);
+
+        // The header may not even be complete.
+        assert!(read(&BUNNY_99[..50], SizePolicy::Truncate).is_err());
+    }
+
     #[test]
     fn read_ascii_and_binary_stl_bunny() {
         let mut binary_reader = ::std::io::Cursor::new(BUNNY_99);
@@ -747,4 +1350,171 @@ mod test {
             .to_indexed_triangles();
         assert!(stl.is_ok(), "{:?}", stl);
     }
+
+    #[test]
+    fn read_ascii_stl_lenient_accepts_violations() {
+        let mut reader = ::std::io::Cursor::new(
+            b"SOLID
+        Facet Normal 0,1 0.2 3D-1
+            outer loop
+                vertex 1 2 3
+                vertex 4 5 6e-15
+                vertex 7 8 9.87654321
+            endloop
+        endfacet
+        facet normal
+            outer loop
+                vertex 1 2 3
+                vertex 4 5 6e-15
+                vertex 7 8 9
+            endloop
+        endfacet"
+                .to_vec(),
+        );
+        let mut stl = AsciiStlReader::with_mode(&mut reader, ParseMode::Lenient).unwrap();
+        let mesh = stl.to_indexed_triangles().unwrap();
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.faces[0].normal, [0.1, 0.2, 0.3]);
+        assert_eq!(mesh.faces[1].normal, [0., 0., 0.]);
+        assert_eq!(
+            stl.warnings(),
+            &[
+                ParseWarning {
+                    line: 1,
+                    kind: ParseWarningKind::UppercaseKeyword,
+                },
+                ParseWarning {
+                    line: 1,
+                    kind: ParseWarningKind::UnnamedSolid,
+                },
+                ParseWarning {
+                    line: 2,
+                    kind: ParseWarningKind::CommaDecimal,
+                },
+                ParseWarning {
+                    line: 2,
+                    kind: ParseWarningKind::FortranExponent,
+                },
+                ParseWarning {
+                    line: 9,
+                    kind: ParseWarningKind::MalformedNormal,
+                },
+                ParseWarning {
+                    line: 15,
+                    kind: ParseWarningKind::MissingEndsolid,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn read_ascii_stl_strict_rejects_violations() {
+        let mut reader = ::std::io::Cursor::new(
+            b"solid foobar
+        FACET NORMAL 0.1 0.2 0.3
+            outer loop
+                vertex 1 2 3
+                vertex 4 5 6e-15
+                vertex 7 8 9.87654321
+            endloop
+        endfacet
+        endsolid foobar"
+                .to_vec(),
+        );
+        let stl = AsciiStlReader::create_triangle_iterator(&mut reader)
+            .unwrap()
+            .to_indexed_triangles();
+        assert_eq!(
+            stl.as_ref().err().unwrap().kind(),
+            ::std::io::ErrorKind::InvalidData,
+            "{:?}",
+            stl
+        );
+    }
+
+    #[test]
+    fn read_ascii_stl_lenient_strict_file_has_no_warnings() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99_ASCII);
+        let mut stl = AsciiStlReader::with_mode(&mut reader, ParseMode::Lenient).unwrap();
+        assert_eq!(stl.to_indexed_triangles().unwrap().faces.len(), 99);
+        assert!(stl.warnings().is_empty(), "{:?}", stl.warnings());
+    }
+
+    #[test]
+    fn write_binary_stl_clockwise() {
+        let mesh = [Triangle {
+            normal: [0., 0., 1.],
+            vertices: [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+        }];
+        let mut binary_stl = Vec::<u8>::new();
+        write_stl_with_winding(&mut binary_stl, mesh.iter(), Winding::Clockwise).unwrap();
+        let stl = from_bytes(&binary_stl).unwrap();
+        assert_eq!(
+            stl.get(0).unwrap(),
+            Triangle {
+                normal: [0., 0., 1.],
+                vertices: [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.]],
+            }
+        );
+    }
+
+    #[test]
+    fn write_ascii_stl_clockwise() {
+        let mesh = [Triangle {
+            normal: [0., 0., 1.],
+            vertices: [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+        }];
+        let mut ascii_stl = Vec::<u8>::new();
+        write_ascii_stl_with_winding(&mut ascii_stl, mesh.iter(), Winding::Clockwise).unwrap();
+        let mut reader = ::std::io::Cursor::new(ascii_stl);
+        let mut stl = AsciiStlReader::create_triangle_iterator(&mut reader).unwrap();
+        assert_eq!(
+            stl.next().unwrap().unwrap(),
+            Triangle {
+                normal: [0., 0., 1.],
+                vertices: [[0., 0., 0.], [0., 1., 0.], [1., 0., 0.]],
+            }
+        );
+    }
+
+    #[test]
+    fn binary_stl_is_little_endian() {
+        let mesh = [Triangle {
+            normal: [1., 0., -2.],
+            vertices: [[0.5, 0., 0.], [0., 1.5, 0.], [0., 0., -0.25]],
+        }];
+        // Spelled out byte by byte, so that the test also holds on big-endian targets.
+        let mut expected = vec![0u8; 80];
+        expected.extend_from_slice(&[1, 0, 0, 0]);
+        for bytes in &[
+            [0x00, 0x00, 0x80, 0x3f],
+            [0x00, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0x00, 0xc0],
+            [0x00, 0x00, 0x00, 0x3f],
+            [0x00, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0xc0, 0x3f],
+            [0x00, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0x00, 0x00],
+            [0x00, 0x00, 0x80, 0xbe],
+        ] {
+            expected.extend_from_slice(bytes);
+        }
+        expected.extend_from_slice(&[0, 0]);
+
+        let mut written = Vec::<u8>::new();
+        write_stl(&mut written, mesh.iter()).unwrap();
+        assert_eq!(written, expected);
+        assert_eq!(to_bytes(mesh.iter()), expected);
+
+        let read =
+            BinaryStlReader::create_triangle_iterator(&mut ::std::io::Cursor::new(&expected))
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+        assert_eq!(read, mesh);
+        assert_eq!(from_bytes(&expected).unwrap().get(0).as_ref(), mesh.first());
+    }
 }
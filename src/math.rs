@@ -0,0 +1,387 @@
+// Small f64 linear algebra helpers shared by the mesh algorithms.
+
+pub(crate) type Vec3 = [f64; 3];
+
+pub(crate) fn to_f64(v: &[f32; 3]) -> Vec3 {
+    [f64::from(v[0]), f64::from(v[1]), f64::from(v[2])]
+}
+
+pub(crate) fn to_f32(v: &Vec3) -> [f32; 3] {
+    [v[0] as f32, v[1] as f32, v[2] as f32]
+}
+
+pub(crate) fn add(a: &Vec3, b: &Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: &Vec3, b: &Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: &Vec3, s: f64) -> Vec3 {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub(crate) fn dot(a: &Vec3, b: &Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: &Vec3, b: &Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn norm(a: &Vec3) -> f64 {
+    dot(a, a).sqrt()
+}
+
+// Returns a unit vector in the direction of a, or None if a has zero length.
+pub(crate) fn normalize(a: &Vec3) -> Option<Vec3> {
+    let n = norm(a);
+    if n > 0. && n.is_finite() {
+        Some(scale(a, 1. / n))
+    } else {
+        None
+    }
+}
+
+// Normal of the triangle spanned by the three vertices, scaled by twice its area.
+pub(crate) fn area_normal(vertices: &[Vec3; 3]) -> Vec3 {
+    cross(
+        &sub(&vertices[1], &vertices[0]),
+        &sub(&vertices[2], &vertices[0]),
+    )
+}
+
+// Eigen decomposition of a symmetric 3x3 matrix using Jacobi rotations.
+// Returns the eigenvalues in ascending order and the matching unit eigenvectors.
+pub(crate) fn symmetric_eigen(m: &[[f64; 3]; 3]) -> ([f64; 3], [Vec3; 3]) {
+    let mut a = *m;
+    let mut v = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+    for _ in 0..50 {
+        let off = a[0][1].abs() + a[0][2].abs() + a[1][2].abs();
+        if off < 1e-15 * (a[0][0].abs() + a[1][1].abs() + a[2][2].abs()).max(1e-300) {
+            break;
+        }
+        for &(p, q) in &[(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0. {
+                continue;
+            }
+            let theta = (a[q][q] - a[p][p]) / (2. * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+            let c = 1. / (t * t + 1.).sqrt();
+            let s = t * c;
+            let rotate = |x: f64, y: f64| (c * x - s * y, s * x + c * y);
+            for row in &mut a {
+                let (x, y) = rotate(row[p], row[q]);
+                row[p] = x;
+                row[q] = y;
+            }
+            let (row_p, row_q) = (a[p], a[q]);
+            for (k, (x, y)) in row_p.iter().zip(&row_q).enumerate() {
+                let (x, y) = rotate(*x, *y);
+                a[p][k] = x;
+                a[q][k] = y;
+            }
+            for row in &mut v {
+                let (x, y) = rotate(row[p], row[q]);
+                row[p] = x;
+                row[q] = y;
+            }
+        }
+    }
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| a[i][i].total_cmp(&a[j][j]));
+    let values = [
+        a[order[0]][order[0]],
+        a[order[1]][order[1]],
+        a[order[2]][order[2]],
+    ];
+    let column = |c: usize| [v[0][c], v[1][c], v[2][c]];
+    (
+        values,
+        [column(order[0]), column(order[1]), column(order[2])],
+    )
+}
+
+// Solves the square linear system a * x = b by Gaussian elimination with partial pivoting.
+// Returns None if the system is singular.
+pub(crate) fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].is_nan() || a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            let (upper, lower) = a.split_at_mut(row);
+            for (x, pivot) in lower[0][col..].iter_mut().zip(&upper[col][col..]) {
+                *x -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+// 3x3 matrix stored by rows.
+pub(crate) type Matrix = [Vec3; 3];
+
+pub(crate) fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let column = |c: usize| [b[0][c], b[1][c], b[2][c]];
+    a.map(|row| [0, 1, 2].map(|c| dot(&row, &column(c))))
+}
+
+// Rotation by the angle |r| around the axis r (Rodrigues' formula).
+pub(crate) fn rotation(r: &Vec3) -> Matrix {
+    let angle = norm(r);
+    let mut m = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+    if angle == 0. {
+        return m;
+    }
+    let [x, y, z] = scale(r, 1. / angle);
+    let (s, c) = angle.sin_cos();
+    let k = [[0., -z, y], [z, 0., -x], [-y, x, 0.]];
+    let k2 = multiply(&k, &k);
+    for i in 0..3 {
+        for j in 0..3 {
+            m[i][j] += s * k[i][j] + (1. - c) * k2[i][j];
+        }
+    }
+    m
+}
+
+// Rotation that turns the unit vector up into +z.
+pub(crate) fn rotation_to_z(up: &Vec3) -> Matrix {
+    let axis = cross(up, &[0., 0., 1.]);
+    let sin = norm(&axis);
+    if sin < 1e-12 {
+        // up is parallel to z, turn it around the x axis if it points down.
+        let angle = if up[2] > 0. {
+            0.
+        } else {
+            ::std::f64::consts::PI
+        };
+        return rotation(&[angle, 0., 0.]);
+    }
+    rotation(&scale(&axis, sin.atan2(up[2]) / sin))
+}
+
+// Closest point to p on the triangle, following Ericson, Real-Time Collision Detection, 5.1.5.
+pub(crate) fn closest_point_on_triangle(p: &Vec3, t: &[Vec3; 3]) -> Vec3 {
+    let (a, b, c) = (&t[0], &t[1], &t[2]);
+    let ab = sub(b, a);
+    let ac = sub(c, a);
+    let ap = sub(p, a);
+    let d1 = dot(&ab, &ap);
+    let d2 = dot(&ac, &ap);
+    if d1 <= 0. && d2 <= 0. {
+        return *a;
+    }
+    let bp = sub(p, b);
+    let d3 = dot(&ab, &bp);
+    let d4 = dot(&ac, &bp);
+    if d3 >= 0. && d4 <= d3 {
+        return *b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0. && d1 >= 0. && d3 <= 0. {
+        return add(a, &scale(&ab, d1 / (d1 - d3)));
+    }
+    let cp = sub(p, c);
+    let d5 = dot(&ab, &cp);
+    let d6 = dot(&ac, &cp);
+    if d6 >= 0. && d5 <= d6 {
+        return *c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0. && d2 >= 0. && d6 <= 0. {
+        return add(a, &scale(&ac, d2 / (d2 - d6)));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0. && (d4 - d3) >= 0. && (d5 - d6) >= 0. {
+        return add(b, &scale(&sub(c, b), (d4 - d3) / ((d4 - d3) + (d5 - d6))));
+    }
+    let denom = va + vb + vc;
+    if denom == 0. {
+        // Degenerate triangle whose corners are collinear; fall back to the closest edge.
+        return [(a, b), (b, c), (c, a)]
+            .iter()
+            .map(|&(s, e)| closest_point_on_segment(p, s, e))
+            .min_by(|x, y| norm(&sub(x, p)).total_cmp(&norm(&sub(y, p))))
+            .unwrap();
+    }
+    add(a, &add(&scale(&ab, vb / denom), &scale(&ac, vc / denom)))
+}
+
+pub(crate) fn closest_point_on_segment(p: &Vec3, a: &Vec3, b: &Vec3) -> Vec3 {
+    let ab = sub(b, a);
+    let len2 = dot(&ab, &ab);
+    if len2 == 0. {
+        return *a;
+    }
+    let t = (dot(&sub(p, a), &ab) / len2).clamp(0., 1.);
+    add(a, &scale(&ab, t))
+}
+
+// Intersection of the ray origin + t * direction, t >= 0, with a triangle, following Moeller and
+// Trumbore. Returns t, or None if the ray misses or is parallel to the triangle.
+pub(crate) fn ray_triangle(origin: &Vec3, direction: &Vec3, t: &[Vec3; 3]) -> Option<f64> {
+    let e1 = sub(&t[1], &t[0]);
+    let e2 = sub(&t[2], &t[0]);
+    let p = cross(direction, &e2);
+    let det = dot(&e1, &p);
+    if det == 0. || !det.is_finite() {
+        return None;
+    }
+    let s = sub(origin, &t[0]);
+    let u = dot(&s, &p) / det;
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+    let q = cross(&s, &e1);
+    let v = dot(direction, &q) / det;
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+    let hit = dot(&e2, &q) / det;
+    if hit >= 0. {
+        Some(hit)
+    } else {
+        None
+    }
+}
+
+// Deterministic pseudo random numbers (SplitMix64), so that randomized algorithms are
+// reproducible from a seed without depending on a random number crate.
+pub(crate) struct Random(u64);
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Random {
+        Random(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniformly distributed in [0, 1).
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn symmetric_eigen_diagonalizes() {
+        let m = [[2., 1., 0.], [1., 2., 0.], [0., 0., 5.]];
+        let (values, vectors) = symmetric_eigen(&m);
+        let expected = [1., 3., 5.];
+        for i in 0..3 {
+            assert!((values[i] - expected[i]).abs() < 1e-12, "{:?}", values);
+            let mv = [
+                dot(&m[0], &vectors[i]),
+                dot(&m[1], &vectors[i]),
+                dot(&m[2], &vectors[i]),
+            ];
+            assert!(norm(&sub(&mv, &scale(&vectors[i], values[i]))) < 1e-12);
+        }
+    }
+
+    #[test]
+    fn solve_linear_system() {
+        let x = solve(
+            vec![vec![0., 2., 1.], vec![1., 1., 1.], vec![2., 1., 3.]],
+            vec![7., 6., 13.],
+        )
+        .unwrap();
+        assert_eq!(
+            x.iter().map(|c| c.round()).collect::<Vec<_>>(),
+            vec![1., 2., 3.]
+        );
+        assert!(solve(vec![vec![1., 2.], vec![2., 4.]], vec![1., 2.]).is_none());
+        assert!(solve(vec![vec![f64::NAN, 2.], vec![2., 4.]], vec![1., 2.]).is_none());
+        // Does not panic on NaN.
+        symmetric_eigen(&[[f64::NAN, 1., 0.], [1., 2., 0.], [0., 0., 5.]]);
+    }
+
+    #[test]
+    fn closest_point_regions() {
+        let t = [[0., 0., 0.], [2., 0., 0.], [0., 2., 0.]];
+        let cases = [
+            ([0.5, 0.5, 3.], [0.5, 0.5, 0.]),
+            ([-1., -1., 0.], [0., 0., 0.]),
+            ([3., -1., 0.], [2., 0., 0.]),
+            ([1., -1., 1.], [1., 0., 0.]),
+            ([2., 2., 0.], [1., 1., 0.]),
+            ([-1., 1., 0.], [0., 1., 0.]),
+        ];
+        for &(p, expected) in &cases {
+            assert_eq!(closest_point_on_triangle(&p, &t), expected, "{:?}", p);
+        }
+        let degenerate = [[0., 0., 0.], [1., 0., 0.], [2., 0., 0.]];
+        assert_eq!(
+            closest_point_on_triangle(&[1.5, 1., 0.], &degenerate),
+            [1.5, 0., 0.]
+        );
+    }
+
+    #[test]
+    fn ray_triangle_hits_and_misses() {
+        let t = [[0., 0., 0.], [2., 0., 0.], [0., 2., 0.]];
+        assert_eq!(ray_triangle(&[0.5, 0.5, 2.], &[0., 0., -0.5], &t), Some(4.));
+        assert_eq!(ray_triangle(&[0.5, 0.5, -2.], &[0., 0., 1.], &t), Some(2.));
+        assert_eq!(ray_triangle(&[0.5, 0.5, 2.], &[0., 0., 1.], &t), None);
+        assert_eq!(ray_triangle(&[1.5, 1.5, 2.], &[0., 0., -1.], &t), None);
+        assert_eq!(ray_triangle(&[0.5, 0.5, 0.], &[1., 0., 0.], &t), None);
+    }
+
+    #[test]
+    fn random_is_reproducible_and_uniform() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+        let values = (0..1000).map(|_| a.next_f64()).collect::<Vec<_>>();
+        assert!(values
+            .iter()
+            .all(|&x| x == b.next_f64() && (0. ..1.).contains(&x)));
+        let mean = values.iter().sum::<f64>() / 1000.;
+        assert!((mean - 0.5).abs() < 0.05);
+        assert_ne!(Random::new(43).next_u64(), Random::new(42).next_u64());
+    }
+
+    #[test]
+    fn rotation_matrix() {
+        let r = rotation(&[0., 0., ::std::f64::consts::FRAC_PI_2]);
+        let x = r.map(|row| dot(&row, &[1., 0., 0.]));
+        assert!(norm(&sub(&x, &[0., 1., 0.])) < 1e-12);
+        let identity = multiply(&r, &rotation(&[0., 0., -::std::f64::consts::FRAC_PI_2]));
+        assert!((0..3).all(|i| (identity[i][i] - 1.).abs() < 1e-12));
+    }
+
+    #[test]
+    fn rotations_to_z() {
+        for up in [[0., 0., 1.], [0., 0., -1.], [1., 0., 0.], [0.6, 0., 0.8]] {
+            let r = rotation_to_z(&up);
+            let z = r.map(|row| dot(&row, &up));
+            assert!(norm(&sub(&z, &[0., 0., 1.])) < 1e-12);
+        }
+    }
+}
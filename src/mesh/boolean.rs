@@ -0,0 +1,497 @@
+//! Boolean operations on closed meshes.
+//!
+//! The operations follow the BSP tree approach of [csg.js](https://github.com/evanw/csg.js):
+//! each mesh is clipped against a BSP tree of the other one in f64 precision, and the
+//! resulting vertices are snapped back to f32 and merged. The results are closed, but may
+//! contain T-junctions where faces of the two inputs were split differently.
+//!
+//! ```
+//! let tetrahedron = stl_io::IndexedMesh {
+//!     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+//!     faces: vec![
+//!         stl_io::IndexedTriangle { normal: [0., 0., -1.], vertices: [0, 2, 1] },
+//!         stl_io::IndexedTriangle { normal: [0., -1., 0.], vertices: [0, 1, 3] },
+//!         stl_io::IndexedTriangle { normal: [-1., 0., 0.], vertices: [0, 3, 2] },
+//!         stl_io::IndexedTriangle { normal: [1., 1., 1.], vertices: [1, 2, 3] },
+//!     ],
+//! };
+//! let empty = stl_io::mesh::boolean::difference(&tetrahedron, &tetrahedron);
+//! assert!(empty.faces.is_empty());
+//! ```
+
+use crate::math::{add, area_normal, dot, normalize, scale, sub, to_f32, to_f64, Vec3};
+use crate::{IndexedMesh, IndexedMeshBuilder, Triangle};
+
+/// Volume covered by a or b.
+pub fn union(a: &IndexedMesh, b: &IndexedMesh) -> IndexedMesh {
+    let epsilon = epsilon(a, b);
+    let mut a = Tree::new(polygons(a, epsilon), epsilon);
+    let mut b = Tree::new(polygons(b, epsilon), epsilon);
+    a.clip_to(&b);
+    b.clip_to(&a);
+    b.invert();
+    b.clip_to(&a);
+    b.invert();
+    a.build(b.all_polygons());
+    to_mesh(a.all_polygons())
+}
+
+/// Volume covered by both a and b.
+pub fn intersection(a: &IndexedMesh, b: &IndexedMesh) -> IndexedMesh {
+    let epsilon = epsilon(a, b);
+    let mut a = Tree::new(polygons(a, epsilon), epsilon);
+    let mut b = Tree::new(polygons(b, epsilon), epsilon);
+    a.invert();
+    b.clip_to(&a);
+    b.invert();
+    a.clip_to(&b);
+    b.clip_to(&a);
+    a.build(b.all_polygons());
+    a.invert();
+    to_mesh(a.all_polygons())
+}
+
+/// Volume covered by a but not by b.
+pub fn difference(a: &IndexedMesh, b: &IndexedMesh) -> IndexedMesh {
+    let epsilon = epsilon(a, b);
+    let mut a = Tree::new(polygons(a, epsilon), epsilon);
+    let mut b = Tree::new(polygons(b, epsilon), epsilon);
+    a.invert();
+    a.clip_to(&b);
+    b.clip_to(&a);
+    b.invert();
+    b.clip_to(&a);
+    b.invert();
+    a.build(b.all_polygons());
+    a.invert();
+    to_mesh(a.all_polygons())
+}
+
+// Tolerance for classifying points as on a plane, relative to the size of the inputs.
+fn epsilon(a: &IndexedMesh, b: &IndexedMesh) -> f64 {
+    let extent = a
+        .vertices
+        .iter()
+        .chain(&b.vertices)
+        .flat_map(|v| v.iter())
+        .fold(0f64, |m, &c| m.max(f64::from(c).abs()));
+    extent.max(1.) * 1e-6
+}
+
+#[derive(Clone, Copy)]
+struct Plane {
+    normal: Vec3,
+    w: f64,
+}
+
+impl Plane {
+    fn through(points: &[Vec3; 3]) -> Option<Plane> {
+        let normal = normalize(&area_normal(points))?;
+        Some(Plane {
+            normal,
+            w: dot(&normal, &points[0]),
+        })
+    }
+
+    fn flip(&mut self) {
+        self.normal = scale(&self.normal, -1.);
+        self.w = -self.w;
+    }
+}
+
+#[derive(Clone)]
+struct Polygon {
+    vertices: Vec<Vec3>,
+    plane: Plane,
+}
+
+impl Polygon {
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane.flip();
+    }
+}
+
+fn polygons(mesh: &IndexedMesh, epsilon: f64) -> Vec<Polygon> {
+    mesh.faces
+        .iter()
+        .filter_map(|f| {
+            let vertices = [
+                to_f64(&mesh.vertices[f.vertices[0]]),
+                to_f64(&mesh.vertices[f.vertices[1]]),
+                to_f64(&mesh.vertices[f.vertices[2]]),
+            ];
+            // Skip slivers whose plane cannot be trusted.
+            if dot(&area_normal(&vertices), &area_normal(&vertices)).sqrt() < epsilon * epsilon {
+                return None;
+            }
+            Some(Polygon {
+                plane: Plane::through(&vertices)?,
+                vertices: vertices.to_vec(),
+            })
+        })
+        .collect()
+}
+
+fn to_mesh(polygons: Vec<Polygon>) -> IndexedMesh {
+    let mut builder = IndexedMeshBuilder::default();
+    for p in polygons {
+        for i in 1..p.vertices.len() - 1 {
+            let vertices = [
+                to_f32(&p.vertices[0]),
+                to_f32(&p.vertices[i]),
+                to_f32(&p.vertices[i + 1]),
+            ];
+            // Snapping to f32 may collapse tiny triangles.
+            if vertices[0] == vertices[1]
+                || vertices[1] == vertices[2]
+                || vertices[2] == vertices[0]
+            {
+                continue;
+            }
+            builder.push(&Triangle {
+                normal: to_f32(&p.plane.normal),
+                vertices,
+            });
+        }
+    }
+    builder.build()
+}
+
+// Polygons split by a plane, with polygons in the plane sorted by their orientation.
+#[derive(Default)]
+struct Split {
+    coplanar_front: Vec<Polygon>,
+    coplanar_back: Vec<Polygon>,
+    front: Vec<Polygon>,
+    back: Vec<Polygon>,
+}
+
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+fn split_polygon(plane: &Plane, polygon: Polygon, epsilon: f64, split: &mut Split) {
+    let classify = |v: &Vec3| {
+        let t = dot(&plane.normal, v) - plane.w;
+        if t < -epsilon {
+            BACK
+        } else if t > epsilon {
+            FRONT
+        } else {
+            COPLANAR
+        }
+    };
+    // Most polygons are not split, so only those that are allocate their vertex types.
+    let polygon_type = polygon
+        .vertices
+        .iter()
+        .fold(COPLANAR, |a, v| a | classify(v));
+    match polygon_type {
+        COPLANAR => {
+            if dot(&plane.normal, &polygon.plane.normal) > 0. {
+                split.coplanar_front.push(polygon)
+            } else {
+                split.coplanar_back.push(polygon)
+            }
+        }
+        FRONT => split.front.push(polygon),
+        BACK => split.back.push(polygon),
+        _ => {
+            let types = polygon.vertices.iter().map(classify).collect::<Vec<_>>();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            let n = polygon.vertices.len();
+            for i in 0..n {
+                let j = (i + 1) % n;
+                let (vi, vj) = (polygon.vertices[i], polygon.vertices[j]);
+                if types[i] != BACK {
+                    front.push(vi);
+                }
+                if types[i] != FRONT {
+                    back.push(vi);
+                }
+                if types[i] | types[j] == SPANNING {
+                    let edge = sub(&vj, &vi);
+                    let t = (plane.w - dot(&plane.normal, &vi)) / dot(&plane.normal, &edge);
+                    let v = add(&vi, &scale(&edge, t));
+                    front.push(v);
+                    back.push(v);
+                }
+            }
+            if front.len() >= 3 {
+                split.front.push(Polygon {
+                    vertices: front,
+                    plane: polygon.plane,
+                });
+            }
+            if back.len() >= 3 {
+                split.back.push(Polygon {
+                    vertices: back,
+                    plane: polygon.plane,
+                });
+            }
+        }
+    }
+}
+
+// Nodes with at least this many polygons are divided by an axis aligned plane if possible.
+const DIVIDE_POLYGONS: usize = 32;
+
+// BSP tree, with the nodes stored in a Vec and referring to their children by index. Polygons
+// in front of the plane of a node are stored under front, polygons behind it under back, and
+// polygons within it in the node itself.
+//
+// Planes through polygons alone give trees as deep as convex inputs have faces, so large sets
+// of polygons are first divided at the median of their centroids along an axis. Such nodes
+// hold no polygons and always have both children, so below them each child describes the
+// solid within its half space as usual. The tree is still traversed with explicit stacks, as
+// it can be deep where division is not possible.
+struct Tree {
+    nodes: Vec<Node>,
+    epsilon: f64,
+}
+
+#[derive(Default)]
+struct Node {
+    plane: Option<Plane>,
+    // Whether the plane only divides space, and is not the plane of any polygon.
+    divides: bool,
+    front: Option<usize>,
+    back: Option<usize>,
+    polygons: Vec<Polygon>,
+}
+
+impl Tree {
+    fn new(polygons: Vec<Polygon>, epsilon: f64) -> Tree {
+        let mut tree = Tree {
+            nodes: vec![Node::default()],
+            epsilon,
+        };
+        tree.build(polygons);
+        tree
+    }
+
+    // Swaps solid and empty space.
+    fn invert(&mut self) {
+        for node in &mut self.nodes {
+            for p in &mut node.polygons {
+                p.flip();
+            }
+            if let Some(plane) = &mut node.plane {
+                plane.flip();
+            }
+            ::std::mem::swap(&mut node.front, &mut node.back);
+        }
+    }
+
+    // Removes the parts of polygons inside the solid this tree describes.
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let mut clipped = Vec::new();
+        let mut stack = vec![(0, polygons)];
+        while let Some((i, polygons)) = stack.pop() {
+            let node = &self.nodes[i];
+            let plane = match &node.plane {
+                Some(plane) => plane,
+                None => {
+                    clipped.extend(polygons);
+                    continue;
+                }
+            };
+            let mut split = Split::default();
+            for p in polygons {
+                split_polygon(plane, p, self.epsilon, &mut split);
+            }
+            let mut front = split.front;
+            front.append(&mut split.coplanar_front);
+            let mut back = split.back;
+            back.append(&mut split.coplanar_back);
+            // Polygons behind a leaf are inside the solid and dropped.
+            if let (Some(child), false) = (node.back, back.is_empty()) {
+                stack.push((child, back));
+            }
+            match node.front {
+                Some(child) if !front.is_empty() => stack.push((child, front)),
+                Some(_) => {}
+                None => clipped.append(&mut front),
+            }
+        }
+        clipped
+    }
+
+    // Removes the parts of all polygons in this tree inside the solid of other.
+    fn clip_to(&mut self, other: &Tree) {
+        for node in &mut self.nodes {
+            node.polygons = other.clip_polygons(::std::mem::take(&mut node.polygons));
+        }
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.polygons.iter().cloned())
+            .collect()
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        let mut stack = vec![(0, polygons)];
+        while let Some((i, polygons)) = stack.pop() {
+            if polygons.is_empty() {
+                continue;
+            }
+            let node = &mut self.nodes[i];
+            let plane = match node.plane {
+                Some(plane) => plane,
+                None => {
+                    let divider = divider(&polygons, self.epsilon);
+                    node.divides = divider.is_some();
+                    *node.plane.insert(divider.unwrap_or(polygons[0].plane))
+                }
+            };
+            let mut split = Split::default();
+            for p in polygons {
+                split_polygon(&plane, p, self.epsilon, &mut split);
+            }
+            if node.divides {
+                split.front.append(&mut split.coplanar_front);
+                split.back.append(&mut split.coplanar_back);
+            } else {
+                node.polygons.append(&mut split.coplanar_front);
+                node.polygons.append(&mut split.coplanar_back);
+            }
+            for (polygons, is_front) in [(split.front, true), (split.back, false)] {
+                if polygons.is_empty() {
+                    continue;
+                }
+                let next = self.nodes.len();
+                let node = &mut self.nodes[i];
+                let child = if is_front {
+                    &mut node.front
+                } else {
+                    &mut node.back
+                };
+                let child = *child.get_or_insert(next);
+                if child == next {
+                    self.nodes.push(Node::default());
+                }
+                stack.push((child, polygons));
+            }
+        }
+    }
+}
+
+// Axis aligned plane through the median of the centroids of polygons along their longest
+// extent, if it leaves fewer polygons on each side, with few of them split and none within it.
+fn divider(polygons: &[Polygon], epsilon: f64) -> Option<Plane> {
+    let n = polygons.len();
+    if n < DIVIDE_POLYGONS {
+        return None;
+    }
+    let (min, max) = polygons.iter().flat_map(|p| &p.vertices).fold(
+        ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]),
+        |(lo, hi), v| {
+            (
+                [0, 1, 2].map(|i| lo[i].min(v[i])),
+                [0, 1, 2].map(|i| hi[i].max(v[i])),
+            )
+        },
+    );
+    let axis = (0..3).max_by(|&a, &b| (max[a] - min[a]).total_cmp(&(max[b] - min[b])))?;
+    let mut centroids = polygons
+        .iter()
+        .map(|p| p.vertices.iter().map(|v| v[axis]).sum::<f64>() / p.vertices.len() as f64)
+        .collect::<Vec<_>>();
+    let (_, &mut median, _) = centroids.select_nth_unstable_by(n / 2, f64::total_cmp);
+    let mut normal = [0.; 3];
+    normal[axis] = 1.;
+    let (mut front, mut back) = (0, 0);
+    for p in polygons {
+        let mut polygon_type = COPLANAR;
+        for v in &p.vertices {
+            let t = v[axis] - median;
+            polygon_type |= if t < -epsilon {
+                BACK
+            } else if t > epsilon {
+                FRONT
+            } else {
+                COPLANAR
+            };
+        }
+        if polygon_type == COPLANAR {
+            return None;
+        }
+        front += usize::from(polygon_type & FRONT != 0);
+        back += usize::from(polygon_type & BACK != 0);
+    }
+    if front == 0 || back == 0 || front.max(back) >= n || front + back > n + n / 4 {
+        return None;
+    }
+    Some(Plane { normal, w: median })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{cube, volume};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn deep_trees() {
+        // Convex inputs give one tree level per face.
+        let a = crate::mesh::primitives::icosphere(1., 5);
+        assert_eq!(a.faces.len(), 20_480);
+        let mut b = a.clone();
+        for v in &mut b.vertices {
+            v[0] += 1.;
+        }
+        // Two unit balls overlapping in a lens of volume 5π / 12.
+        let (ball, lens) = (4. / 3. * PI, 5. / 12. * PI);
+        let cases = [
+            (union(&a, &b), 2. * ball - lens),
+            (intersection(&a, &b), lens),
+            (difference(&a, &b), ball - lens),
+        ];
+        for (mesh, expected) in &cases {
+            let v = volume(mesh);
+            assert!((v - expected).abs() < 0.01 * expected, "{} {}", v, expected);
+        }
+    }
+
+    #[test]
+    fn overlapping_cubes() {
+        let a = cube(0., 1.);
+        let b = cube(0.5, 1.);
+        let cases = [
+            (union(&a, &b), 2. - 0.125),
+            (intersection(&a, &b), 0.125),
+            (difference(&a, &b), 1. - 0.125),
+            (difference(&b, &a), 1. - 0.125),
+        ];
+        for (i, (mesh, expected)) in cases.iter().enumerate() {
+            assert!(
+                (volume(mesh) - expected).abs() < 1e-6,
+                "case {}: {} != {}",
+                i,
+                volume(mesh),
+                expected
+            );
+        }
+        let inner = intersection(&a, &b);
+        assert!(inner
+            .vertices
+            .iter()
+            .all(|v| v.iter().all(|&c| (0.5..=1.).contains(&c))));
+    }
+
+    #[test]
+    fn disjoint_and_contained() {
+        let a = cube(0., 1.);
+        let far = cube(5., 1.);
+        assert!(intersection(&a, &far).faces.is_empty());
+        assert!((volume(&union(&a, &far)) - 2.).abs() < 1e-9);
+        let inner = cube(0.25, 0.5);
+        assert!((volume(&difference(&a, &inner)) - 0.875).abs() < 1e-9);
+        assert!((volume(&intersection(&a, &inner)) - 0.125).abs() < 1e-9);
+    }
+}
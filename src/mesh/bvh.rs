@@ -0,0 +1,358 @@
+use crate::math::{closest_point_on_triangle, norm, ray_triangle, sub, to_f32, to_f64, Vec3};
+use crate::{BoundingBox, IndexedMesh, Vertex};
+
+const LEAF_SIZE: usize = 4;
+
+/// Point on a mesh surface found by [Bvh::closest_point](struct.Bvh.html#method.closest_point).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestPoint {
+    /// Index of the face the point lies on.
+    pub face: usize,
+    /// The point on the surface.
+    pub point: Vertex,
+    /// Distance between the point and the query point.
+    pub distance: f32,
+}
+
+/// Bounding volume hierarchy over the faces of a mesh, for fast spatial queries.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let bvh = stl_io::mesh::Bvh::build(&mesh);
+/// let closest = bvh.closest_point(&[0.25, 0.25, 2.]).unwrap();
+/// assert_eq!(closest.point, [0.25, 0.25, 0.]);
+/// assert_eq!(closest.distance, 2.);
+/// ```
+pub struct Bvh<'a> {
+    mesh: &'a IndexedMesh,
+    // Face indices, ordered so that every node covers a contiguous range.
+    faces: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    // Leaves cover faces[start..start + len]. Inner nodes have len == 0 and their children
+    // at start and start + 1.
+    start: usize,
+    len: usize,
+}
+
+impl<'a> Bvh<'a> {
+    /// Builds the hierarchy by recursively splitting the faces at the median along the longest
+    /// axis of their bounding box.
+    pub fn build(mesh: &'a IndexedMesh) -> Bvh<'a> {
+        let mut bvh = Bvh {
+            mesh,
+            faces: (0..mesh.faces.len()).collect(),
+            nodes: Vec::new(),
+        };
+        if !mesh.faces.is_empty() {
+            let centroids = mesh
+                .faces
+                .iter()
+                .map(|f| {
+                    let mut c = [0.; 3];
+                    for &v in &f.vertices {
+                        for (c, x) in c.iter_mut().zip(&to_f64(&mesh.vertices[v])) {
+                            *c += x / 3.;
+                        }
+                    }
+                    c
+                })
+                .collect::<Vec<Vec3>>();
+            bvh.nodes.push(bvh.leaf(0, mesh.faces.len()));
+            bvh.split(0, &centroids);
+        }
+        bvh
+    }
+
+    /// The mesh this hierarchy was built for.
+    pub fn mesh(&self) -> &'a IndexedMesh {
+        self.mesh
+    }
+
+    /// Bounding box of all faces, or None if the mesh has no faces.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.nodes.first().map(|root| BoundingBox {
+            min: to_f32(&root.min),
+            max: to_f32(&root.max),
+        })
+    }
+
+    /// Closest point to point on the surface of the mesh, or None if the mesh has no faces.
+    pub fn closest_point(&self, point: &Vertex) -> Option<ClosestPoint> {
+        self.nearest(&to_f64(point))
+            .map(|(face, point, distance)| ClosestPoint {
+                face,
+                point: to_f32(&point),
+                distance: distance as f32,
+            })
+    }
+
+    /// Index of the face closest to point, or None if the mesh has no faces.
+    pub fn nearest_triangle(&self, point: &Vertex) -> Option<usize> {
+        self.nearest(&to_f64(point)).map(|(face, _, _)| face)
+    }
+
+    /// Indices of the faces whose bounding boxes overlap bbox, in ascending order.
+    pub fn query_box(&self, bbox: &BoundingBox) -> Vec<usize> {
+        let (min, max) = (to_f64(&bbox.min), to_f64(&bbox.max));
+        let overlaps =
+            |a_min: &Vec3, a_max: &Vec3| (0..3).all(|i| a_min[i] <= max[i] && min[i] <= a_max[i]);
+        let mut faces = Vec::new();
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if !overlaps(&node.min, &node.max) {
+                continue;
+            }
+            if node.len > 0 {
+                for &f in &self.faces[node.start..node.start + node.len] {
+                    let t = self.triangle(f);
+                    let mut f_min = t[0];
+                    let mut f_max = t[0];
+                    for p in &t[1..] {
+                        for i in 0..3 {
+                            f_min[i] = f_min[i].min(p[i]);
+                            f_max[i] = f_max[i].max(p[i]);
+                        }
+                    }
+                    if overlaps(&f_min, &f_max) {
+                        faces.push(f);
+                    }
+                }
+            } else {
+                stack.push(node.start);
+                stack.push(node.start + 1);
+            }
+        }
+        faces.sort_unstable();
+        faces
+    }
+
+    pub(crate) fn triangle(&self, face: usize) -> [Vec3; 3] {
+        let v = &self.mesh.faces[face].vertices;
+        [
+            to_f64(&self.mesh.vertices[v[0]]),
+            to_f64(&self.mesh.vertices[v[1]]),
+            to_f64(&self.mesh.vertices[v[2]]),
+        ]
+    }
+
+    fn leaf(&self, start: usize, len: usize) -> Node {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for &f in &self.faces[start..start + len] {
+            for p in &self.triangle(f) {
+                for i in 0..3 {
+                    min[i] = min[i].min(p[i]);
+                    max[i] = max[i].max(p[i]);
+                }
+            }
+        }
+        Node {
+            min,
+            max,
+            start,
+            len,
+        }
+    }
+
+    // Splits the leaf at index node at the median centroid along its longest axis.
+    fn split(&mut self, node: usize, centroids: &[Vec3]) {
+        let (start, len) = (self.nodes[node].start, self.nodes[node].len);
+        if len <= LEAF_SIZE {
+            return;
+        }
+        let extent = sub(&self.nodes[node].max, &self.nodes[node].min);
+        let axis = (0..3)
+            .max_by(|&a, &b| extent[a].total_cmp(&extent[b]))
+            .unwrap();
+        let mid = len / 2;
+        self.faces[start..start + len].select_nth_unstable_by(mid, |&a, &b| {
+            centroids[a][axis].total_cmp(&centroids[b][axis])
+        });
+        let left = self.nodes.len();
+        let left_node = self.leaf(start, mid);
+        let right_node = self.leaf(start + mid, len - mid);
+        self.nodes.push(left_node);
+        self.nodes.push(right_node);
+        self.nodes[node].start = left;
+        self.nodes[node].len = 0;
+        self.split(left, centroids);
+        self.split(left + 1, centroids);
+    }
+
+    // Closest point on the mesh surface to p, as (face, point, distance).
+    pub(crate) fn nearest(&self, p: &Vec3) -> Option<(usize, Vec3, f64)> {
+        let mut best: Option<(usize, Vec3, f64)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            let bound = box_distance(p, &node.min, &node.max);
+            if best.is_some_and(|(_, _, d)| bound >= d) {
+                continue;
+            }
+            if node.len > 0 {
+                for &f in &self.faces[node.start..node.start + node.len] {
+                    let q = closest_point_on_triangle(p, &self.triangle(f));
+                    let d = norm(&sub(&q, p));
+                    if !best.is_some_and(|(_, _, best)| d >= best) {
+                        best = Some((f, q, d));
+                    }
+                }
+            } else {
+                // Visit the closer child first by pushing it last.
+                let (a, b) = (node.start, node.start + 1);
+                let da = box_distance(p, &self.nodes[a].min, &self.nodes[a].max);
+                let db = box_distance(p, &self.nodes[b].min, &self.nodes[b].max);
+                if da < db {
+                    stack.push(b);
+                    stack.push(a);
+                } else {
+                    stack.push(a);
+                    stack.push(b);
+                }
+            }
+        }
+        best
+    }
+
+    // Calls hit(face, t) for the faces hit by the ray origin + t * direction with t <= max_t.
+    // hit returns the new max_t, which allows pruning when only the nearest hit matters.
+    pub(crate) fn ray<F>(&self, origin: &Vec3, direction: &Vec3, mut max_t: f64, mut hit: F)
+    where
+        F: FnMut(usize, f64) -> f64,
+    {
+        let inverse = [1. / direction[0], 1. / direction[1], 1. / direction[2]];
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if !ray_hits_box(origin, &inverse, max_t, &node.min, &node.max) {
+                continue;
+            }
+            if node.len > 0 {
+                for &f in &self.faces[node.start..node.start + node.len] {
+                    if let Some(t) = ray_triangle(origin, direction, &self.triangle(f)) {
+                        if t <= max_t {
+                            max_t = hit(f, t);
+                        }
+                    }
+                }
+            } else {
+                stack.push(node.start);
+                stack.push(node.start + 1);
+            }
+        }
+    }
+}
+
+// Slab test for the ray origin + t * direction with 0 <= t <= max_t, given 1 / direction.
+fn ray_hits_box(origin: &Vec3, inverse: &Vec3, max_t: f64, min: &Vec3, max: &Vec3) -> bool {
+    let (mut near, mut far) = (0., max_t);
+    for i in 0..3 {
+        if inverse[i].is_infinite() {
+            // The ray is parallel to the slab.
+            if origin[i] < min[i] || origin[i] > max[i] {
+                return false;
+            }
+            continue;
+        }
+        let a = (min[i] - origin[i]) * inverse[i];
+        let b = (max[i] - origin[i]) * inverse[i];
+        near = a.min(b).max(near);
+        far = a.max(b).min(far);
+    }
+    near <= far
+}
+
+fn box_distance(p: &Vec3, min: &Vec3, max: &Vec3) -> f64 {
+    let mut d2 = 0.;
+    for i in 0..3 {
+        let d = (min[i] - p[i]).max(0.).max(p[i] - max[i]);
+        d2 += d * d;
+    }
+    d2.sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn closest_point_matches_brute_force() {
+        let mesh = crate::create_stl_reader(&mut ::std::io::Cursor::new(BUNNY_99))
+            .unwrap()
+            .to_indexed_triangles()
+            .unwrap();
+        let bvh = Bvh::build(&mesh);
+        for i in 0..50 {
+            let t = f64::from(i);
+            let p = [
+                (t * 0.37).sin() * 0.1,
+                (t * 0.71).cos() * 0.1 + 0.1,
+                (t * 0.13).sin() * 0.1,
+            ];
+            let brute = (0..mesh.faces.len())
+                .map(|f| norm(&sub(&closest_point_on_triangle(&p, &bvh.triangle(f)), &p)))
+                .fold(f64::INFINITY, f64::min);
+            let (_, _, d) = bvh.nearest(&p).unwrap();
+            assert_eq!(d, brute, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn build_with_nan_vertices() {
+        let mut mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        for f in mesh.faces.iter().step_by(3) {
+            mesh.vertices[f.vertices[0]][1] = f32::NAN;
+        }
+        let bvh = Bvh::build(&mesh);
+        assert!(bvh.closest_point(&[0., 0.1, 0.]).is_some());
+        assert!(!bvh.query_box(&bvh.bounding_box().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn query_box_matches_brute_force() {
+        let mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let bvh = Bvh::build(&mesh);
+        let bbox = bvh.bounding_box().unwrap();
+        assert_eq!(
+            bvh.query_box(&bbox),
+            (0..mesh.faces.len()).collect::<Vec<_>>()
+        );
+        let center = [
+            (bbox.min[0] + bbox.max[0]) / 2.,
+            (bbox.min[1] + bbox.max[1]) / 2.,
+            (bbox.min[2] + bbox.max[2]) / 2.,
+        ];
+        let query = BoundingBox {
+            min: bbox.min,
+            max: center,
+        };
+        let expected = (0..mesh.faces.len())
+            .filter(|&f| {
+                let vertices = mesh.faces[f].vertices.iter().map(|&v| mesh.vertices[v]);
+                let f_box = BoundingBox::from_vertices(vertices).unwrap();
+                (0..3).all(|i| f_box.min[i] <= query.max[i] && query.min[i] <= f_box.max[i])
+            })
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty() && expected.len() < mesh.faces.len());
+        assert_eq!(bvh.query_box(&query), expected);
+    }
+}
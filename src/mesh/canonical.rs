@@ -0,0 +1,81 @@
+use crate::{IndexedMesh, Vertex};
+use std::cmp::Ordering;
+
+fn compare(a: &Vertex, b: &Vertex) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x.total_cmp(y))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+impl IndexedMesh {
+    /// Sorts vertices and faces into an order that only depends on the geometry, not on the
+    /// order they were read or generated in.
+    ///
+    /// Vertices are sorted by x, then y, then z. Each face is rotated to start at its smallest
+    /// vertex index, which keeps its winding, and faces are sorted by their vertex indices,
+    /// then by normal. Meshes with the same vertices, faces and normals thus become equal, and
+    /// [write_stl](../fn.write_stl.html) writes identical bytes for their faces, which keeps
+    /// generated files stable under version control. Nothing is merged or removed.
+    ///
+    /// ```
+    /// let mut mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[1., 0., 0.], [0., 1., 0.], [0., 0., 0.]],
+    ///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [2, 0, 1] }],
+    /// };
+    /// mesh.canonicalize();
+    /// assert_eq!(mesh.vertices, vec![[0., 0., 0.], [0., 1., 0.], [1., 0., 0.]]);
+    /// assert_eq!(mesh.faces[0].vertices, [0, 2, 1]);
+    /// ```
+    pub fn canonicalize(&mut self) {
+        let mut order = (0..self.vertices.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| compare(&self.vertices[a], &self.vertices[b]));
+        let mut new_index = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        self.vertices = order.iter().map(|&old| self.vertices[old]).collect();
+        for f in &mut self.faces {
+            let v = f.vertices.map(|old| new_index[old]);
+            let first = (0..3).min_by_key(|&i| v[i]).unwrap();
+            f.vertices = [v[first], v[(first + 1) % 3], v[(first + 2) % 3]];
+        }
+        self.faces.sort_by(|a, b| {
+            a.vertices
+                .cmp(&b.vertices)
+                .then_with(|| compare(&a.normal, &b.normal))
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn canonicalize_shuffled_bunny() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let mut shuffled = bunny.clone();
+        // Reverse the vertices, rotate the corners of every face and reorder the faces.
+        let n = shuffled.vertices.len();
+        shuffled.vertices.reverse();
+        for f in &mut shuffled.faces {
+            let v = f.vertices.map(|v| n - 1 - v);
+            f.vertices = [v[1], v[2], v[0]];
+        }
+        shuffled.faces.rotate_left(42);
+        assert_ne!(shuffled, bunny);
+
+        let mut canonical = bunny.clone();
+        canonical.canonicalize();
+        shuffled.canonicalize();
+        assert_eq!(shuffled, canonical);
+        crate::testing::assert_meshes_eq(&canonical, &bunny, 0.);
+
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        crate::write_stl(&mut a, canonical.triangles().iter()).unwrap();
+        crate::write_stl(&mut b, shuffled.triangles().iter()).unwrap();
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,376 @@
+use super::{slice, Plane};
+use crate::math::{add, cross, dot, normalize, scale, sub, to_f32, to_f64, Vec3};
+use crate::{IndexedMesh, IndexedMeshBuilder, Triangle, Vertex};
+
+/// The two parts of a mesh on either side of a [Plane](struct.Plane.html), as returned by
+/// [cut](fn.cut.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Halves {
+    /// The part on the side the plane normal points to.
+    pub above: IndexedMesh,
+    /// The part on the other side.
+    pub below: IndexedMesh,
+}
+
+/// Splits mesh along plane and closes both halves with the triangulated cross section.
+///
+/// Faces crossing the plane are split at the same points [slice](fn.slice.html) reports, and
+/// vertices on the plane count as above it. Only closed contours are capped, so the halves of a
+/// closed mesh are closed, while holes in the mesh stay open. Contours running
+/// counter-clockwise around material are capped as outlines, clockwise ones as holes in the
+/// smallest outline around them.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+///     faces: vec![
+///         stl_io::IndexedTriangle { normal: [0., 0., -1.], vertices: [0, 2, 1] },
+///         stl_io::IndexedTriangle { normal: [0., -1., 0.], vertices: [0, 1, 3] },
+///         stl_io::IndexedTriangle { normal: [-1., 0., 0.], vertices: [0, 3, 2] },
+///         stl_io::IndexedTriangle { normal: [1., 1., 1.], vertices: [1, 2, 3] },
+///     ],
+/// };
+/// let plane = stl_io::mesh::Plane { normal: [0., 0., 1.], distance: 0.5 };
+/// let halves = stl_io::mesh::cut(&mesh, &plane);
+/// // The tip and its cap.
+/// assert_eq!(halves.above.faces.len(), 4);
+/// ```
+pub fn cut(mesh: &IndexedMesh, plane: &Plane) -> Halves {
+    let normal = to_f64(&plane.normal);
+    let offset = dot(&normal, &normal) * f64::from(plane.distance);
+    let distances: Vec<f64> = mesh
+        .vertices
+        .iter()
+        .map(|v| dot(&normal, &to_f64(v)) - offset)
+        .collect();
+    // Same as the crossing points of slice, so that the caps fit the split faces.
+    let crossing = |a: usize, b: usize| -> Vertex {
+        let (a, b) = (a.min(b), a.max(b));
+        let (da, db) = (distances[a], distances[b]);
+        if da == 0. {
+            mesh.vertices[a]
+        } else if db == 0. {
+            mesh.vertices[b]
+        } else {
+            let (pa, pb) = (to_f64(&mesh.vertices[a]), to_f64(&mesh.vertices[b]));
+            to_f32(&add(&pa, &scale(&sub(&pb, &pa), da / (da - db))))
+        }
+    };
+
+    let mut above = IndexedMeshBuilder::default();
+    let mut below = IndexedMeshBuilder::default();
+    for f in &mesh.faces {
+        let v = f.vertices;
+        let side = v.map(|i| distances[i] >= 0.);
+        // The corner alone on its side of the plane, if any.
+        let lone = (0..3).find(|&i| side[i] != side[(i + 1) % 3] && side[i] != side[(i + 2) % 3]);
+        let i = match lone {
+            Some(i) => i,
+            None => {
+                let half = if side[0] { &mut above } else { &mut below };
+                half.push(&Triangle {
+                    normal: f.normal,
+                    vertices: v.map(|i| mesh.vertices[i]),
+                });
+                continue;
+            }
+        };
+        let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+        let (pij, pki) = (crossing(v[i], v[j]), crossing(v[k], v[i]));
+        let (lone_half, other_half) = if side[i] {
+            (&mut above, &mut below)
+        } else {
+            (&mut below, &mut above)
+        };
+        push_proper(lone_half, f.normal, [mesh.vertices[v[i]], pij, pki]);
+        push_proper(
+            other_half,
+            f.normal,
+            [pij, mesh.vertices[v[j]], mesh.vertices[v[k]]],
+        );
+        push_proper(other_half, f.normal, [pij, mesh.vertices[v[k]], pki]);
+    }
+
+    if let Some(n) = normalize(&normal) {
+        let contours: Vec<Vec<Vertex>> = slice(mesh, plane)
+            .into_iter()
+            .filter(|c| c.closed)
+            .map(|c| c.points)
+            .collect();
+        let up = to_f32(&n);
+        let down = to_f32(&scale(&n, -1.));
+        for t in triangulate(&contours, &n) {
+            let [a, b, c] = t.map(|(ci, pi)| contours[ci][pi]);
+            push_proper(&mut below, up, [a, b, c]);
+            push_proper(&mut above, down, [a, c, b]);
+        }
+    }
+    Halves {
+        above: above.build(),
+        below: below.build(),
+    }
+}
+
+// Pushes the triangle unless two of its corners coincide.
+fn push_proper(builder: &mut IndexedMeshBuilder, normal: Vertex, vertices: [Vertex; 3]) {
+    if vertices[0] != vertices[1] && vertices[1] != vertices[2] && vertices[2] != vertices[0] {
+        builder.push(&Triangle { normal, vertices });
+    }
+}
+
+// Point of a contour as (contour index, point index).
+pub(super) type Corner = (usize, usize);
+
+// Triangulates closed contours in the plane with the given unit normal by ear clipping. Holes
+// are joined to their outline by a pair of bridge edges first. The triangles run
+// counter-clockwise when seen from the side normal points to.
+pub(super) fn triangulate(contours: &[Vec<Vertex>], normal: &Vec3) -> Vec<[Corner; 3]> {
+    // Orthonormal basis of the plane, oriented so that u x v = normal.
+    let helper = if normal[0].abs() < 0.9 {
+        [1., 0., 0.]
+    } else {
+        [0., 1., 0.]
+    };
+    let u = normalize(&cross(&helper, normal)).unwrap_or([1., 0., 0.]);
+    let v = cross(normal, &u);
+    let points: Vec<Vec<[f64; 2]>> = contours
+        .iter()
+        .map(|c| {
+            c.iter()
+                .map(|p| {
+                    let p = to_f64(p);
+                    [dot(&p, &u), dot(&p, &v)]
+                })
+                .collect()
+        })
+        .collect();
+    let at = |(ci, pi): Corner| points[ci][pi];
+    let areas: Vec<f64> = points.iter().map(|c| signed_area(c)).collect();
+
+    // Each hole belongs to the smallest outline containing it.
+    let mut holes = vec![Vec::new(); contours.len()];
+    for (hi, hole) in points.iter().enumerate().filter(|(i, _)| areas[*i] < 0.) {
+        let owner = (0..contours.len())
+            .filter(|&oi| areas[oi] > 0. && contains(&points[oi], &hole[0]))
+            .min_by(|&a, &b| areas[a].total_cmp(&areas[b]));
+        if let Some(oi) = owner {
+            holes[oi].push(hi);
+        }
+    }
+
+    let mut triangles = Vec::new();
+    for (oi, outline_holes) in holes.iter_mut().enumerate() {
+        if areas[oi] <= 0. {
+            continue;
+        }
+        let mut polygon: Vec<Corner> = (0..points[oi].len()).map(|pi| (oi, pi)).collect();
+        // Bridge holes from right to left, so that the bridges of later holes cannot cross
+        // holes that are already joined.
+        let rightmost = |hi: usize| {
+            (0..points[hi].len())
+                .max_by(|&a, &b| points[hi][a][0].total_cmp(&points[hi][b][0]))
+                .unwrap()
+        };
+        outline_holes
+            .sort_by(|&a, &b| at((b, rightmost(b)))[0].total_cmp(&at((a, rightmost(a)))[0]));
+        for (n, &hi) in outline_holes.iter().enumerate() {
+            let m = (hi, rightmost(hi));
+            // The bridge must not cross the hole itself or holes still to be joined.
+            let pending = &outline_holes[n..];
+            let bridge = (0..polygon.len())
+                .filter(|&i| {
+                    let pm = at(m);
+                    let len = polygon.len();
+                    let (prev, here, next) = (
+                        at(polygon[(i + len - 1) % len]),
+                        at(polygon[i]),
+                        at(polygon[(i + 1) % len]),
+                    );
+                    in_cone(&prev, &here, &next, &pm)
+                        && !crosses_any(&here, &pm, &polygon, &at)
+                        && !pending.iter().any(|&ph| {
+                            let ring: Vec<Corner> =
+                                (0..points[ph].len()).map(|pi| (ph, pi)).collect();
+                            crosses_any(&here, &pm, &ring, &at)
+                        })
+                })
+                .min_by(|&a, &b| {
+                    distance2(&at(polygon[a]), &at(m))
+                        .total_cmp(&distance2(&at(polygon[b]), &at(m)))
+                });
+            if let Some(b) = bridge {
+                let len = points[hi].len();
+                let mut spliced: Vec<Corner> = (0..=len).map(|k| (hi, (m.1 + k) % len)).collect();
+                spliced.push(polygon[b]);
+                polygon.splice(b + 1..b + 1, spliced);
+            }
+        }
+        clip_ears(polygon, &at, &mut triangles);
+    }
+    triangles
+}
+
+fn clip_ears<F: Fn(Corner) -> [f64; 2]>(
+    mut polygon: Vec<Corner>,
+    at: &F,
+    triangles: &mut Vec<[Corner; 3]>,
+) {
+    let mut i = 0;
+    let mut misses = 0;
+    while polygon.len() > 3 {
+        let len = polygon.len();
+        let (a, b, c) = (
+            polygon[(i + len - 1) % len],
+            polygon[i % len],
+            polygon[(i + 1) % len],
+        );
+        let (pa, pb, pc) = (at(a), at(b), at(c));
+        let is_ear = orientation(&pa, &pb, &pc) > 0.
+            && !polygon.iter().any(|&q| {
+                let pq = at(q);
+                pq != pa && pq != pb && pq != pc && in_triangle(&pa, &pb, &pc, &pq)
+            });
+        // Give up on finding a proper ear once a full round failed, which only happens for
+        // numerically degenerate input.
+        if is_ear || misses > len {
+            triangles.push([a, b, c]);
+            polygon.remove(i % len);
+            misses = 0;
+        } else {
+            i += 1;
+            misses += 1;
+        }
+        i %= polygon.len();
+    }
+    if polygon.len() == 3 {
+        triangles.push([polygon[0], polygon[1], polygon[2]]);
+    }
+}
+
+fn orientation(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn signed_area(points: &[[f64; 2]]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % n]);
+            p[0] * q[1] - q[0] * p[1]
+        })
+        .sum::<f64>()
+        / 2.
+}
+
+fn distance2(a: &[f64; 2], b: &[f64; 2]) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+}
+
+fn in_triangle(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2], p: &[f64; 2]) -> bool {
+    orientation(a, b, p) >= 0. && orientation(b, c, p) >= 0. && orientation(c, a, p) >= 0.
+}
+
+// Whether p lies inside the polygon, by the even-odd rule.
+fn contains(polygon: &[[f64; 2]], p: &[f64; 2]) -> bool {
+    let n = polygon.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        if (a[1] > p[1]) != (b[1] > p[1])
+            && p[0] < a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// Whether the direction from here to p points into the material left of the boundary
+// prev -> here -> next.
+fn in_cone(prev: &[f64; 2], here: &[f64; 2], next: &[f64; 2], p: &[f64; 2]) -> bool {
+    if orientation(prev, here, next) >= 0. {
+        orientation(here, next, p) > 0. && orientation(prev, here, p) > 0.
+    } else {
+        orientation(here, next, p) > 0. || orientation(prev, here, p) > 0.
+    }
+}
+
+// Whether the segment from a to b properly crosses an edge of the ring, ignoring edges that
+// touch either end.
+fn crosses_any<F: Fn(Corner) -> [f64; 2]>(
+    a: &[f64; 2],
+    b: &[f64; 2],
+    ring: &[Corner],
+    at: &F,
+) -> bool {
+    let n = ring.len();
+    (0..n).any(|i| {
+        let (c, d) = (at(ring[i]), at(ring[(i + 1) % n]));
+        if [c, d].iter().any(|p| p == a || p == b) {
+            return false;
+        }
+        let (o1, o2) = (orientation(a, b, &c), orientation(a, b, &d));
+        let (o3, o4) = (orientation(&c, &d, a), orientation(&c, &d, b));
+        o1 * o2 <= 0. && o3 * o4 <= 0.
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assert_closed, assert_meshes_eq, combine, cube, volume};
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    fn z_plane(distance: f32) -> Plane {
+        Plane {
+            normal: [0., 0., 1.],
+            distance,
+        }
+    }
+
+    #[test]
+    fn cut_cube() {
+        let halves = cut(&cube(0., 1.), &z_plane(0.25));
+        assert_closed(&halves.above);
+        assert_closed(&halves.below);
+        assert!((volume(&halves.above) - 0.75).abs() < 1e-6);
+        assert!((volume(&halves.below) - 0.25).abs() < 1e-6);
+        assert!(halves.below.vertices.iter().all(|v| v[2] <= 0.25));
+
+        // Cutting along faces leaves everything on one side.
+        let halves = cut(&cube(0., 1.), &z_plane(0.));
+        assert_meshes_eq(&halves.above, &cube(0., 1.), 0.);
+        assert!(halves.below.faces.is_empty());
+    }
+
+    #[test]
+    fn cut_hollow_cube() {
+        let hollow = combine(&cube(0., 1.), &cube(0.25, -0.5));
+        let halves = cut(&hollow, &z_plane(0.5));
+        assert_closed(&halves.above);
+        assert_closed(&halves.below);
+        assert!((volume(&halves.above) - 0.4375).abs() < 1e-6);
+        assert!((volume(&halves.below) - 0.4375).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cut_bunny() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let plane = Plane {
+            normal: [0.3, 1., 0.2],
+            distance: 0.1,
+        };
+        let halves = cut(&bunny, &plane);
+        // The bunny has a hole in its bottom, which ends up below the plane.
+        let open = |m: &IndexedMesh| {
+            super::super::edge_faces(m)
+                .values()
+                .filter(|faces| faces.len() != 2)
+                .count()
+        };
+        assert_closed(&halves.above);
+        assert_eq!(open(&halves.below), open(&bunny));
+        let sum = volume(&halves.above) + volume(&halves.below);
+        assert!((sum - volume(&bunny)).abs() < 1e-3 * volume(&bunny).abs());
+    }
+}
@@ -0,0 +1,233 @@
+use super::bvh::Bvh;
+use crate::math::{to_f64, Vec3};
+use crate::{IndexedMesh, Vertex};
+use std::collections::HashMap;
+
+/// Differences between two meshes, see [diff](fn.diff.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshDiff {
+    /// Faces of the first mesh without a counterpart in the second mesh.
+    pub removed: Vec<usize>,
+    /// Faces of the second mesh without a counterpart in the first mesh.
+    pub added: Vec<usize>,
+    /// Pairs of faces (first mesh, second mesh) whose corners moved by at most the tolerance.
+    pub moved: Vec<(usize, usize)>,
+    /// Hausdorff distance between the two surfaces, measured at the vertices and face centroids
+    /// of both meshes. Infinite if exactly one of the meshes has no faces.
+    pub hausdorff_distance: f32,
+}
+
+impl MeshDiff {
+    /// Whether the meshes contain the same triangles.
+    pub fn is_identical(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Compares the triangles of two meshes.
+///
+/// Triangles with identical corners in the same winding order are unchanged, regardless of
+/// vertex and face order. Remaining triangles whose corners all moved by at most tolerance in
+/// every coordinate are reported as moved, all others as removed or added.
+///
+/// ```
+/// let a = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let mut b = a.clone();
+/// b.vertices[2][2] = 0.01;
+/// let diff = stl_io::mesh::diff(&a, &b, 0.1);
+/// assert_eq!(diff.moved, vec![(0, 0)]);
+/// assert_eq!(diff.hausdorff_distance, 0.01);
+/// ```
+pub fn diff(a: &IndexedMesh, b: &IndexedMesh, tolerance: f32) -> MeshDiff {
+    let corners = |mesh: &IndexedMesh, f: usize| {
+        let v = &mesh.faces[f].vertices;
+        [
+            mesh.vertices[v[0]],
+            mesh.vertices[v[1]],
+            mesh.vertices[v[2]],
+        ]
+    };
+    let mut b_exact = HashMap::new();
+    for f in 0..b.faces.len() {
+        b_exact
+            .entry(exact_key(&corners(b, f)))
+            .or_insert_with(Vec::new)
+            .push(f);
+    }
+    for faces in b_exact.values_mut() {
+        faces.reverse();
+    }
+    let mut removed = Vec::new();
+    let mut b_matched = vec![false; b.faces.len()];
+    for f in 0..a.faces.len() {
+        match b_exact
+            .get_mut(&exact_key(&corners(a, f)))
+            .and_then(|faces| faces.pop())
+        {
+            Some(g) => b_matched[g] = true,
+            None => removed.push(f),
+        }
+    }
+
+    // Pair up the remaining faces whose corners moved at most by tolerance, using a hash grid of
+    // their centroids.
+    let mut moved = Vec::new();
+    if tolerance > 0. {
+        let cell = |t: &[Vertex; 3]| {
+            let mut c = [0i64; 3];
+            for (i, c) in c.iter_mut().enumerate() {
+                let centroid = (t[0][i] + t[1][i] + t[2][i]) / 3.;
+                *c = (f64::from(centroid) / f64::from(tolerance)).floor() as i64;
+            }
+            c
+        };
+        let mut grid = HashMap::new();
+        for g in (0..b.faces.len()).filter(|&g| !b_matched[g]) {
+            grid.entry(cell(&corners(b, g)))
+                .or_insert_with(Vec::new)
+                .push(g);
+        }
+        removed.retain(|&f| {
+            let t = corners(a, f);
+            let c = cell(&t);
+            let mut candidates = Vec::new();
+            for dx in -1..2 {
+                for dy in -1..2 {
+                    for dz in -1..2 {
+                        if let Some(faces) = grid.get(&[c[0] + dx, c[1] + dy, c[2] + dz]) {
+                            candidates.extend(faces.iter().cloned());
+                        }
+                    }
+                }
+            }
+            candidates.sort_unstable();
+            let found = candidates
+                .into_iter()
+                .find(|&g| !b_matched[g] && moved_within(&t, &corners(b, g), tolerance));
+            match found {
+                Some(g) => {
+                    b_matched[g] = true;
+                    moved.push((f, g));
+                    false
+                }
+                None => true,
+            }
+        });
+    }
+    let added = (0..b.faces.len()).filter(|&g| !b_matched[g]).collect();
+    MeshDiff {
+        removed,
+        added,
+        moved,
+        hausdorff_distance: directed_hausdorff(a, b).max(directed_hausdorff(b, a)) as f32,
+    }
+}
+
+// Rotates the corners so that the smallest one comes first, keeping the winding, and returns
+// their bit patterns.
+fn exact_key(t: &[Vertex; 3]) -> [[u32; 3]; 3] {
+    let bits = |v: &Vertex| [v[0].to_bits(), v[1].to_bits(), v[2].to_bits()];
+    let t = [bits(&t[0]), bits(&t[1]), bits(&t[2])];
+    let first = (0..3).min_by_key(|&i| t[i]).unwrap();
+    [t[first], t[(first + 1) % 3], t[(first + 2) % 3]]
+}
+
+// Whether some rotation of b (keeping the winding) has all corners within tolerance of a.
+fn moved_within(a: &[Vertex; 3], b: &[Vertex; 3], tolerance: f32) -> bool {
+    (0..3).any(|r| {
+        (0..3).all(|i| {
+            a[i].iter()
+                .zip(&b[(i + r) % 3])
+                .all(|(x, y)| (x - y).abs() <= tolerance)
+        })
+    })
+}
+
+// Largest distance from the vertices and face centroids of a to the surface of b.
+fn directed_hausdorff(a: &IndexedMesh, b: &IndexedMesh) -> f64 {
+    if a.faces.is_empty() {
+        return 0.;
+    }
+    if b.faces.is_empty() {
+        return f64::INFINITY;
+    }
+    let bvh = Bvh::build(b);
+    let mut samples = a
+        .faces
+        .iter()
+        .flat_map(|f| f.vertices.iter().cloned())
+        .collect::<Vec<_>>();
+    samples.sort_unstable();
+    samples.dedup();
+    let mut points = samples
+        .iter()
+        .map(|&v| to_f64(&a.vertices[v]))
+        .collect::<Vec<Vec3>>();
+    points.extend(a.faces.iter().map(|f| {
+        let mut c = [0.; 3];
+        for &v in &f.vertices {
+            for (c, x) in c.iter_mut().zip(&to_f64(&a.vertices[v])) {
+                *c += x / 3.;
+            }
+        }
+        c
+    }));
+    points
+        .iter()
+        .map(|p| bvh.nearest(p).unwrap().2)
+        .fold(0., f64::max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::bunny;
+    use crate::IndexedTriangle;
+
+    #[test]
+    fn diff_identical_permuted() {
+        let a = bunny();
+        let mut b = a.clone();
+        b.faces.reverse();
+        for f in &mut b.faces {
+            f.vertices.rotate_left(1);
+        }
+        let d = diff(&a, &b, 0.);
+        assert!(d.is_identical(), "{:?}", d);
+        assert!(d.hausdorff_distance < 1e-6);
+    }
+
+    #[test]
+    fn diff_added_removed_moved() {
+        let a = bunny();
+        let mut b = a.clone();
+        // Move one vertex slightly, replace the last face with a new one.
+        let v = b.faces[10].vertices[0];
+        b.vertices[v][1] += 1e-4;
+        let removed = b.faces.pop().unwrap();
+        let n = b.vertices.len();
+        b.vertices
+            .extend(&[[1., 1., 1.], [1., 2., 1.], [2., 1., 1.]]);
+        b.faces.push(IndexedTriangle {
+            normal: removed.normal,
+            vertices: [n, n + 1, n + 2],
+        });
+        let d = diff(&a, &b, 1e-3);
+        assert_eq!(d.removed, vec![a.faces.len() - 1]);
+        assert_eq!(d.added, vec![b.faces.len() - 1]);
+        assert!(!d.moved.is_empty());
+        assert!(d
+            .moved
+            .iter()
+            .all(|&(f, g)| a.faces[f].vertices.contains(&v) && b.faces[g].vertices.contains(&v)));
+        assert!(d.hausdorff_distance > 0.5);
+
+        // Without tolerance, moved faces are reported as removed and added.
+        let d = diff(&a, &b, 0.);
+        assert!(d.moved.is_empty());
+        assert_eq!(d.removed.len(), d.added.len());
+    }
+}
@@ -0,0 +1,84 @@
+use super::{edge_faces, face_normals};
+use crate::math::dot;
+use crate::IndexedMesh;
+
+/// Undirected edge of an [IndexedMesh](../struct.IndexedMesh.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edge {
+    /// Indices of the two vertices, smaller index first.
+    pub vertices: [usize; 2],
+    /// Whether the edge belongs to exactly one face.
+    pub boundary: bool,
+    /// Whether the normals of two of its faces differ by more than the crease angle.
+    pub crease: bool,
+}
+
+impl IndexedMesh {
+    /// Lists every edge of the mesh once, sorted by vertex indices.
+    /// An edge is a crease if the normals of its faces differ by more than crease_angle
+    /// (in radians).
+    ///
+    /// ```
+    /// let mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+    ///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+    /// };
+    /// let edges = mesh.edges(0.5);
+    /// assert_eq!(edges.len(), 3);
+    /// assert!(edges.iter().all(|e| e.boundary && !e.crease));
+    /// ```
+    pub fn edges(&self, crease_angle: f32) -> Vec<Edge> {
+        let normals = face_normals(self);
+        let cos_crease = f64::from(crease_angle).cos();
+        let mut edges = edge_faces(self)
+            .into_iter()
+            .filter(|&((a, b), _)| a != b)
+            .map(|((a, b), faces)| Edge {
+                vertices: [a, b],
+                boundary: faces.len() == 1,
+                crease: faces.iter().enumerate().any(|(i, &f)| {
+                    faces[i + 1..]
+                        .iter()
+                        .any(|&g| match (normals[f], normals[g]) {
+                            (Some(n), Some(m)) => dot(&n, &m) < cos_crease,
+                            _ => false,
+                        })
+                }),
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by_key(|e| e.vertices);
+        edges
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{IndexedMesh, IndexedTriangle};
+
+    #[test]
+    fn edges_of_folded_quad() {
+        // Two triangles sharing edge 1-2, folded by 90 degrees.
+        let mesh = IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [1., 0., 1.]],
+            faces: vec![
+                IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [0, 1, 2],
+                },
+                IndexedTriangle {
+                    normal: [1., 0., 0.],
+                    vertices: [1, 3, 2],
+                },
+            ],
+        };
+        let edges = mesh.edges(0.5);
+        assert_eq!(
+            edges.iter().map(|e| e.vertices).collect::<Vec<_>>(),
+            vec![[0, 1], [0, 2], [1, 2], [1, 3], [2, 3]]
+        );
+        let shared = &edges[2];
+        assert!(!shared.boundary && shared.crease);
+        assert_eq!(edges.iter().filter(|e| e.boundary).count(), 4);
+        assert!(!mesh.edges(2.).iter().any(|e| e.crease));
+    }
+}
@@ -0,0 +1,149 @@
+use super::cut::triangulate;
+use super::primitives::lathe;
+use crate::math::{dot, normalize, rotation_to_z, to_f32, to_f64};
+use crate::{IndexedMesh, Normal};
+
+/// Extrudes polygons in the xy plane along z, from 0 to height, into a closed solid.
+///
+/// Outlines run counter-clockwise and holes clockwise, as in [slice](fn.slice.html) results
+/// seen from above, so e.g. the outlines of glyphs can be extruded into text. Contours with
+/// fewer than three points are ignored.
+///
+/// ```
+/// let square = vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+/// let cube = stl_io::mesh::extrude(&[square], 1.);
+/// assert_eq!(cube.vertices.len(), 8);
+/// assert_eq!(cube.faces.len(), 12);
+/// ```
+pub fn extrude(contours: &[Vec<[f32; 2]>], height: f32) -> IndexedMesh {
+    let contours = contours.iter().filter(|c| c.len() >= 3).collect::<Vec<_>>();
+    let (bottom, top) = (height.min(0.), height.max(0.));
+    let mut offsets = Vec::with_capacity(contours.len());
+    let mut vertices = Vec::new();
+    for c in &contours {
+        offsets.push(vertices.len());
+        vertices.extend(c.iter().map(|p| [p[0], p[1], bottom]));
+    }
+    let n = vertices.len();
+    let lid = vertices
+        .iter()
+        .map(|v| [v[0], v[1], top])
+        .collect::<Vec<_>>();
+    vertices.extend(lid);
+
+    let mut faces = Vec::new();
+    for (c, &offset) in contours.iter().zip(&offsets) {
+        let len = c.len();
+        for i in 0..len {
+            let (a, b) = (offset + i, offset + (i + 1) % len);
+            faces.push([a, b, b + n]);
+            faces.push([a, b + n, a + n]);
+        }
+    }
+    let planar = contours
+        .iter()
+        .map(|c| c.iter().map(|p| [p[0], p[1], 0.]).collect())
+        .collect::<Vec<_>>();
+    for t in triangulate(&planar, &[0., 0., 1.]) {
+        let [a, b, c] = t.map(|(ci, pi)| offsets[ci] + pi);
+        faces.push([a, c, b]);
+        faces.push([a + n, b + n, c + n]);
+    }
+    IndexedMesh::from_vertices(vertices, faces)
+}
+
+/// Revolves a closed profile around an axis through the origin by angle, in radians, into a
+/// closed solid, e.g. to turn a cross section into a vase or a ring.
+///
+/// Profile points are given as [distance from the axis, position along the axis], with the
+/// profile running counter-clockwise in these coordinates, and may lie on the axis. The
+/// revolution follows the right hand rule around axis, and is approximated by segments steps,
+/// at least 3 for a full turn. Revolutions by less than a full turn are capped at both ends.
+///
+/// Returns an empty mesh if axis is zero.
+///
+/// ```
+/// // A square ring of width 1 around the z axis.
+/// let profile = [[1., 0.], [2., 0.], [2., 1.], [1., 1.]];
+/// let ring = stl_io::mesh::revolve(&profile, &[0., 0., 1.], std::f32::consts::TAU, 32);
+/// assert_eq!(ring.faces.len(), 4 * 2 * 32);
+/// ```
+pub fn revolve(profile: &[[f32; 2]], axis: &Normal, angle: f32, segments: usize) -> IndexedMesh {
+    let axis = match normalize(&to_f64(axis)) {
+        Some(axis) => axis,
+        None => {
+            return IndexedMesh {
+                vertices: Vec::new(),
+                faces: Vec::new(),
+            }
+        }
+    };
+    let profile = profile
+        .iter()
+        .map(|p| [f64::from(p[0]), f64::from(p[1])])
+        .collect::<Vec<_>>();
+    let mesh = lathe(&profile, true, segments, f64::from(angle));
+    // Rotate z onto the axis with the inverse, i.e. transposed, rotation.
+    let rotation = rotation_to_z(&axis);
+    let vertices = mesh.vertices.iter().map(|v| {
+        let v = to_f64(v);
+        to_f32(&[0, 1, 2].map(|i| dot(&[rotation[0][i], rotation[1][i], rotation[2][i]], &v)))
+    });
+    IndexedMesh::from_vertices(vertices, mesh.faces.iter().map(|f| f.vertices))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assert_closed, volume};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn extrude_square_with_hole() {
+        let outline = vec![[0., 0.], [3., 0.], [3., 3.], [0., 3.]];
+        let hole = vec![[1., 1.], [1., 2.], [2., 2.], [2., 1.]];
+        let solid = extrude(&[outline.clone(), hole, vec![[5., 5.]]], 2.);
+        assert_closed(&solid);
+        assert!((volume(&solid) - 16.).abs() < 1e-5);
+        // Extruding downward gives the same solid below the plane.
+        let down = extrude(&[outline], -2.);
+        assert_closed(&down);
+        assert!((volume(&down) - 18.).abs() < 1e-5);
+        assert!(down.vertices.iter().all(|v| v[2] <= 0.));
+    }
+
+    #[test]
+    fn revolve_profiles() {
+        let square = [[1., 0.], [2., 0.], [2., 1.], [1., 1.]];
+        let ring = revolve(&square, &[0., 0., 1.], std::f32::consts::TAU, 256);
+        assert_closed(&ring);
+        assert!((volume(&ring) - 3. * PI).abs() < 0.01);
+
+        // A quarter turn is capped, and turns from the x axis towards the y axis.
+        let quarter = revolve(&square, &[0., 0., 1.], std::f32::consts::FRAC_PI_2, 64);
+        assert_closed(&quarter);
+        assert!((volume(&quarter) - 0.75 * PI).abs() < 0.01);
+        assert!(quarter
+            .vertices
+            .iter()
+            .all(|v| v[0] > -1e-6 && v[1] > -1e-6));
+
+        // A half disk touching the axis becomes a sphere, here around the x axis.
+        let half_disk = (0..=32)
+            .map(|i| {
+                let a = PI * i as f64 / 32.;
+                let r = if i % 32 == 0 { 0. } else { a.sin() as f32 };
+                [r, -a.cos() as f32]
+            })
+            .collect::<Vec<_>>();
+        let sphere = revolve(&half_disk, &[2., 0., 0.], std::f32::consts::TAU, 64);
+        assert_closed(&sphere);
+        assert!((volume(&sphere) - 4. / 3. * PI).abs() < 0.02);
+        assert!(sphere
+            .vertices
+            .iter()
+            .any(|v| (0..3).all(|i| (v[i] - [1., 0., 0.][i]).abs() < 1e-6)));
+
+        assert!(revolve(&square, &[0., 0., 0.], 1., 8).faces.is_empty());
+    }
+}
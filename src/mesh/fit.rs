@@ -0,0 +1,316 @@
+use crate::math::{
+    add, area_normal, cross, dot, norm, normalize, scale, solve, sub, symmetric_eigen, to_f32,
+    to_f64, Vec3,
+};
+use crate::{IndexedMesh, Normal, Vertex};
+
+/// Least squares plane, points x on the plane satisfy `normal · x = distance`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaneFit {
+    /// Unit normal of the plane.
+    pub normal: Normal,
+    /// Signed distance of the plane from the origin along normal.
+    pub distance: f32,
+    /// Root mean square distance of the fitted vertices from the plane.
+    pub rms_error: f32,
+}
+
+/// Least squares sphere.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SphereFit {
+    /// Center of the sphere.
+    pub center: Vertex,
+    /// Radius of the sphere.
+    pub radius: f32,
+    /// Root mean square distance of the fitted vertices from the sphere.
+    pub rms_error: f32,
+}
+
+/// Least squares cylinder of infinite length.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CylinderFit {
+    /// A point on the axis, the projection of the centroid of the fitted vertices.
+    pub point: Vertex,
+    /// Unit direction of the axis.
+    pub axis: Normal,
+    /// Radius of the cylinder.
+    pub radius: f32,
+    /// Root mean square distance of the fitted vertices from the cylinder surface.
+    pub rms_error: f32,
+}
+
+/// Best fitting primitives for a set of faces. A fit is None if the faces do not determine it,
+/// e.g. a sphere needs at least four vertices that are not coplanar.
+/// Compare the rms_error of the fits to find the primitive that describes the faces best.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrimitiveFits {
+    /// Best fitting plane.
+    pub plane: Option<PlaneFit>,
+    /// Best fitting sphere.
+    pub sphere: Option<SphereFit>,
+    /// Best fitting cylinder.
+    pub cylinder: Option<CylinderFit>,
+}
+
+/// Fits a plane, a sphere and a cylinder to the vertices of the selected faces.
+///
+/// The plane minimizes the orthogonal distances. Sphere and cylinder cross section are algebraic
+/// least squares fits. The cylinder axis is the direction most perpendicular to the face normals.
+///
+/// Panics if faces contains an index that is out of range.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 1.], [1., 0., 1.], [0., 1., 1.], [1., 1., 1.]],
+///     faces: vec![
+///         stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] },
+///         stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [2, 1, 3] },
+///     ],
+/// };
+/// let plane = stl_io::mesh::fit_primitives(&mesh, &[0, 1]).plane.unwrap();
+/// assert_eq!(plane.distance.abs(), 1.);
+/// ```
+pub fn fit_primitives(mesh: &IndexedMesh, faces: &[usize]) -> PrimitiveFits {
+    let mut vertex_indices = faces
+        .iter()
+        .flat_map(|&f| mesh.faces[f].vertices.iter().cloned())
+        .collect::<Vec<_>>();
+    vertex_indices.sort_unstable();
+    vertex_indices.dedup();
+    let points = vertex_indices
+        .iter()
+        .map(|&v| to_f64(&mesh.vertices[v]))
+        .collect::<Vec<_>>();
+    let normals = faces
+        .iter()
+        .filter_map(|&f| {
+            let v = &mesh.faces[f].vertices;
+            normalize(&area_normal(&[
+                to_f64(&mesh.vertices[v[0]]),
+                to_f64(&mesh.vertices[v[1]]),
+                to_f64(&mesh.vertices[v[2]]),
+            ]))
+        })
+        .collect::<Vec<_>>();
+    PrimitiveFits {
+        plane: fit_plane(&points),
+        sphere: fit_sphere(&points),
+        cylinder: fit_cylinder(&points, &normals),
+    }
+}
+
+fn centroid(points: &[Vec3]) -> Vec3 {
+    let sum = points.iter().fold([0.; 3], |sum, p| add(&sum, p));
+    scale(&sum, 1. / points.len() as f64)
+}
+
+fn rms(residuals: impl Iterator<Item = f64>, n: usize) -> f32 {
+    (residuals.map(|r| r * r).sum::<f64>() / n as f64).sqrt() as f32
+}
+
+fn fit_plane(points: &[Vec3]) -> Option<PlaneFit> {
+    if points.len() < 3 {
+        return None;
+    }
+    let c = centroid(points);
+    let mut covariance = [[0.; 3]; 3];
+    for p in points {
+        let d = sub(p, &c);
+        for (row, di) in covariance.iter_mut().zip(&d) {
+            for (entry, dj) in row.iter_mut().zip(&d) {
+                *entry += di * dj;
+            }
+        }
+    }
+    let (values, vectors) = symmetric_eigen(&covariance);
+    // All points on a line do not determine a plane.
+    if values[1] <= 1e-12 * values[2] {
+        return None;
+    }
+    let normal = vectors[0];
+    let distance = dot(&normal, &c);
+    Some(PlaneFit {
+        normal: to_f32(&normal),
+        distance: distance as f32,
+        rms_error: rms(
+            points.iter().map(|p| dot(&normal, p) - distance),
+            points.len(),
+        ),
+    })
+}
+
+fn fit_sphere(points: &[Vec3]) -> Option<SphereFit> {
+    if points.len() < 4 {
+        return None;
+    }
+    // Solve |p - c|^2 = r^2, i.e. 2 c·p + (r^2 - |c|^2) = |p|^2, relative to the centroid for
+    // numerical stability.
+    let origin = centroid(points);
+    let mut ata = vec![vec![0.; 4]; 4];
+    let mut atb = vec![0.; 4];
+    for p in points {
+        let p = sub(p, &origin);
+        let row = [2. * p[0], 2. * p[1], 2. * p[2], 1.];
+        let rhs = dot(&p, &p);
+        for i in 0..4 {
+            for j in 0..4 {
+                ata[i][j] += row[i] * row[j];
+            }
+            atb[i] += row[i] * rhs;
+        }
+    }
+    let x = solve(ata, atb)?;
+    let center = [x[0], x[1], x[2]];
+    let radius = (x[3] + dot(&center, &center)).sqrt();
+    if !radius.is_finite() {
+        return None;
+    }
+    let center = add(&center, &origin);
+    Some(SphereFit {
+        center: to_f32(&center),
+        radius: radius as f32,
+        rms_error: rms(
+            points.iter().map(|p| norm(&sub(p, &center)) - radius),
+            points.len(),
+        ),
+    })
+}
+
+fn fit_cylinder(points: &[Vec3], normals: &[Vec3]) -> Option<CylinderFit> {
+    if points.len() < 3 || normals.len() < 2 {
+        return None;
+    }
+    // Normals of a cylinder are perpendicular to its axis.
+    let mut normal_covariance = [[0.; 3]; 3];
+    for n in normals {
+        for (row, ni) in normal_covariance.iter_mut().zip(n) {
+            for (entry, nj) in row.iter_mut().zip(n) {
+                *entry += ni * nj;
+            }
+        }
+    }
+    let (values, vectors) = symmetric_eigen(&normal_covariance);
+    // Normals spanning only one direction (a plane) leave the axis undetermined.
+    if values[1] <= 1e-9 * values[2] {
+        return None;
+    }
+    let axis = vectors[0];
+    let u = normalize(&cross(&axis, &vectors[2]))?;
+    let v = cross(&axis, &u);
+    // Fit a circle to the points projected onto the plane perpendicular to the axis.
+    let origin = centroid(points);
+    let mut ata = vec![vec![0.; 3]; 3];
+    let mut atb = vec![0.; 3];
+    for p in points {
+        let d = sub(p, &origin);
+        let (x, y) = (dot(&d, &u), dot(&d, &v));
+        let row = [2. * x, 2. * y, 1.];
+        for i in 0..3 {
+            for j in 0..3 {
+                ata[i][j] += row[i] * row[j];
+            }
+            atb[i] += row[i] * (x * x + y * y);
+        }
+    }
+    let x = solve(ata, atb)?;
+    let radius = (x[2] + x[0] * x[0] + x[1] * x[1]).sqrt();
+    if !radius.is_finite() {
+        return None;
+    }
+    let point = add(&origin, &add(&scale(&u, x[0]), &scale(&v, x[1])));
+    let distance_to_axis = |p: &Vec3| {
+        let d = sub(p, &point);
+        norm(&sub(&d, &scale(&axis, dot(&d, &axis))))
+    };
+    Some(CylinderFit {
+        point: to_f32(&point),
+        axis: to_f32(&axis),
+        radius: radius as f32,
+        rms_error: rms(
+            points.iter().map(|p| distance_to_axis(p) - radius),
+            points.len(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{IndexedMesh, IndexedTriangle};
+
+    // Grid of (u, v) parameters mapped through f, triangulated into quads.
+    fn parametric_mesh<F: Fn(f32, f32) -> Vertex>(n: usize, f: F) -> IndexedMesh {
+        let mut vertices = Vec::new();
+        for i in 0..=n {
+            for j in 0..=n {
+                vertices.push(f(i as f32 / n as f32, j as f32 / n as f32));
+            }
+        }
+        let mut faces = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                let a = i * (n + 1) + j;
+                let b = a + n + 1;
+                for t in &[[a, b, a + 1], [a + 1, b, b + 1]] {
+                    faces.push(IndexedTriangle {
+                        normal: [0., 0., 0.],
+                        vertices: *t,
+                    });
+                }
+            }
+        }
+        IndexedMesh { vertices, faces }
+    }
+
+    fn all_faces(mesh: &IndexedMesh) -> Vec<usize> {
+        (0..mesh.faces.len()).collect()
+    }
+
+    #[test]
+    fn fit_tilted_plane() {
+        let mesh = parametric_mesh(4, |u, v| [u, v, 0.5 * u + 2.]);
+        let fits = fit_primitives(&mesh, &all_faces(&mesh));
+        let plane = fits.plane.unwrap();
+        assert!(plane.rms_error < 1e-5, "{:?}", plane);
+        let expected = 2. / (1.25f32).sqrt();
+        assert!(
+            (plane.distance.abs() - expected).abs() < 1e-5,
+            "{:?}",
+            plane
+        );
+        assert!(fits.cylinder.is_none());
+    }
+
+    #[test]
+    fn fit_sphere_patch() {
+        let mesh = parametric_mesh(8, |u, v| {
+            let (theta, phi) = (0.3 + 2. * u, 1. + 3. * v);
+            [
+                1. + 2. * theta.sin() * phi.cos(),
+                -1. + 2. * theta.sin() * phi.sin(),
+                3. + 2. * theta.cos(),
+            ]
+        });
+        let fits = fit_primitives(&mesh, &all_faces(&mesh));
+        let sphere = fits.sphere.unwrap();
+        assert!(sphere.rms_error < 1e-4, "{:?}", sphere);
+        assert!((sphere.radius - 2.).abs() < 1e-4, "{:?}", sphere);
+        assert!(norm(&sub(&to_f64(&sphere.center), &[1., -1., 3.])) < 1e-4);
+        assert!(fits.plane.unwrap().rms_error > 0.1);
+    }
+
+    #[test]
+    fn fit_cylinder_patch() {
+        let mesh = parametric_mesh(8, |u, v| {
+            let phi = 4. * u;
+            [3. * v, 5. + 0.5 * phi.cos(), 0.5 * phi.sin()]
+        });
+        let fits = fit_primitives(&mesh, &all_faces(&mesh));
+        let cylinder = fits.cylinder.unwrap();
+        assert!(cylinder.rms_error < 1e-4, "{:?}", cylinder);
+        assert!((cylinder.radius - 0.5).abs() < 1e-4, "{:?}", cylinder);
+        assert!((cylinder.axis[0].abs() - 1.).abs() < 1e-4, "{:?}", cylinder);
+        assert!((cylinder.point[1] - 5.).abs() < 1e-4, "{:?}", cylinder);
+        assert!(cylinder.rms_error < fits.sphere.unwrap().rms_error);
+    }
+}
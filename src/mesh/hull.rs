@@ -0,0 +1,250 @@
+use crate::math::{area_normal, cross, dot, norm, normalize, sub, to_f32, to_f64, Vec3};
+use crate::{IndexedMesh, IndexedTriangle, Vertex};
+use std::collections::{HashMap, HashSet};
+
+struct Face {
+    vertices: [usize; 3],
+    normal: Vec3,
+    offset: f64,
+    // Points in front of this face that are not yet part of the hull.
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl Face {
+    fn new(vertices: [usize; 3], points: &[Vec3]) -> Face {
+        let normal = normalize(&area_normal(&[
+            points[vertices[0]],
+            points[vertices[1]],
+            points[vertices[2]],
+        ]))
+        .unwrap_or([0.; 3]);
+        Face {
+            vertices,
+            normal,
+            offset: dot(&normal, &points[vertices[0]]),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    fn distance(&self, p: &Vec3) -> f64 {
+        dot(&self.normal, p) - self.offset
+    }
+}
+
+/// Computes the convex hull of points with the quickhull algorithm. Pass `&mesh.vertices` to
+/// get the hull of a mesh.
+///
+/// The result has outward facing triangles and contains the points that are corners of the
+/// hull. Points on a flat part of the hull are dropped if the surrounding corners are found
+/// first, but may otherwise remain as vertices. Points with non-finite coordinates are
+/// ignored. If the points do not span a volume, the result is empty.
+///
+/// ```
+/// let points = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.], [0.1, 0.1, 0.1]];
+/// let hull = stl_io::mesh::convex_hull(&points);
+/// assert_eq!(hull.vertices.len(), 4);
+/// assert_eq!(hull.faces.len(), 4);
+/// ```
+pub fn convex_hull(points: &[Vertex]) -> IndexedMesh {
+    let empty = IndexedMesh {
+        vertices: Vec::new(),
+        faces: Vec::new(),
+    };
+    // The finite points, and their indices in points.
+    let (source, p): (Vec<usize>, Vec<Vec3>) = points
+        .iter()
+        .map(to_f64)
+        .enumerate()
+        .filter(|(_, v)| v.iter().all(|c| c.is_finite()))
+        .unzip();
+    let extent = p
+        .iter()
+        .flat_map(|v| v.iter())
+        .fold(0f64, |m, &c| m.max(c.abs()));
+    let epsilon = extent.max(f64::MIN_POSITIVE) * 1e-6;
+    let simplex = match initial_simplex(&p, epsilon) {
+        Some(simplex) => simplex,
+        None => return empty,
+    };
+
+    // Orient the faces of the tetrahedron away from its fourth corner.
+    let [a, b, c, d] = simplex;
+    let mut faces: Vec<Face> = [[a, b, c], [a, c, d], [a, d, b], [b, d, c]]
+        .iter()
+        .map(|&v| Face::new(v, &p))
+        .collect();
+    if faces[0].distance(&p[d]) > 0. {
+        faces = [[a, c, b], [a, d, c], [a, b, d], [b, c, d]]
+            .iter()
+            .map(|&v| Face::new(v, &p))
+            .collect();
+    }
+    let rest: Vec<usize> = (0..p.len()).filter(|i| !simplex.contains(i)).collect();
+    assign(&mut faces, 0, rest, &p, epsilon);
+
+    while let Some(fi) = faces.iter().position(|f| f.alive && !f.outside.is_empty()) {
+        let face = &faces[fi];
+        let eye = *face
+            .outside
+            .iter()
+            .max_by(|&&i, &&j| face.distance(&p[i]).total_cmp(&face.distance(&p[j])))
+            .unwrap();
+
+        let visible: Vec<usize> = (0..faces.len())
+            .filter(|&i| faces[i].alive && faces[i].distance(&p[eye]) > epsilon)
+            .collect();
+        let edges: HashSet<(usize, usize)> = visible
+            .iter()
+            .flat_map(|&i| {
+                let v = faces[i].vertices;
+                (0..3).map(move |k| (v[k], v[(k + 1) % 3]))
+            })
+            .collect();
+        // The horizon consists of the edges between visible and hidden faces.
+        let horizon: Vec<(usize, usize)> = edges
+            .iter()
+            .filter(|&&(a, b)| !edges.contains(&(b, a)))
+            .cloned()
+            .collect();
+
+        let mut orphans = Vec::new();
+        for &i in &visible {
+            faces[i].alive = false;
+            orphans.append(&mut faces[i].outside);
+        }
+        orphans.retain(|&i| i != eye);
+        let first = faces.len();
+        for (a, b) in horizon {
+            faces.push(Face::new([a, b, eye], &p));
+        }
+        assign(&mut faces, first, orphans, &p, epsilon);
+    }
+
+    let mut indices = HashMap::new();
+    let mut mesh = empty;
+    for f in faces.iter().filter(|f| f.alive) {
+        let mut vertices = [0; 3];
+        for (v, &i) in vertices.iter_mut().zip(&f.vertices) {
+            *v = *indices.entry(i).or_insert_with(|| {
+                mesh.vertices.push(points[source[i]]);
+                mesh.vertices.len() - 1
+            });
+        }
+        mesh.faces.push(IndexedTriangle {
+            normal: to_f32(&f.normal),
+            vertices,
+        });
+    }
+    mesh
+}
+
+// Hands each point to the first face from index first on that it lies in front of.
+fn assign(faces: &mut [Face], first: usize, points: Vec<usize>, p: &[Vec3], epsilon: f64) {
+    for i in points {
+        if let Some(f) = faces[first..]
+            .iter_mut()
+            .find(|f| f.distance(&p[i]) > epsilon)
+        {
+            f.outside.push(i);
+        }
+    }
+}
+
+// Four points spanning a tetrahedron of reasonable size, or None if all points are coplanar.
+fn initial_simplex(p: &[Vec3], epsilon: f64) -> Option<[usize; 4]> {
+    if p.len() < 4 {
+        return None;
+    }
+    let farthest =
+        |f: &dyn Fn(&Vec3) -> f64| (0..p.len()).max_by(|&i, &j| f(&p[i]).total_cmp(&f(&p[j])));
+    // The pair of extreme points along the axis with the largest spread.
+    let (a, b) = (0..3)
+        .map(|k| {
+            let min = farthest(&|v: &Vec3| -v[k]).unwrap_or(0);
+            let max = farthest(&|v: &Vec3| v[k]).unwrap_or(0);
+            (min, max, k)
+        })
+        .max_by(|x, y| (p[x.1][x.2] - p[x.0][x.2]).total_cmp(&(p[y.1][y.2] - p[y.0][y.2])))
+        .map(|(a, b, _)| (a, b))?;
+    let ab = sub(&p[b], &p[a]);
+    if norm(&ab) <= epsilon {
+        return None;
+    }
+    let c = farthest(&|v: &Vec3| norm(&cross(&ab, &sub(v, &p[a]))))?;
+    let ac = sub(&p[c], &p[a]);
+    if norm(&cross(&ab, &ac)) / norm(&ab) <= epsilon {
+        return None;
+    }
+    let normal = normalize(&cross(&ab, &ac))?;
+    let d = farthest(&|v: &Vec3| dot(&normal, &sub(v, &p[a])).abs())?;
+    if dot(&normal, &sub(&p[d], &p[a])).abs() <= epsilon {
+        return None;
+    }
+    Some([a, b, c, d])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::assert_closed;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    fn check_closed_and_enclosing(hull: &IndexedMesh, points: &[Vertex]) {
+        assert_closed(hull);
+        for f in &hull.faces {
+            let normal = to_f64(&f.normal);
+            let offset = dot(&normal, &to_f64(&hull.vertices[f.vertices[0]]));
+            for p in points {
+                assert!(dot(&normal, &to_f64(p)) - offset < 1e-5, "{:?}", p);
+            }
+        }
+    }
+
+    #[test]
+    fn hull_of_cube() {
+        let mut points = Vec::new();
+        for i in 0..8 {
+            points.push([(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32]);
+            points.push([0.5, 0.2 + 0.2 * (i % 4) as f32, 0.5]);
+        }
+        let hull = convex_hull(&points);
+        assert_eq!(hull.vertices.len(), 8);
+        assert_eq!(hull.faces.len(), 12);
+        check_closed_and_enclosing(&hull, &points);
+    }
+
+    #[test]
+    fn hull_of_bunny() {
+        let mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let hull = convex_hull(&mesh.vertices);
+        assert!(hull.vertices.len() < mesh.vertices.len());
+        assert_eq!(hull.faces.len(), 2 * hull.vertices.len() - 4);
+        check_closed_and_enclosing(&hull, &mesh.vertices);
+    }
+
+    #[test]
+    fn hull_degenerate() {
+        assert!(convex_hull(&[]).faces.is_empty());
+        let flat = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [1., 1., 0.]];
+        assert!(convex_hull(&flat).faces.is_empty());
+        let line = [[0., 0., 0.], [1., 1., 1.], [2., 2., 2.], [3., 3., 3.]];
+        assert!(convex_hull(&line).faces.is_empty());
+        let nan = [[0., 0., 0.], [1., 0., 0.], [f32::NAN, 1., 0.], [0., 0., 1.]];
+        assert!(convex_hull(&nan).faces.is_empty());
+    }
+
+    #[test]
+    fn hull_ignores_non_finite_points() {
+        let mut points = vec![[f32::NAN, 0., 0.], [0., f32::INFINITY, 0.]];
+        for i in 0..8 {
+            points.push([(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32]);
+        }
+        points.push([0.5, f32::NAN, 0.5]);
+        let hull = convex_hull(&points);
+        assert_eq!(hull.vertices.len(), 8);
+        assert_eq!(hull.faces.len(), 12);
+        assert!(hull.vertices.iter().flatten().all(|c| c.is_finite()));
+    }
+}
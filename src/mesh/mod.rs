@@ -0,0 +1,84 @@
+//! Algorithms working on [IndexedMeshes](../struct.IndexedMesh.html).
+
+pub mod boolean;
+mod bvh;
+mod canonical;
+mod cut;
+mod diff;
+mod edges;
+mod extrude;
+mod fit;
+mod hull;
+mod orient;
+mod orientation;
+mod overhang;
+pub mod primitives;
+mod quality;
+mod raycast;
+mod register;
+mod sample;
+mod silhouette;
+mod slice;
+mod snap;
+mod units;
+mod voxelize;
+
+pub use self::bvh::{Bvh, ClosestPoint};
+pub use self::cut::{cut, Halves};
+pub use self::diff::{diff, MeshDiff};
+pub use self::edges::Edge;
+pub use self::extrude::{extrude, revolve};
+pub use self::fit::{fit_primitives, CylinderFit, PlaneFit, PrimitiveFits, SphereFit};
+pub use self::hull::convex_hull;
+pub use self::orientation::{optimize_orientation, Orientation, OrientationCriteria};
+pub use self::overhang::{overhangs, Overhangs};
+pub use self::quality::{quality_report, Histogram, QualityReport};
+pub use self::raycast::{Hit, Ray, Raycaster};
+pub use self::register::{register, Registration};
+pub use self::sample::{sample_surface, sample_surface_poisson, SurfacePoint};
+pub use self::silhouette::Silhouette;
+pub use self::slice::{slice, slice_stack, Layer, Plane};
+pub use self::units::{guess_units, Unit};
+pub use self::voxelize::{offset, voxelize, ScalarGrid};
+
+use crate::math::{area_normal, normalize, to_f64, Vec3};
+use crate::{IndexedMesh, Vertex};
+use std::collections::HashMap;
+
+/// Sequence of connected line segments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polyline {
+    /// The corners of the polyline.
+    pub points: Vec<Vertex>,
+    /// Whether the last point connects back to the first one.
+    pub closed: bool,
+}
+
+// Unit normals of all faces derived from their winding, None for degenerate faces.
+pub(crate) fn face_normals(mesh: &IndexedMesh) -> Vec<Option<Vec3>> {
+    mesh.faces
+        .iter()
+        .map(|f| {
+            normalize(&area_normal(&[
+                to_f64(&mesh.vertices[f.vertices[0]]),
+                to_f64(&mesh.vertices[f.vertices[1]]),
+                to_f64(&mesh.vertices[f.vertices[2]]),
+            ]))
+        })
+        .collect()
+}
+
+// Maps each undirected edge (smaller vertex index first) to the faces containing it.
+pub(crate) fn edge_faces(mesh: &IndexedMesh) -> HashMap<(usize, usize), Vec<usize>> {
+    let mut edge_faces = HashMap::new();
+    for (fi, f) in mesh.faces.iter().enumerate() {
+        for i in 0..3 {
+            let (a, b) = (f.vertices[i], f.vertices[(i + 1) % 3]);
+            edge_faces
+                .entry((a.min(b), a.max(b)))
+                .or_insert_with(Vec::new)
+                .push(fi);
+        }
+    }
+    edge_faces
+}
@@ -0,0 +1,146 @@
+use super::{edge_faces, Raycaster};
+use crate::math::{add, area_normal, norm, normalize, scale, to_f32, to_f64, Vec3};
+use crate::IndexedMesh;
+
+impl IndexedMesh {
+    /// Flips faces so that all of them face away from the interior, fixing inside-out shells.
+    /// Returns the number of flipped faces.
+    ///
+    /// Faces connected by edges shared by exactly two faces form a shell, and are first given
+    /// a consistent winding. Each shell then faces the way in which a point just in front of
+    /// its largest face lies outside the whole mesh, as told by ray parity. Cavities therefore
+    /// face into the empty space they enclose. Stored normals of all faces are recomputed from
+    /// their winding. The mesh should be closed, or the result may be arbitrary.
+    ///
+    /// ```
+    /// let mut mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+    ///     faces: vec![
+    ///         stl_io::IndexedTriangle { normal: [0., 0., 0.], vertices: [0, 1, 2] },
+    ///         stl_io::IndexedTriangle { normal: [0., 0., 0.], vertices: [0, 1, 3] },
+    ///         stl_io::IndexedTriangle { normal: [0., 0., 0.], vertices: [0, 3, 2] },
+    ///         stl_io::IndexedTriangle { normal: [0., 0., 0.], vertices: [1, 2, 3] },
+    ///     ],
+    /// };
+    /// assert_eq!(mesh.orient_outward(), 1);
+    /// assert_eq!(mesh.faces[0].normal, [0., 0., -1.]);
+    /// assert!(mesh.validate().is_ok());
+    /// ```
+    pub fn orient_outward(&mut self) -> usize {
+        let edges = edge_faces(self);
+        let forward = |f: usize, a: usize, b: usize| {
+            let v = self.faces[f].vertices;
+            (0..3).any(|i| v[i] == a && v[(i + 1) % 3] == b)
+        };
+
+        // Make the winding within each shell consistent, face by face from a seed.
+        let mut flip = vec![false; self.faces.len()];
+        let mut shell_of = vec![usize::MAX; self.faces.len()];
+        let mut shells: Vec<Vec<usize>> = Vec::new();
+        for seed in 0..self.faces.len() {
+            if shell_of[seed] != usize::MAX {
+                continue;
+            }
+            let id = shells.len();
+            shell_of[seed] = id;
+            let mut shell = vec![seed];
+            let mut next = 0;
+            while next < shell.len() {
+                let f = shell[next];
+                next += 1;
+                let v = self.faces[f].vertices;
+                for i in 0..3 {
+                    let (a, b) = (v[i], v[(i + 1) % 3]);
+                    let faces = &edges[&(a.min(b), a.max(b))];
+                    if faces.len() != 2 || a == b {
+                        continue;
+                    }
+                    let g = if faces[0] == f { faces[1] } else { faces[0] };
+                    if shell_of[g] != usize::MAX {
+                        continue;
+                    }
+                    // Neighbors traverse their shared edge in opposite directions.
+                    flip[g] = flip[f] != forward(g, a, b);
+                    shell_of[g] = id;
+                    shell.push(g);
+                }
+            }
+            shells.push(shell);
+        }
+        for (f, &flip) in flip.iter().enumerate() {
+            if flip {
+                self.faces[f].vertices.swap(1, 2);
+            }
+        }
+
+        // Turn shells whose front side lies inside the mesh.
+        let triangle = |mesh: &IndexedMesh, f: usize| -> [Vec3; 3] {
+            mesh.faces[f].vertices.map(|v| to_f64(&mesh.vertices[v]))
+        };
+        let inside_out = {
+            let raycaster = Raycaster::new(self);
+            shells
+                .iter()
+                .map(|shell| {
+                    let largest = shell.iter().cloned().max_by(|&a, &b| {
+                        let area = |f| norm(&area_normal(&triangle(self, f)));
+                        area(a).total_cmp(&area(b))
+                    });
+                    let t = triangle(self, largest.unwrap());
+                    let normal = area_normal(&t);
+                    let direction = match normalize(&normal) {
+                        Some(direction) => direction,
+                        None => return false,
+                    };
+                    let center = scale(&add(&add(&t[0], &t[1]), &t[2]), 1. / 3.);
+                    // Step off the face by a small fraction of its size.
+                    let step = norm(&normal).sqrt() * 1e-3;
+                    raycaster.contains(&to_f32(&add(&center, &scale(&direction, step))))
+                })
+                .collect::<Vec<_>>()
+        };
+        for (shell, &inside_out) in shells.iter().zip(&inside_out) {
+            if inside_out {
+                for &f in shell {
+                    self.faces[f].vertices.swap(1, 2);
+                    flip[f] = !flip[f];
+                }
+            }
+        }
+
+        for (f, &flip) in flip.iter().enumerate() {
+            if let Some(normal) = normalize(&area_normal(&triangle(self, f))) {
+                self.faces[f].normal = to_f32(&normal);
+            } else if flip {
+                let n = self.faces[f].normal;
+                self.faces[f].normal = [-n[0], -n[1], -n[2]];
+            }
+        }
+        flip.iter().filter(|&&flip| flip).count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::{combine, cube, volume};
+
+    #[test]
+    fn orient_hollow_cube() {
+        // An outer cube turned inside out with a few faces flipped back, and a cavity that
+        // faces outward like a solid.
+        let mut outer = cube(0., 1.);
+        for (i, f) in outer.faces.iter_mut().enumerate() {
+            if i % 5 != 0 {
+                f.vertices.swap(1, 2);
+            }
+        }
+        let mut mesh = combine(&outer, &cube(0.25, 0.5));
+
+        assert_eq!(mesh.orient_outward(), 9 + 12);
+        assert!((volume(&mesh) - (1. - 0.125)).abs() < 1e-9);
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.faces[0].normal, [0., 0., -1.]);
+        assert_eq!(mesh.faces[12].normal, [0., 0., 1.]);
+        assert_eq!(mesh.orient_outward(), 0);
+    }
+}
@@ -0,0 +1,261 @@
+use super::{convex_hull, face_normals};
+use crate::math::{
+    area_normal, dot, norm, normalize, rotation_to_z, scale, sub, to_f32, to_f64, Vec3,
+};
+use crate::{IndexedMesh, Normal};
+use std::f64::consts::PI;
+
+const SPHERE_DIRECTIONS: usize = 256;
+const HULL_DIRECTIONS: usize = 64;
+
+/// Weights of the goals of [optimize_orientation](fn.optimize_orientation.html).
+///
+/// Support and contact areas are measured relative to the surface area of the mesh, and the
+/// height relative to the diagonal of its bounding box, so the weights are comparable across
+/// meshes of any size. A weight of 0 ignores a goal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrientationCriteria {
+    /// Weight of the area that needs support, which is minimized.
+    pub support: f32,
+    /// Weight of the area lying on the build plate, which is maximized.
+    pub contact: f32,
+    /// Weight of the print height, which is minimized.
+    pub height: f32,
+    /// Overhang angle from the vertical in degrees above which faces need support, see
+    /// [overhangs](fn.overhangs.html).
+    pub max_overhang_angle: f32,
+}
+
+impl Default for OrientationCriteria {
+    /// Minimizes support first, with bed contact and height as tie breakers, for overhangs
+    /// above 45 degrees.
+    fn default() -> OrientationCriteria {
+        OrientationCriteria {
+            support: 1.,
+            contact: 0.2,
+            height: 0.1,
+            max_overhang_angle: 45.,
+        }
+    }
+}
+
+/// Print orientation found by [optimize_orientation](fn.optimize_orientation.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Orientation {
+    /// Direction of the mesh that should point up.
+    pub up: Normal,
+    /// Rotation matrix, stored by rows, that turns up into +z.
+    pub rotation: [[f32; 3]; 3],
+    /// Area of the faces that need support.
+    pub support_area: f32,
+    /// Area of the faces lying on the build plate.
+    pub contact_area: f32,
+    /// Extent of the mesh along up.
+    pub height: f32,
+    /// Weighted score of the orientation, lower is better.
+    pub score: f32,
+}
+
+/// Searches the direction to print the mesh in that scores best according to the weighted
+/// criteria.
+///
+/// Candidates are the directions that place one of the largest faces of the convex hull on
+/// the build plate, the coordinate axes, and directions spread evenly over the sphere. The
+/// search is exhaustive over these candidates, and thus deterministic.
+///
+/// Returns None if the mesh has no area.
+///
+/// ```
+/// // A box of 1 x 1 x 3 standing upright.
+/// let mut vertices = Vec::new();
+/// for i in 0..8 {
+///     vertices.push([(i & 1) as f32, (i >> 1 & 1) as f32, 3. * (i >> 2) as f32]);
+/// }
+/// let faces = vec![
+///     [0, 2, 1], [1, 2, 3], [4, 5, 6], [5, 7, 6], [0, 1, 4], [1, 5, 4],
+///     [2, 6, 3], [3, 6, 7], [0, 4, 2], [2, 4, 6], [1, 3, 5], [3, 7, 5],
+/// ];
+/// let mesh = stl_io::IndexedMesh::from_vertices(vertices, faces);
+/// let criteria = stl_io::mesh::OrientationCriteria {
+///     height: 1.,
+///     ..Default::default()
+/// };
+/// let orientation = stl_io::mesh::optimize_orientation(&mesh, &criteria).unwrap();
+/// assert!(orientation.up[2].abs() < 1e-6);
+/// assert!((orientation.height - 1.).abs() < 1e-6);
+/// assert!((orientation.contact_area - 3.).abs() < 1e-6);
+/// ```
+pub fn optimize_orientation(
+    mesh: &IndexedMesh,
+    criteria: &OrientationCriteria,
+) -> Option<Orientation> {
+    let normals = face_normals(mesh);
+    let areas = mesh
+        .faces
+        .iter()
+        .map(|f| norm(&area_normal(&f.vertices.map(|v| to_f64(&mesh.vertices[v])))) / 2.)
+        .collect::<Vec<_>>();
+    let total_area = areas.iter().sum::<f64>();
+    if !total_area.is_finite() || total_area <= 0. {
+        return None;
+    }
+    let vertices = mesh.vertices.iter().map(to_f64).collect::<Vec<_>>();
+    let (min, max) = mesh
+        .faces
+        .iter()
+        .flat_map(|f| f.vertices.iter().map(|&v| vertices[v]))
+        .fold(
+            ([f64::INFINITY; 3], [f64::NEG_INFINITY; 3]),
+            |(lo, hi), v| {
+                (
+                    [0, 1, 2].map(|i| lo[i].min(v[i])),
+                    [0, 1, 2].map(|i| hi[i].max(v[i])),
+                )
+            },
+        );
+    let diagonal = norm(&sub(&max, &min)).max(f64::MIN_POSITIVE);
+
+    let mut candidates = vec![
+        [1., 0., 0.],
+        [-1., 0., 0.],
+        [0., 1., 0.],
+        [0., -1., 0.],
+        [0., 0., 1.],
+        [0., 0., -1.],
+    ];
+    let hull = convex_hull(&mesh.vertices);
+    let mut hull_faces = face_normals(&hull)
+        .into_iter()
+        .zip(&hull.faces)
+        .filter_map(|(normal, f)| {
+            let corners = f.vertices.map(|v| to_f64(&hull.vertices[v]));
+            Some((norm(&area_normal(&corners)), scale(&normal?, -1.)))
+        })
+        .collect::<Vec<_>>();
+    hull_faces.sort_by(|a, b| b.0.total_cmp(&a.0));
+    candidates.extend(hull_faces.iter().take(HULL_DIRECTIONS).map(|&(_, up)| up));
+    // Fibonacci sphere.
+    let golden_angle = PI * (3. - 5f64.sqrt());
+    candidates.extend((0..SPHERE_DIRECTIONS).map(|i| {
+        let z = 1. - (2 * i + 1) as f64 / SPHERE_DIRECTIONS as f64;
+        let r = (1. - z * z).sqrt();
+        let phi = golden_angle * i as f64;
+        [r * phi.cos(), r * phi.sin(), z]
+    }));
+
+    let threshold = f64::from(criteria.max_overhang_angle).to_radians().sin();
+    let evaluate = |up: Vec3| {
+        let heights = vertices.iter().map(|v| dot(v, &up)).collect::<Vec<_>>();
+        let (bottom, top) = mesh
+            .faces
+            .iter()
+            .flat_map(|f| f.vertices.iter().map(|&v| heights[v]))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), h| {
+                (lo.min(h), hi.max(h))
+            });
+        // The same tolerance for lying on the plate as in overhangs.
+        let tolerance = 1e-5 * (top - bottom);
+        let (mut support, mut contact) = (0., 0.);
+        for ((f, normal), area) in mesh.faces.iter().zip(&normals).zip(&areas) {
+            let down = match normal {
+                Some(normal) => -dot(normal, &up),
+                None => continue,
+            };
+            let on_plate = f.vertices.iter().all(|&v| heights[v] <= bottom + tolerance);
+            if on_plate && down > 0. {
+                contact += area;
+            } else if !on_plate && down > threshold {
+                support += area;
+            }
+        }
+        let height = top - bottom;
+        let score = f64::from(criteria.support) * support / total_area
+            - f64::from(criteria.contact) * contact / total_area
+            + f64::from(criteria.height) * height / diagonal;
+        (score, support, contact, height)
+    };
+    let (up, (score, support, contact, height)) = candidates
+        .iter()
+        .filter_map(normalize)
+        .map(|up| (up, evaluate(up)))
+        .min_by(|a, b| (a.1).0.total_cmp(&(b.1).0))?;
+    Some(Orientation {
+        up: to_f32(&up),
+        rotation: rotation_to_z(&up).map(|row| to_f32(&row)),
+        support_area: support as f32,
+        contact_area: contact as f32,
+        height: height as f32,
+        score: score as f32,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    // A square pyramid of width 2 and height 1 standing on its apex.
+    fn pyramid() -> IndexedMesh {
+        IndexedMesh::from_vertices(
+            vec![
+                [0., 0., 1.],
+                [2., 0., 1.],
+                [2., 2., 1.],
+                [0., 2., 1.],
+                [1., 1., 0.],
+            ],
+            vec![
+                [1, 0, 4],
+                [2, 1, 4],
+                [3, 2, 4],
+                [0, 3, 4],
+                [0, 1, 2],
+                [0, 2, 3],
+            ],
+        )
+    }
+
+    #[test]
+    fn pyramid_on_its_base() {
+        let orientation =
+            optimize_orientation(&pyramid(), &OrientationCriteria::default()).unwrap();
+        assert!(norm(&sub(&to_f64(&orientation.up), &[0., 0., -1.])) < 1e-6);
+        assert_eq!(orientation.support_area, 0.);
+        assert!((orientation.contact_area - 4.).abs() < 1e-5);
+        assert!((orientation.height - 1.).abs() < 1e-5);
+        let z = orientation
+            .rotation
+            .map(|row| dot(&to_f64(&row), &to_f64(&orientation.up)));
+        assert!(norm(&sub(&z, &[0., 0., 1.])) < 1e-6);
+    }
+
+    #[test]
+    fn bunny_orientation() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let criteria = OrientationCriteria::default();
+        let best = optimize_orientation(&bunny, &criteria).unwrap();
+        let upright = optimize_orientation(
+            &bunny,
+            &OrientationCriteria {
+                support: 0.,
+                contact: 0.,
+                height: 1.,
+                max_overhang_angle: 45.,
+            },
+        )
+        .unwrap();
+        assert!(best.score <= upright.score + 1e-6);
+        assert!(best.support_area <= upright.support_area);
+        assert!(upright.height <= best.height);
+        let empty = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        assert_eq!(optimize_orientation(&empty, &criteria), None);
+        // Vertices not used by any face do not matter, even if they are not finite.
+        let mut pyramid = pyramid();
+        pyramid.vertices.push([f32::NAN; 3]);
+        let orientation = optimize_orientation(&pyramid, &criteria).unwrap();
+        assert_eq!(orientation.up, [0., 0., -1.]);
+    }
+}
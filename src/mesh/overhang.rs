@@ -0,0 +1,221 @@
+use super::face_normals;
+use crate::math::{area_normal, dot, norm, normalize, to_f64};
+use crate::{IndexedMesh, Normal, Vertex};
+
+// Representative of the set containing v, halving the path on the way.
+fn root(parent: &mut [usize], mut v: usize) -> usize {
+    while parent[v] != v {
+        parent[v] = parent[parent[v]];
+        v = parent[v];
+    }
+    v
+}
+
+/// Parts of a mesh that need support when printed, found by [overhangs](fn.overhangs.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overhangs {
+    /// Indices of the faces that are too steep to print without support.
+    pub faces: Vec<usize>,
+    /// Total area of these faces.
+    pub area: f32,
+    /// Lowest points of islands, local minima above the build plate where a new part of the
+    /// print starts in mid air. Each flat minimum is reported once.
+    pub islands: Vec<Vertex>,
+}
+
+/// Finds faces and points that need support when the mesh is printed in the direction up,
+/// starting on a build plate at the lowest point of the mesh.
+///
+/// Overhang angles are measured from the vertical, as in slicers: walls have an angle of 0
+/// and horizontal faces pointing down one of 90 degrees. A face needs support if its angle
+/// exceeds max_angle, in degrees, typically 45. Faces lying on the build plate do not. up need
+/// not have unit length, no overhangs are found if it is zero.
+///
+/// ```
+/// // A horizontal triangle pointing down, hovering above a vertical one standing on the plate.
+/// let mesh = stl_io::IndexedMesh::from_vertices(
+///     vec![[0., 0., 1.], [0., 1., 1.], [1., 0., 1.], [5., 0., 0.], [6., 0., 0.], [5., 0., 1.]],
+///     vec![[0, 1, 2], [3, 4, 5]],
+/// );
+/// let overhangs = stl_io::mesh::overhangs(&mesh, &[0., 0., 1.], 45.);
+/// assert_eq!(overhangs.faces, vec![0]);
+/// assert_eq!(overhangs.area, 0.5);
+/// assert_eq!(overhangs.islands, vec![[0., 0., 1.]]);
+/// ```
+pub fn overhangs(mesh: &IndexedMesh, up: &Normal, max_angle: f32) -> Overhangs {
+    let mut result = Overhangs {
+        faces: Vec::new(),
+        area: 0.,
+        islands: Vec::new(),
+    };
+    let up = match normalize(&to_f64(up)) {
+        Some(up) => up,
+        None => return result,
+    };
+    let height = |v: usize| dot(&to_f64(&mesh.vertices[v]), &up);
+    let heights = (0..mesh.vertices.len()).map(height).collect::<Vec<_>>();
+    let (bottom, top) = mesh
+        .faces
+        .iter()
+        .flat_map(|f| f.vertices.iter().map(|&v| heights[v]))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), h| {
+            (lo.min(h), hi.max(h))
+        });
+    let tolerance = 1e-5 * (top - bottom);
+    let on_plate = |v: usize| heights[v] <= bottom + tolerance;
+
+    // A face with unit normal n has an overhang angle of 90 degrees minus its angle to the
+    // vertical, so it exceeds max_angle if n points down steeper than that.
+    let threshold = f64::from(max_angle).to_radians().sin();
+    for (fi, (f, normal)) in mesh.faces.iter().zip(face_normals(mesh)).enumerate() {
+        let normal = match normal {
+            Some(normal) => normal,
+            None => continue,
+        };
+        if -dot(&normal, &up) > threshold && !f.vertices.iter().all(|&v| on_plate(v)) {
+            let corners = f.vertices.map(|v| to_f64(&mesh.vertices[v]));
+            result.faces.push(fi);
+            result.area += (norm(&area_normal(&corners)) / 2.) as f32;
+        }
+    }
+
+    // Vertices without lower neighbors are local minima. Neighboring minima of equal height
+    // form one flat minimum, which is merged with union find.
+    let mut neighbors = vec![Vec::new(); mesh.vertices.len()];
+    for f in &mesh.faces {
+        for i in 0..3 {
+            let (a, b) = (f.vertices[i], f.vertices[(i + 1) % 3]);
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+    }
+    let minimum = |v: usize| {
+        !neighbors[v].is_empty()
+            && !on_plate(v)
+            && neighbors[v].iter().all(|&n| heights[n] >= heights[v])
+    };
+    let mut parent = (0..mesh.vertices.len()).collect::<Vec<_>>();
+    for v in (0..mesh.vertices.len()).filter(|&v| minimum(v)) {
+        for &n in &neighbors[v] {
+            if heights[n] == heights[v] {
+                let (a, b) = (root(&mut parent, v), root(&mut parent, n));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+    // A flat minimum only counts if none of its vertices has a lower neighbor.
+    let mut valid = vec![true; mesh.vertices.len()];
+    for v in 0..mesh.vertices.len() {
+        if !minimum(v) {
+            let r = root(&mut parent, v);
+            valid[r] = false;
+        }
+    }
+    for (v, &valid) in valid.iter().enumerate() {
+        if valid && root(&mut parent, v) == v && minimum(v) {
+            result.islands.push(mesh.vertices[v]);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::IndexedTriangle;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    // An upright square pyramid of width 1 and height 1 standing on the plate at z = 0, and
+    // an upside down one of width 3 and height 0.5 with its apex hovering at z = 0.5.
+    fn pyramids() -> IndexedMesh {
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for &(x, z, apex, width) in &[(0f32, 0f32, 1f32, 1f32), (10., 1., 0.5, 3.)] {
+            let base = vertices.len();
+            vertices.extend_from_slice(&[
+                [x, 0., z],
+                [x + width, 0., z],
+                [x + width, width, z],
+                [x, width, z],
+                [x + width / 2., width / 2., apex],
+            ]);
+            let upright = apex > z;
+            for i in 0..4 {
+                let (a, b) = (base + i, base + (i + 1) % 4);
+                faces.push(if upright {
+                    [a, b, base + 4]
+                } else {
+                    [b, a, base + 4]
+                });
+            }
+            if upright {
+                faces.extend_from_slice(&[[base, base + 2, base + 1], [base, base + 3, base + 2]]);
+            } else {
+                faces.extend_from_slice(&[[base, base + 1, base + 2], [base, base + 2, base + 3]]);
+            }
+        }
+        IndexedMesh::from_vertices(vertices, faces)
+    }
+
+    #[test]
+    fn pyramid_overhangs() {
+        let mesh = pyramids();
+        let up = [0., 0., 1.];
+        // The sides of the upside down pyramid have an overhang angle of atan(1.5 / 0.5), about
+        // 71.6 degrees, those of the upright one atan(0.5 / 1), about 26.6 degrees.
+        let overhangs = super::overhangs(&mesh, &up, 75.);
+        assert!(overhangs.faces.is_empty());
+        assert_eq!(overhangs.islands, vec![[11.5, 1.5, 0.5]]);
+        let overhangs = super::overhangs(&mesh, &up, 60.);
+        assert_eq!(overhangs.faces, vec![6, 7, 8, 9]);
+        let side = 0.5 * 3. * (1.5f32 * 1.5 + 0.5 * 0.5).sqrt();
+        assert!((overhangs.area - 4. * side).abs() < 1e-5);
+
+        // Upside down, the top of the second pyramid lies on the plate and the first pyramid
+        // stands on its apex.
+        let overhangs = super::overhangs(&mesh, &[0., 0., -1.], 20.);
+        assert_eq!(overhangs.faces, vec![0, 1, 2, 3]);
+        assert!(overhangs.islands.is_empty());
+    }
+
+    #[test]
+    fn flat_island() {
+        // A square hovering above a triangle on the plate.
+        let mesh = IndexedMesh::from_vertices(
+            vec![
+                [0., 0., 1.],
+                [1., 0., 1.],
+                [1., 1., 1.],
+                [0., 1., 1.],
+                [5., 0., 0.],
+                [6., 0., 0.],
+                [5., 1., 0.],
+            ],
+            vec![[0, 2, 1], [0, 3, 2], [4, 5, 6]],
+        );
+        let overhangs = overhangs(&mesh, &[0., 0., 1.], 45.);
+        assert_eq!(overhangs.faces, vec![0, 1]);
+        assert_eq!(overhangs.islands, vec![[0., 0., 1.]]);
+    }
+
+    #[test]
+    fn bunny_overhangs() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let steep = overhangs(&bunny, &[0., 0., 1.], 30.);
+        let flat = overhangs(&bunny, &[0., 0., 1.], 60.);
+        assert!(!steep.faces.is_empty());
+        assert!(flat.faces.iter().all(|f| steep.faces.contains(f)));
+        assert!(flat.area < steep.area);
+        let none = overhangs(&bunny, &[0., 0., 0.], 30.);
+        assert!(none.faces.is_empty() && none.islands.is_empty());
+        let single = IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+            faces: vec![IndexedTriangle {
+                normal: [0., 0., -1.],
+                vertices: [0, 2, 1],
+            }],
+        };
+        // Lying on the plate.
+        assert!(overhangs(&single, &[0., 0., 1.], 45.).faces.is_empty());
+    }
+}
@@ -0,0 +1,296 @@
+//! Closed meshes of simple solids, centered at the origin, with faces wound counter-clockwise
+//! seen from outside.
+//!
+//! Curved surfaces are approximated with the given number of segments, with all vertices
+//! lying on the exact surface. Solids of revolution have their axis along z.
+//!
+//! ```
+//! let cylinder = stl_io::mesh::primitives::cylinder(1., 2., 32);
+//! assert_eq!(cylinder.faces.len(), 4 * 32);
+//! ```
+
+use super::cut::triangulate;
+use crate::math::{add, normalize, scale, to_f32, Vec3};
+use crate::IndexedMesh;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Box with the given edge lengths along x, y and z.
+pub fn cuboid(size: [f32; 3]) -> IndexedMesh {
+    let vertices = (0..8).map(|i| {
+        [0, 1, 2].map(|axis| {
+            let sign = if i >> axis & 1 == 1 { 0.5 } else { -0.5 };
+            sign * size[axis]
+        })
+    });
+    let faces = vec![
+        [0, 2, 1],
+        [1, 2, 3],
+        [4, 5, 6],
+        [5, 7, 6],
+        [0, 1, 4],
+        [1, 5, 4],
+        [2, 6, 3],
+        [3, 6, 7],
+        [0, 4, 2],
+        [2, 4, 6],
+        [1, 3, 5],
+        [3, 7, 5],
+    ];
+    IndexedMesh::from_vertices(vertices, faces)
+}
+
+/// Sphere made of segments slices around the z axis and rings stacks from pole to pole, at
+/// least 3 and 2.
+pub fn uv_sphere(radius: f32, segments: usize, rings: usize) -> IndexedMesh {
+    let rings = rings.max(2);
+    let r = f64::from(radius);
+    let profile = (0..=rings)
+        .map(|i| {
+            let angle = PI * i as f64 / rings as f64;
+            // Exactly on the axis at the poles.
+            let x = if i == 0 || i == rings {
+                0.
+            } else {
+                r * angle.sin()
+            };
+            [x, -r * angle.cos()]
+        })
+        .collect::<Vec<_>>();
+    lathe(&profile, false, segments, 2. * PI)
+}
+
+/// Sphere made by splitting the faces of an icosahedron into four subdivisions times, which
+/// gives evenly sized, nearly equilateral triangles.
+pub fn icosphere(radius: f32, subdivisions: usize) -> IndexedMesh {
+    let t = (1. + 5f64.sqrt()) / 2.;
+    let mut directions = [
+        [-1., t, 0.],
+        [1., t, 0.],
+        [-1., -t, 0.],
+        [1., -t, 0.],
+        [0., -1., t],
+        [0., 1., t],
+        [0., -1., -t],
+        [0., 1., -t],
+        [t, 0., -1.],
+        [t, 0., 1.],
+        [-t, 0., -1.],
+        [-t, 0., 1.],
+    ]
+    .iter()
+    .map(|d| normalize(d).unwrap())
+    .collect::<Vec<Vec3>>();
+    let mut faces = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    subdivide(&mut directions, &mut faces, subdivisions);
+    let r = f64::from(radius);
+    IndexedMesh::from_vertices(directions.iter().map(|d| to_f32(&scale(d, r))), faces)
+}
+
+// Splits each face between unit directions into four, levels times, adding the normalized
+// edge midpoints as new directions. Edges shared by faces share their midpoint.
+pub(crate) fn subdivide(directions: &mut Vec<Vec3>, faces: &mut Vec<[usize; 3]>, levels: usize) {
+    for _ in 0..levels {
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, directions: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let sum = add(&directions[a], &directions[b]);
+                directions.push(normalize(&sum).unwrap());
+                directions.len() - 1
+            })
+        };
+        *faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, directions);
+                let bc = midpoint(b, c, directions);
+                let ca = midpoint(c, a, directions);
+                vec![[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+            })
+            .collect();
+    }
+}
+
+/// Cylinder of the given radius and height, with segments sides, at least 3.
+pub fn cylinder(radius: f32, height: f32, segments: usize) -> IndexedMesh {
+    let (r, h) = (f64::from(radius), f64::from(height) / 2.);
+    lathe(
+        &[[0., -h], [r, -h], [r, h], [0., h]],
+        false,
+        segments,
+        2. * PI,
+    )
+}
+
+/// Cone with the given base radius and height, its base at the bottom, with segments sides,
+/// at least 3.
+pub fn cone(radius: f32, height: f32, segments: usize) -> IndexedMesh {
+    let (r, h) = (f64::from(radius), f64::from(height) / 2.);
+    lathe(&[[0., -h], [r, -h], [0., h]], false, segments, 2. * PI)
+}
+
+/// Torus around the z axis, with major_radius from the axis to the center of the tube and
+/// minor_radius of the tube, made of major_segments by minor_segments quads, at least 3 each.
+pub fn torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: usize,
+    minor_segments: usize,
+) -> IndexedMesh {
+    let minor_segments = minor_segments.max(3);
+    let (big, small) = (f64::from(major_radius), f64::from(minor_radius));
+    let profile = (0..minor_segments)
+        .map(|i| {
+            let angle = 2. * PI * i as f64 / minor_segments as f64;
+            [big + small * angle.cos(), small * angle.sin()]
+        })
+        .collect::<Vec<_>>();
+    lathe(&profile, true, major_segments, 2. * PI)
+}
+
+// Revolves a profile of [radius, z] points around the z axis by angle in segments steps, at
+// least 3 for a full turn. The solid must lie to the left of the profile, e.g. it runs
+// counter-clockwise around a closed cross section. Points with radius 0 become a single vertex
+// on the axis. Open profiles must start and end on the axis for the result to be closed.
+// Closed profiles revolved by less than a full turn are capped at both ends.
+pub(crate) fn lathe(
+    profile: &[[f64; 2]],
+    closed: bool,
+    segments: usize,
+    angle: f64,
+) -> IndexedMesh {
+    let full = angle >= 2. * PI;
+    let segments = segments.max(if full { 3 } else { 1 });
+    let angle = angle.min(2. * PI);
+    // A full turn reuses the first column of vertices as the last one.
+    let columns = if full { segments } else { segments + 1 };
+    let mut vertices = Vec::new();
+    // Index of the first vertex of each ring, and whether it is a single point on the axis.
+    let mut rings = Vec::with_capacity(profile.len());
+    for &[r, z] in profile {
+        rings.push((vertices.len(), r == 0.));
+        if r == 0. {
+            vertices.push([0., 0., z as f32]);
+            continue;
+        }
+        for j in 0..columns {
+            let a = angle * j as f64 / segments as f64;
+            vertices.push(to_f32(&[r * a.cos(), r * a.sin(), z]));
+        }
+    }
+    let vertex = |(start, on_axis): (usize, bool), j: usize| {
+        if on_axis {
+            start
+        } else {
+            start + j % columns
+        }
+    };
+    let mut faces = Vec::new();
+    let pairs = if closed {
+        profile.len()
+    } else {
+        profile.len().saturating_sub(1)
+    };
+    for k in 0..pairs {
+        let (lower, upper) = (rings[k], rings[(k + 1) % rings.len()]);
+        for j in 0..segments {
+            let (a, b) = (vertex(lower, j), vertex(lower, j + 1));
+            let (c, d) = (vertex(upper, j + 1), vertex(upper, j));
+            if a != b {
+                faces.push([a, b, c]);
+            }
+            if c != d {
+                faces.push([a, c, d]);
+            }
+        }
+    }
+    if closed && !full {
+        // The profile runs counter-clockwise seen from -y, where the first cap faces.
+        let contour = profile
+            .iter()
+            .map(|&[r, z]| [r as f32, 0., z as f32])
+            .collect::<Vec<_>>();
+        for t in triangulate(&[contour], &[0., -1., 0.]) {
+            let [a, b, c] = t.map(|(_, k)| rings[k]);
+            faces.push([vertex(a, 0), vertex(b, 0), vertex(c, 0)]);
+            faces.push([
+                vertex(a, segments),
+                vertex(c, segments),
+                vertex(b, segments),
+            ]);
+        }
+    }
+    IndexedMesh::from_vertices(vertices, faces)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::{dot, to_f64};
+    use crate::testing::{assert_closed, volume};
+
+    fn assert_volume(mesh: &IndexedMesh, expected: f64, tolerance: f64) {
+        assert_closed(mesh);
+        let v = volume(mesh);
+        assert!(
+            (v - expected).abs() < tolerance * expected,
+            "{} {}",
+            v,
+            expected
+        );
+    }
+
+    #[test]
+    fn primitive_volumes() {
+        assert_volume(&cuboid([1., 2., 3.]), 6., 1e-6);
+        assert_volume(&uv_sphere(2., 64, 32), 4. / 3. * PI * 8., 0.01);
+        assert_volume(&icosphere(2., 4), 4. / 3. * PI * 8., 0.01);
+        assert_volume(&cylinder(1., 2., 128), 2. * PI, 0.001);
+        assert_volume(&cone(1., 3., 128), PI, 0.001);
+        assert_volume(&torus(2., 0.5, 128, 64), 2. * PI * PI * 2. * 0.25, 0.005);
+    }
+
+    #[test]
+    fn primitive_sizes() {
+        let icosphere = icosphere(1., 2);
+        assert_eq!(icosphere.faces.len(), 20 * 16);
+        assert_eq!(icosphere.vertices.len(), 10 * 16 + 2);
+        assert!(icosphere
+            .vertices
+            .iter()
+            .all(|v| (dot(&to_f64(v), &to_f64(v)) - 1.).abs() < 1e-6));
+
+        let sphere = uv_sphere(1., 8, 4);
+        assert_eq!(sphere.vertices.len(), 2 + 3 * 8);
+        assert_eq!(sphere.faces.len(), 2 * 8 + 2 * 2 * 8);
+        let torus = torus(2., 1., 1, 1);
+        assert_eq!(torus.faces.len(), 2 * 3 * 3);
+        assert_closed(&torus);
+        let cuboid = cuboid([2., 2., 2.]);
+        assert!(cuboid.faces.iter().all(|f| {
+            let v = to_f64(&cuboid.vertices[f.vertices[0]]);
+            dot(&to_f64(&f.normal), &v) == 1.
+        }));
+    }
+}
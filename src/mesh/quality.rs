@@ -0,0 +1,231 @@
+use super::edge_faces;
+use crate::math::{add, area_normal, cross, dot, norm, normalize, scale, sub, to_f64, Vec3};
+use crate::IndexedMesh;
+use std::f64::consts::PI;
+
+/// Number of values in consecutive ranges.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    /// Lower bounds of the bins in ascending order. Bin i counts the values from `bounds[i]`
+    /// up to `bounds[i + 1]`, the last bin all values from its bound up. Values below the
+    /// first bound are counted in the first bin.
+    pub bounds: Vec<f32>,
+    /// Number of values in each bin.
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    fn new(bounds: Vec<f32>, values: &[f32]) -> Histogram {
+        let mut counts = vec![0; bounds.len()];
+        for &v in values {
+            // NaN counts as larger than every bound.
+            let bin = bounds
+                .iter()
+                .rposition(|&b| v >= b || v.is_nan())
+                .unwrap_or(0);
+            counts[bin] += 1;
+        }
+        Histogram { bounds, counts }
+    }
+}
+
+/// Curvature and triangle shape metrics computed by [quality_report](fn.quality_report.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualityReport {
+    /// Discrete mean curvature at each vertex, the inverse of the radius on a sphere, from the
+    /// cotangent Laplacian. Positive where the surface is convex with respect to its winding.
+    pub mean_curvature: Vec<f32>,
+    /// Discrete Gaussian curvature at each vertex, the angle deficit divided by a third of
+    /// the area of the adjacent faces. Vertices on a boundary have a deficit of π instead
+    /// of 2π with a flat neighborhood.
+    pub gaussian_curvature: Vec<f32>,
+    /// Aspect ratio of each face, the longest edge times the perimeter over 4√3 times the area.
+    /// 1 for equilateral triangles, growing for slivers and infinite for degenerate faces.
+    pub aspect_ratio: Vec<f32>,
+    /// Smallest angle of each face in degrees, at most 60.
+    pub min_angle: Vec<f32>,
+    /// Histogram of aspect_ratio.
+    pub aspect_ratio_histogram: Histogram,
+    /// Histogram of min_angle in bins of 5 degrees.
+    pub min_angle_histogram: Histogram,
+}
+
+// Angle between a and b, robust for nearly parallel vectors.
+fn angle(a: &Vec3, b: &Vec3) -> f64 {
+    norm(&cross(a, b)).atan2(dot(a, b))
+}
+
+/// Computes curvature per vertex and shape metrics per face, to judge whether a tessellation
+/// is fine and regular enough for printing or simulation.
+///
+/// A tessellation approximates curved surfaces well if the curvature times the edge length
+/// stays small, and solvers usually need minimum angles well above 10 degrees. Degenerate
+/// faces do not contribute to the curvature, and vertices without faces have zero curvature.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [1., 1., 0.]],
+///     faces: vec![
+///         stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] },
+///         stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [2, 1, 3] },
+///     ],
+/// };
+/// let report = stl_io::mesh::quality_report(&mesh);
+/// assert_eq!(report.min_angle, vec![45., 45.]);
+/// assert_eq!(report.min_angle_histogram.counts[9], 2);
+/// assert!(report.mean_curvature.iter().all(|&h| h.abs() < 1e-6));
+/// ```
+pub fn quality_report(mesh: &IndexedMesh) -> QualityReport {
+    let n = mesh.vertices.len();
+    let mut laplacian = vec![[0.; 3]; n];
+    let mut normals = vec![[0.; 3]; n];
+    let mut areas = vec![0.; n];
+    let mut angle_sums = vec![0.; n];
+    let mut aspect_ratio = Vec::with_capacity(mesh.faces.len());
+    let mut min_angle = Vec::with_capacity(mesh.faces.len());
+    for f in &mesh.faces {
+        let p = f.vertices.map(|v| to_f64(&mesh.vertices[v]));
+        let edges = [0, 1, 2].map(|i| sub(&p[(i + 1) % 3], &p[i]));
+        let lengths = edges.map(|e| norm(&e));
+        let area_normal = area_normal(&p);
+        let area = norm(&area_normal) / 2.;
+        // The angle at corner i lies between the edges to the other two corners.
+        let angles = [0, 1, 2].map(|i| angle(&edges[i], &scale(&edges[(i + 2) % 3], -1.)));
+        let longest = lengths.iter().cloned().fold(0., f64::max);
+        let perimeter = lengths.iter().sum::<f64>();
+        aspect_ratio.push(if area > 0. {
+            (longest * perimeter / (4. * 3f64.sqrt() * area)) as f32
+        } else {
+            f32::INFINITY
+        });
+        min_angle.push(angles.iter().cloned().fold(PI, f64::min).to_degrees() as f32);
+        if area == 0. {
+            continue;
+        }
+        for i in 0..3 {
+            let v = f.vertices[i];
+            areas[v] += area / 3.;
+            angle_sums[v] += angles[i];
+            normals[v] = add(&normals[v], &area_normal);
+            // The cotangent of the angle at i weights the opposite edge from j to k.
+            let (j, k) = (f.vertices[(i + 1) % 3], f.vertices[(i + 2) % 3]);
+            let cot = angles[i].cos() / angles[i].sin();
+            let edge = sub(&p[(i + 2) % 3], &p[(i + 1) % 3]);
+            laplacian[j] = add(&laplacian[j], &scale(&edge, cot));
+            laplacian[k] = sub(&laplacian[k], &scale(&edge, cot));
+        }
+    }
+    let mut boundary = vec![false; n];
+    for ((a, b), faces) in edge_faces(mesh) {
+        if faces.len() == 1 {
+            boundary[a] = true;
+            boundary[b] = true;
+        }
+    }
+    let mut mean_curvature = vec![0.; n];
+    let mut gaussian_curvature = vec![0.; n];
+    for v in 0..n {
+        if areas[v] == 0. {
+            continue;
+        }
+        // The Laplacian of the position is -2 H n.
+        if let Some(normal) = normalize(&normals[v]) {
+            let laplacian = scale(&laplacian[v], 1. / (2. * areas[v]));
+            mean_curvature[v] = (-dot(&laplacian, &normal) / 2.) as f32;
+        }
+        let full_angle = if boundary[v] { PI } else { 2. * PI };
+        gaussian_curvature[v] = ((full_angle - angle_sums[v]) / areas[v]) as f32;
+    }
+    QualityReport {
+        aspect_ratio_histogram: Histogram::new(vec![1., 1.5, 2., 3., 5., 10., 100.], &aspect_ratio),
+        min_angle_histogram: Histogram::new((0..12).map(|i| 5. * i as f32).collect(), &min_angle),
+        mean_curvature,
+        gaussian_curvature,
+        aspect_ratio,
+        min_angle,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::to_f32;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn sphere_curvature() {
+        let (directions, faces) = crate::testing::subdivided_octahedron(4);
+        let radius = 2.;
+        let sphere =
+            IndexedMesh::from_vertices(directions.iter().map(|d| to_f32(&scale(d, radius))), faces);
+        let report = quality_report(&sphere);
+        let n = sphere.vertices.len() as f32;
+        let mean = report.mean_curvature.iter().sum::<f32>() / n;
+        let gaussian = report.gaussian_curvature.iter().sum::<f32>() / n;
+        assert!((mean - 0.5).abs() < 0.02);
+        assert!((gaussian - 0.25).abs() < 0.02);
+        // Barycentric areas overestimate the curvature at the six vertices of valence four.
+        assert!(report.mean_curvature.iter().all(|&h| h > 0.45 && h < 0.8));
+
+        // Inverting the winding inverts the mean curvature.
+        let mut inverted = sphere.clone();
+        for f in &mut inverted.faces {
+            f.vertices.swap(1, 2);
+        }
+        let inverted = quality_report(&inverted);
+        assert!((inverted.mean_curvature[7] + report.mean_curvature[7]).abs() < 1e-5);
+        assert_eq!(inverted.gaussian_curvature, report.gaussian_curvature);
+    }
+
+    #[test]
+    fn bunny_shape_metrics() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let report = quality_report(&bunny);
+        assert_eq!(report.aspect_ratio.len(), 99);
+        assert_eq!(report.min_angle.len(), 99);
+        assert_eq!(report.mean_curvature.len(), bunny.vertices.len());
+        assert_eq!(
+            report.aspect_ratio_histogram.counts.iter().sum::<usize>(),
+            99
+        );
+        assert_eq!(report.min_angle_histogram.counts.iter().sum::<usize>(), 99);
+        for (&a, &m) in report.aspect_ratio.iter().zip(&report.min_angle) {
+            assert!(a >= 1. - 1e-5);
+            assert!(m > 0. && m <= 60. + 1e-4);
+        }
+    }
+
+    #[test]
+    fn equilateral_and_degenerate_faces() {
+        let mesh = IndexedMesh {
+            vertices: vec![
+                [0., 0., 0.],
+                [2., 0., 0.],
+                [1., 3f32.sqrt(), 0.],
+                [4., 0., 0.],
+            ],
+            faces: vec![
+                crate::IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [0, 1, 2],
+                },
+                crate::IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [0, 1, 3],
+                },
+            ],
+        };
+        let report = quality_report(&mesh);
+        assert!((report.aspect_ratio[0] - 1.).abs() < 1e-5);
+        assert!((report.min_angle[0] - 60.).abs() < 1e-4);
+        assert_eq!(report.aspect_ratio[1], f32::INFINITY);
+        assert_eq!(report.min_angle[1], 0.);
+        assert_eq!(report.aspect_ratio_histogram.counts, [1, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(report.min_angle_histogram.counts[0], 1);
+        assert_eq!(report.min_angle_histogram.counts[11], 1);
+        // A single triangle only has boundary vertices with a deficit of π minus its angle.
+        let area = 3f32.sqrt() / 3.;
+        let expected = (::std::f32::consts::PI * 2. / 3.) / area;
+        assert!((report.gaussian_curvature[2] - expected).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,186 @@
+use super::bvh::Bvh;
+use crate::math::{add, norm, scale, to_f32, to_f64};
+use crate::{IndexedMesh, Normal, Vertex};
+
+// Directions for inside tests, chosen to avoid running along axes or typical mesh edges.
+const PROBE_DIRECTIONS: [[f64; 3]; 3] = [
+    [0.5773, 0.5719, 0.5831],
+    [-0.6213, 0.4127, -0.6659],
+    [0.2081, -0.8857, 0.4149],
+];
+
+/// Half line starting at origin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    /// Start of the ray.
+    pub origin: Vertex,
+    /// Direction of the ray. Does not need to be of unit length.
+    pub direction: Normal,
+}
+
+/// Intersection of a [Ray](struct.Ray.html) with a face.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    /// Index of the face that was hit.
+    pub face: usize,
+    /// Distance from the origin of the ray.
+    pub distance: f32,
+    /// Point where the face was hit.
+    pub point: Vertex,
+}
+
+/// Answers ray queries against a mesh, using a bounding volume hierarchy over its faces.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let raycaster = stl_io::mesh::Raycaster::new(&mesh);
+/// let ray = stl_io::mesh::Ray { origin: [0.25, 0.25, 1.], direction: [0., 0., -1.] };
+/// assert_eq!(raycaster.intersect(&ray).unwrap().point, [0.25, 0.25, 0.]);
+/// ```
+pub struct Raycaster<'a> {
+    bvh: Bvh<'a>,
+}
+
+impl<'a> Raycaster<'a> {
+    /// Builds the acceleration structure for mesh.
+    pub fn new(mesh: &'a IndexedMesh) -> Raycaster<'a> {
+        Raycaster {
+            bvh: Bvh::build(mesh),
+        }
+    }
+
+    /// The nearest face hit by ray, from either side.
+    pub fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let (origin, direction) = (to_f64(&ray.origin), to_f64(&ray.direction));
+        let mut nearest = None;
+        self.bvh.ray(&origin, &direction, f64::INFINITY, |face, t| {
+            nearest = Some((face, t));
+            t
+        });
+        nearest.map(|(face, t)| self.hit(&origin, &direction, face, t))
+    }
+
+    /// All faces hit by ray, from either side, ordered by distance.
+    pub fn intersect_all(&self, ray: &Ray) -> Vec<Hit> {
+        let (origin, direction) = (to_f64(&ray.origin), to_f64(&ray.direction));
+        let mut hits = Vec::new();
+        self.bvh.ray(&origin, &direction, f64::INFINITY, |face, t| {
+            hits.push((face, t));
+            f64::INFINITY
+        });
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        hits.into_iter()
+            .map(|(face, t)| self.hit(&origin, &direction, face, t))
+            .collect()
+    }
+
+    /// Whether point lies inside the mesh, which should be closed.
+    ///
+    /// Counts how often rays from point cross the surface. A point is inside if an odd number
+    /// of crossings is found for the majority of three rays, which keeps rays that graze edges
+    /// or vertices from flipping the result.
+    pub fn contains(&self, point: &Vertex) -> bool {
+        let origin = to_f64(point);
+        let inside = PROBE_DIRECTIONS
+            .iter()
+            .filter(|direction| {
+                let mut crossings = 0;
+                self.bvh.ray(&origin, direction, f64::INFINITY, |_, _| {
+                    crossings += 1;
+                    f64::INFINITY
+                });
+                crossings % 2 == 1
+            })
+            .count();
+        inside >= 2
+    }
+
+    pub(crate) fn bvh(&self) -> &Bvh<'a> {
+        &self.bvh
+    }
+
+    fn hit(&self, origin: &[f64; 3], direction: &[f64; 3], face: usize, t: f64) -> Hit {
+        Hit {
+            face,
+            distance: (t * norm(direction)) as f32,
+            point: to_f32(&add(origin, &scale(direction, t))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::octahedron;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn intersect_octahedron() {
+        let mesh = octahedron();
+        let raycaster = Raycaster::new(&mesh);
+        let ray = Ray {
+            origin: [0.1, 0.1, 5.],
+            direction: [0., 0., -2.],
+        };
+        let hit = raycaster.intersect(&ray).unwrap();
+        assert!((hit.distance - 4.2).abs() < 1e-6, "{:?}", hit);
+        assert_eq!(hit.face, 0);
+        let all = raycaster.intersect_all(&ray);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0], hit);
+        assert!((all[1].point[2] + 0.8).abs() < 1e-6, "{:?}", all);
+        assert!(raycaster
+            .intersect(&Ray {
+                origin: [0.1, 0.1, 5.],
+                direction: [0., 0., 1.],
+            })
+            .is_none());
+        // Axis parallel ray through a vertex.
+        assert_eq!(
+            raycaster
+                .intersect(&Ray {
+                    origin: [5., 0., 0.],
+                    direction: [-1., 0., 0.],
+                })
+                .unwrap()
+                .point,
+            [1., 0., 0.]
+        );
+    }
+
+    #[test]
+    fn contains_octahedron() {
+        let mesh = octahedron();
+        let raycaster = Raycaster::new(&mesh);
+        assert!(raycaster.contains(&[0., 0., 0.]));
+        assert!(raycaster.contains(&[0.3, -0.3, 0.3]));
+        assert!(!raycaster.contains(&[0.4, 0.4, 0.4]));
+        assert!(!raycaster.contains(&[2., 0., 0.]));
+    }
+
+    #[test]
+    fn intersect_matches_brute_force() {
+        let mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let raycaster = Raycaster::new(&mesh);
+        for i in 0..50 {
+            let t = f32::from(i as u8);
+            let ray = Ray {
+                origin: [(t * 0.37).sin() * 0.1, 0.3, (t * 0.13).sin() * 0.1],
+                direction: [(t * 0.71).cos() * 0.1, -1., (t * 0.29).sin() * 0.1],
+            };
+            let brute = (0..mesh.faces.len())
+                .filter_map(|f| {
+                    crate::math::ray_triangle(
+                        &to_f64(&ray.origin),
+                        &to_f64(&ray.direction),
+                        &raycaster.bvh.triangle(f),
+                    )
+                })
+                .count();
+            assert_eq!(raycaster.intersect_all(&ray).len(), brute, "{:?}", ray);
+        }
+    }
+}
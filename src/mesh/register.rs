@@ -0,0 +1,191 @@
+use super::{face_normals, sample_surface, Bvh};
+use crate::math::{
+    add, area_normal, cross, dot, multiply, norm, rotation, solve, sub, to_f32, to_f64, Matrix,
+    Vec3,
+};
+use crate::{IndexedMesh, Vertex};
+
+const SAMPLES: usize = 1000;
+const MAX_ITERATIONS: usize = 100;
+
+/// Rigid transform found by [register](fn.register.html), mapping a point p to
+/// `rotation * p + translation`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Registration {
+    /// Rotation matrix, stored by rows.
+    pub rotation: [[f32; 3]; 3],
+    /// Translation applied after the rotation.
+    pub translation: Vertex,
+    /// Root mean square distance of the transformed surface samples of the first mesh from
+    /// the surface of the second mesh.
+    pub rms_error: f32,
+    /// Number of iterations until the transform converged.
+    pub iterations: usize,
+}
+
+impl Registration {
+    /// Applies the transform to a point.
+    pub fn apply(&self, point: &Vertex) -> Vertex {
+        let p = to_f64(point);
+        let rotated = self.rotation.map(|row| dot(&to_f64(&row), &p));
+        to_f32(&add(&rotated, &to_f64(&self.translation)))
+    }
+}
+
+fn transform(rotation: &Matrix, translation: &Vec3, p: &Vec3) -> Vec3 {
+    add(&rotation.map(|row| dot(&row, p)), translation)
+}
+
+/// Finds the rigid transform that aligns mesh a onto mesh b, e.g. to compare a scanned part
+/// with the STL it was printed from.
+///
+/// This is point-to-plane ICP (iterative closest point): points sampled from the surface of a
+/// are repeatedly matched to their closest points on b, and the transform is updated to move
+/// them onto the tangent planes of b. ICP converges to the nearest local optimum, so the meshes
+/// should already be roughly aligned, e.g. by their bounding boxes, and differ by no more than
+/// a few degrees of rotation. Sampling is seeded, so the result is deterministic.
+///
+/// Returns None if either mesh has no area.
+///
+/// ```
+/// let triangle = |z: f32| stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., z], [1., 0., z], [0., 1., z]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let registration = stl_io::mesh::register(&triangle(0.5), &triangle(0.)).unwrap();
+/// assert!((registration.translation[2] + 0.5).abs() < 1e-6);
+/// assert!(registration.rms_error < 1e-6);
+/// ```
+pub fn register(a: &IndexedMesh, b: &IndexedMesh) -> Option<Registration> {
+    let samples = sample_surface(a, SAMPLES, 0)
+        .iter()
+        .map(|s| to_f64(&s.point))
+        .collect::<Vec<_>>();
+    let area_b = b
+        .faces
+        .iter()
+        .map(|f| norm(&area_normal(&f.vertices.map(|v| to_f64(&b.vertices[v])))))
+        .sum::<f64>();
+    if samples.is_empty() || !area_b.is_finite() || area_b <= 0. {
+        return None;
+    }
+    let bvh = Bvh::build(b);
+    let normals = face_normals(b);
+    // Matches every transformed sample to its closest point on b.
+    let matches = |rotation: &Matrix, translation: &Vec3| {
+        samples
+            .iter()
+            .filter_map(|p| {
+                let p = transform(rotation, translation, p);
+                let closest = bvh.closest_point(&to_f32(&p))?;
+                Some((p, to_f64(&closest.point), normals[closest.face]))
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut rotation_matrix = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+    let mut translation = [0.; 3];
+    // Bounding sphere diameter of the samples, to make the convergence test scale invariant.
+    let size = samples
+        .iter()
+        .map(|p| norm(&sub(p, &samples[0])))
+        .fold(0., f64::max)
+        .max(f64::MIN_POSITIVE);
+    let mut iterations = 0;
+    while iterations < MAX_ITERATIONS {
+        iterations += 1;
+        // Normal equations of the linearized problem: minimize the sum of
+        // ((p + r × p + t - q) · n)² over the small rotation r and the translation t.
+        let mut ata = vec![vec![0.; 6]; 6];
+        let mut atb = vec![0.; 6];
+        for (p, q, normal) in matches(&rotation_matrix, &translation) {
+            let n = match normal {
+                Some(n) => n,
+                None => continue,
+            };
+            let c = cross(&p, &n);
+            let row = [c[0], c[1], c[2], n[0], n[1], n[2]];
+            let residual = dot(&sub(&q, &p), &n);
+            for i in 0..6 {
+                for j in 0..6 {
+                    ata[i][j] += row[i] * row[j];
+                }
+                atb[i] += row[i] * residual;
+            }
+        }
+        // Damp the directions that the surface does not constrain, e.g. sliding along a plane.
+        let damping = 1e-9 * (0..6).map(|i| ata[i][i]).sum::<f64>() + 1e-300;
+        for (i, row) in ata.iter_mut().enumerate() {
+            row[i] += damping;
+        }
+        let step = match solve(ata, atb) {
+            Some(step) => step,
+            None => break,
+        };
+        let r = [step[0], step[1], step[2]];
+        let t = [step[3], step[4], step[5]];
+        let delta = rotation(&r);
+        rotation_matrix = multiply(&delta, &rotation_matrix);
+        translation = add(&delta.map(|row| dot(&row, &translation)), &t);
+        if norm(&r) < 1e-9 && norm(&t) < 1e-9 * size {
+            break;
+        }
+    }
+    let (sum, count) = matches(&rotation_matrix, &translation).into_iter().fold(
+        (0., 0),
+        |(sum, count), (p, q, _)| {
+            let d = norm(&sub(&q, &p));
+            (sum + d * d, count + 1)
+        },
+    );
+    if count == 0 {
+        return None;
+    }
+    Some(Registration {
+        rotation: rotation_matrix.map(|row| to_f32(&row)),
+        translation: to_f32(&translation),
+        rms_error: (sum / f64::from(count)).sqrt() as f32,
+        iterations,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::scale;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn register_moved_bunny() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let itself = register(&bunny, &bunny).unwrap();
+        assert!(itself.rms_error < 1e-6);
+        assert!(norm(&to_f64(&itself.translation)) < 1e-5);
+
+        // Rotate by 5 degrees around a skew axis and shift by a tenth of the size.
+        let r = rotation(&scale(&[1., 2., 2.], 5f64.to_radians() / 3.));
+        let t = [0.01, -0.005, 0.008];
+        let mut moved = bunny.clone();
+        for v in &mut moved.vertices {
+            *v = to_f32(&transform(&r, &t, &to_f64(v)));
+        }
+        let registration = register(&moved, &bunny).unwrap();
+        assert!(registration.rms_error < 1e-5);
+        assert!(registration.iterations < MAX_ITERATIONS);
+        for (m, v) in moved.vertices.iter().zip(&bunny.vertices) {
+            let back = registration.apply(m);
+            assert!(norm(&sub(&to_f64(&back), &to_f64(v))) < 1e-4);
+        }
+
+        let empty = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        assert_eq!(register(&empty, &bunny), None);
+        assert_eq!(register(&bunny, &empty), None);
+        let mut flat = bunny.clone();
+        for v in &mut flat.vertices {
+            *v = [0., 0., 0.];
+        }
+        assert_eq!(register(&bunny, &flat), None);
+    }
+}
@@ -0,0 +1,223 @@
+use super::face_normals;
+use crate::math::{add, area_normal, norm, scale, sub, to_f32, to_f64, Random, Vec3};
+use crate::{IndexedMesh, Normal, Vertex};
+use std::collections::HashMap;
+
+/// Point sampled from a mesh surface by [sample_surface](fn.sample_surface.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SurfacePoint {
+    /// The point on the surface.
+    pub point: Vertex,
+    /// Unit normal of the face the point lies on, derived from its winding.
+    pub normal: Normal,
+    /// Index of the face the point lies on.
+    pub face: usize,
+}
+
+// Picks faces with probability proportional to their area, and points uniformly within them.
+struct Sampler<'a> {
+    mesh: &'a IndexedMesh,
+    normals: Vec<Option<Vec3>>,
+    // Cumulative face areas.
+    areas: Vec<f64>,
+    random: Random,
+}
+
+impl<'a> Sampler<'a> {
+    fn new(mesh: &'a IndexedMesh, seed: u64) -> Option<Sampler<'a>> {
+        let mut total = 0.;
+        let mut areas = Vec::with_capacity(mesh.faces.len());
+        for f in &mesh.faces {
+            total += norm(&area_normal(&f.vertices.map(|v| to_f64(&mesh.vertices[v]))));
+            areas.push(total);
+        }
+        if !total.is_finite() || total <= 0. {
+            return None;
+        }
+        Some(Sampler {
+            mesh,
+            normals: face_normals(mesh),
+            areas,
+            random: Random::new(seed),
+        })
+    }
+
+    fn sample(&mut self) -> SurfacePoint {
+        let total = self.areas[self.areas.len() - 1];
+        let target = self.random.next_f64() * total;
+        let face = self
+            .areas
+            .partition_point(|&a| a <= target)
+            .min(self.areas.len() - 1);
+        let [a, b, c] = self.mesh.faces[face]
+            .vertices
+            .map(|v| to_f64(&self.mesh.vertices[v]));
+        // Square root warping makes the barycentric coordinates uniform over the triangle.
+        let r1 = self.random.next_f64().sqrt();
+        let r2 = self.random.next_f64();
+        let point = add(
+            &a,
+            &add(
+                &scale(&sub(&b, &a), r1 * (1. - r2)),
+                &scale(&sub(&c, &a), r1 * r2),
+            ),
+        );
+        SurfacePoint {
+            point: to_f32(&point),
+            // Faces without area are never picked.
+            normal: to_f32(&self.normals[face].unwrap_or([0.; 3])),
+            face,
+        }
+    }
+}
+
+/// Samples n_points points uniformly distributed over the surface of mesh, e.g. for
+/// registration, inspection or machine learning datasets.
+///
+/// The same seed always gives the same points. Returns no points if the mesh has no area.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let samples = stl_io::mesh::sample_surface(&mesh, 100, 7);
+/// assert_eq!(samples.len(), 100);
+/// assert!(samples.iter().all(|s| s.point[0] + s.point[1] <= 1. && s.normal == [0., 0., 1.]));
+/// ```
+pub fn sample_surface(mesh: &IndexedMesh, n_points: usize, seed: u64) -> Vec<SurfacePoint> {
+    match Sampler::new(mesh, seed) {
+        Some(mut sampler) => (0..n_points).map(|_| sampler.sample()).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Like [sample_surface](fn.sample_surface.html), but keeps the points at least min_distance
+/// apart, which covers the surface more evenly (Poisson disk sampling).
+///
+/// Points are drawn uniformly and rejected if they are too close to one that was kept. Fewer
+/// than n_points are returned if 30 times as many draws do not find enough points, i.e. if
+/// the surface is too small for n_points at that distance.
+pub fn sample_surface_poisson(
+    mesh: &IndexedMesh,
+    n_points: usize,
+    min_distance: f32,
+    seed: u64,
+) -> Vec<SurfacePoint> {
+    let mut sampler = match Sampler::new(mesh, seed) {
+        Some(sampler) => sampler,
+        None => return Vec::new(),
+    };
+    if min_distance.is_nan() || min_distance <= 0. {
+        return (0..n_points).map(|_| sampler.sample()).collect();
+    }
+    let cell_size = f64::from(min_distance);
+    let cell = |p: &Vertex| p.map(|c| (f64::from(c) / cell_size).floor() as i64);
+    let mut grid = HashMap::<[i64; 3], Vec<usize>>::new();
+    let mut points = Vec::<SurfacePoint>::new();
+    for _ in 0..n_points.saturating_mul(30) {
+        if points.len() == n_points {
+            break;
+        }
+        let candidate = sampler.sample();
+        let p = to_f64(&candidate.point);
+        let center = cell(&candidate.point);
+        let mut too_close = false;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let key = [center[0] + dx, center[1] + dy, center[2] + dz];
+                    for &i in grid.get(&key).into_iter().flatten() {
+                        let q = to_f64(&points[i].point);
+                        if norm(&sub(&p, &q)) < cell_size {
+                            too_close = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        if !too_close {
+            grid.entry(center).or_default().push(points.len());
+            points.push(candidate);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::dot;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn samples_lie_on_bunny() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let samples = sample_surface(&bunny, 2000, 1);
+        assert_eq!(samples.len(), 2000);
+        assert_eq!(samples, sample_surface(&bunny, 2000, 1));
+        assert_ne!(samples, sample_surface(&bunny, 2000, 2));
+        let bvh = crate::mesh::Bvh::build(&bunny);
+        for s in &samples {
+            assert!(bvh.closest_point(&s.point).unwrap().distance < 1e-6);
+            // The point lies in the plane of its face.
+            let a = to_f64(&bunny.vertices[bunny.faces[s.face].vertices[0]]);
+            let offset = dot(&sub(&to_f64(&s.point), &a), &to_f64(&s.normal));
+            assert!(offset.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn samples_are_uniform_by_area() {
+        // Two triangles, the second with three times the area of the first.
+        let mesh = IndexedMesh {
+            vertices: vec![
+                [0., 0., 0.],
+                [1., 0., 0.],
+                [0., 1., 0.],
+                [0., 0., 1.],
+                [3., 0., 1.],
+                [0., 1., 1.],
+            ],
+            faces: vec![
+                crate::IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [0, 1, 2],
+                },
+                crate::IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [3, 4, 5],
+                },
+            ],
+        };
+        let samples = sample_surface(&mesh, 4000, 3);
+        let on_second = samples.iter().filter(|s| s.face == 1).count();
+        assert!((on_second as f64 / 4000. - 0.75).abs() < 0.03);
+        // Within the first triangle, a quarter of the points lies in its corner x + y < 0.5.
+        let first = samples.iter().filter(|s| s.face == 0).collect::<Vec<_>>();
+        let corner = first
+            .iter()
+            .filter(|s| s.point[0] + s.point[1] < 0.5)
+            .count();
+        assert!((corner as f64 / first.len() as f64 - 0.25).abs() < 0.05);
+    }
+
+    #[test]
+    fn poisson_samples_keep_distance() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let samples = sample_surface_poisson(&bunny, 200, 0.01, 5);
+        assert!(samples.len() > 50);
+        for (i, a) in samples.iter().enumerate() {
+            for b in &samples[i + 1..] {
+                let d = norm(&sub(&to_f64(&a.point), &to_f64(&b.point)));
+                assert!(d >= 0.01);
+            }
+        }
+        let empty = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        assert!(sample_surface_poisson(&empty, 10, 0.1, 0).is_empty());
+    }
+}
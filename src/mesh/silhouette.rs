@@ -0,0 +1,176 @@
+use super::{edge_faces, face_normals, Polyline};
+use crate::math::{dot, to_f64};
+use crate::{IndexedMesh, Normal};
+use std::collections::BTreeMap;
+
+/// Feature lines of a mesh as seen from a view direction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Silhouette {
+    /// Outlines: edges between a front and a back facing face, and boundary edges.
+    pub contours: Vec<Polyline>,
+    /// Sharp edges between front facing faces.
+    pub creases: Vec<Polyline>,
+}
+
+impl IndexedMesh {
+    /// Extracts the contour and crease edges visible when looking along view_direction, chained
+    /// into polylines.
+    /// An edge is a crease if the normals of its faces differ by more than crease_angle
+    /// (in radians). Faces are oriented by their winding; stored normals are ignored.
+    /// No hidden line removal is done.
+    ///
+    /// ```
+    /// let mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+    ///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+    /// };
+    /// let silhouette = mesh.silhouette(&[0., 0., -1.], 0.5);
+    /// assert_eq!(silhouette.contours.len(), 1);
+    /// assert!(silhouette.contours[0].closed);
+    /// ```
+    pub fn silhouette(&self, view_direction: &Normal, crease_angle: f32) -> Silhouette {
+        let view = to_f64(view_direction);
+        let cos_crease = f64::from(crease_angle).cos();
+        let normals = face_normals(self);
+        let front_facing = |f: usize| normals[f].map(|n| dot(&n, &view) < 0.);
+
+        let mut contours = Vec::new();
+        let mut creases = Vec::new();
+        for (&(a, b), faces) in &edge_faces(self) {
+            if a == b {
+                continue;
+            }
+            let facing = faces
+                .iter()
+                .filter_map(|&f| front_facing(f))
+                .collect::<Vec<_>>();
+            if facing.is_empty() {
+                continue;
+            }
+            if faces.len() == 1 || facing.iter().any(|&f| f != facing[0]) {
+                contours.push([a, b]);
+            } else if facing[0] {
+                let sharp = faces.iter().enumerate().any(|(i, &f)| {
+                    faces[i + 1..]
+                        .iter()
+                        .any(|&g| match (normals[f], normals[g]) {
+                            (Some(n), Some(m)) => dot(&n, &m) < cos_crease,
+                            _ => false,
+                        })
+                });
+                if sharp {
+                    creases.push([a, b]);
+                }
+            }
+        }
+        // edge_faces is a HashMap, so sort to make the polylines independent of its order.
+        contours.sort_unstable();
+        creases.sort_unstable();
+        Silhouette {
+            contours: chain_edges(&contours, self),
+            creases: chain_edges(&creases, self),
+        }
+    }
+}
+
+// Joins edges (pairs of vertex indices) into maximal polylines. Chains stop at vertices that
+// are not shared by exactly two edges.
+fn chain_edges(edges: &[[usize; 2]], mesh: &IndexedMesh) -> Vec<Polyline> {
+    let mut adjacency = BTreeMap::new();
+    for (ei, e) in edges.iter().enumerate() {
+        for &v in e {
+            adjacency.entry(v).or_insert_with(Vec::new).push(ei);
+        }
+    }
+    let mut used = vec![false; edges.len()];
+    let mut polylines = Vec::new();
+    let walk = |start: usize, used: &mut Vec<bool>| {
+        let mut points = vec![mesh.vertices[start]];
+        let mut current = start;
+        loop {
+            let next = adjacency[&current].iter().find(|&&e| !used[e]).cloned();
+            let e = match next {
+                Some(e) => e,
+                None => break,
+            };
+            used[e] = true;
+            current = if edges[e][0] == current {
+                edges[e][1]
+            } else {
+                edges[e][0]
+            };
+            if current == start {
+                return Polyline {
+                    points,
+                    closed: true,
+                };
+            }
+            points.push(mesh.vertices[current]);
+            if adjacency[&current].len() != 2 {
+                break;
+            }
+        }
+        Polyline {
+            points,
+            closed: false,
+        }
+    };
+    let ends = adjacency
+        .iter()
+        .filter(|&(_, e)| e.len() != 2)
+        .map(|(&v, _)| v)
+        .collect::<Vec<_>>();
+    for v in ends {
+        while adjacency[&v].iter().any(|&e| !used[e]) {
+            polylines.push(walk(v, &mut used));
+        }
+    }
+    for e in 0..edges.len() {
+        if !used[e] {
+            polylines.push(walk(edges[e][0], &mut used));
+        }
+    }
+    polylines
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::cube;
+
+    #[test]
+    fn cube_seen_from_above() {
+        let silhouette = cube(0., 1.).silhouette(&[0., 0., -1.], 0.5);
+        // The side faces are seen edge-on, so the outline is the top square.
+        assert_eq!(silhouette.creases, vec![]);
+        assert_eq!(silhouette.contours.len(), 1);
+        assert!(silhouette.contours[0].closed);
+        assert_eq!(silhouette.contours[0].points.len(), 4);
+        for p in &silhouette.contours[0].points {
+            assert_eq!(p[2], 1.);
+        }
+    }
+
+    #[test]
+    fn cube_seen_from_corner() {
+        let silhouette = cube(0., 1.).silhouette(&[-1., -1., -1.], 0.5);
+        assert_eq!(silhouette.contours.len(), 1);
+        assert!(silhouette.contours[0].closed);
+        assert_eq!(silhouette.contours[0].points.len(), 6);
+        // The three edges meeting at the corner closest to the viewer.
+        assert_eq!(silhouette.creases.len(), 3);
+        for crease in &silhouette.creases {
+            assert!(!crease.closed);
+            assert_eq!(crease.points.len(), 2);
+        }
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let expected = cube(0., 1.).silhouette(&[-1., -1., -1.], 0.5);
+        assert_eq!(expected.contours[0].points[0], [1., 0., 0.]);
+        assert_eq!(expected.creases[0].points, vec![[1., 1., 0.], [1., 1., 1.]]);
+        for _ in 0..10 {
+            assert_eq!(cube(0., 1.).silhouette(&[-1., -1., -1.], 0.5), expected);
+        }
+    }
+}
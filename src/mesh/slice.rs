@@ -0,0 +1,333 @@
+use super::Polyline;
+use crate::math::{add, dot, scale, sub, to_f32, to_f64};
+use crate::{IndexedMesh, Normal, Vertex};
+use std::collections::HashMap;
+
+/// Plane through distance * normal, perpendicular to normal: all points p with
+/// dot(normal, p) == dot(normal, normal) * distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// Normal of the plane. Does not need to be of unit length.
+    pub normal: Normal,
+    /// Distance of the plane from the origin along normal, in units of its length. For a unit
+    /// normal, this is the plain distance.
+    pub distance: f32,
+}
+
+/// Contours of one layer of [slice_stack](fn.slice_stack.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    /// Height of the slicing plane.
+    pub z: f32,
+    /// Cross section outlines at z.
+    pub contours: Vec<Polyline>,
+}
+
+/// Intersects mesh with plane.
+///
+/// For closed meshes the result consists of closed polylines, counter-clockwise around material
+/// when seen from the side plane.normal points to. Vertices exactly on the plane are treated
+/// as lying slightly above it, so touching the plane never creates spurious or open contours,
+/// and faces within the plane are skipped. Open polylines only result from holes in the mesh.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+///     faces: vec![
+///         stl_io::IndexedTriangle { normal: [0., 0., -1.], vertices: [0, 2, 1] },
+///         stl_io::IndexedTriangle { normal: [0., -1., 0.], vertices: [0, 1, 3] },
+///         stl_io::IndexedTriangle { normal: [-1., 0., 0.], vertices: [0, 3, 2] },
+///         stl_io::IndexedTriangle { normal: [1., 1., 1.], vertices: [1, 2, 3] },
+///     ],
+/// };
+/// let plane = stl_io::mesh::Plane { normal: [0., 0., 1.], distance: 0.5 };
+/// let contours = stl_io::mesh::slice(&mesh, &plane);
+/// assert_eq!(contours.len(), 1);
+/// assert!(contours[0].closed);
+/// assert_eq!(contours[0].points.len(), 3);
+/// ```
+pub fn slice(mesh: &IndexedMesh, plane: &Plane) -> Vec<Polyline> {
+    slice_faces(mesh, plane, 0..mesh.faces.len())
+}
+
+/// Slices mesh with horizontal planes z_step apart, starting half a step above the lowest
+/// vertex and ending below the highest one, as used for layer by layer manufacturing.
+///
+/// # Panics
+///
+/// Panics if z_step is not positive, or so small compared to the height of mesh that the
+/// layers would not fit in memory.
+pub fn slice_stack(mesh: &IndexedMesh, z_step: f32) -> Vec<Layer> {
+    assert!(z_step > 0., "z_step must be positive, got {}", z_step);
+    let bbox = match crate::BoundingBox::from_vertices(mesh.vertices.iter().cloned()) {
+        Some(bbox) => bbox,
+        None => return Vec::new(),
+    };
+    let (min_z, step) = (f64::from(bbox.min[2]), f64::from(z_step));
+    let layer_z = |i: usize| (min_z + (i as f64 + 0.5) * step) as f32;
+    // Layers strictly below the top, as a layer at its height would be empty.
+    let num_layers = ((f64::from(bbox.max[2]) - min_z) / step - 0.5)
+        .ceil()
+        .max(0.);
+    assert!(
+        num_layers * ::std::mem::size_of::<Layer>() as f64 <= isize::MAX as f64,
+        "z_step {} gives too many layers",
+        z_step
+    );
+    let num_layers = num_layers as usize;
+    // Only pass each layer the faces spanning its height.
+    let mut layer_faces = vec![Vec::new(); num_layers];
+    for (fi, f) in mesh.faces.iter().enumerate() {
+        let z = f.vertices.iter().map(|&v| f64::from(mesh.vertices[v][2]));
+        let (lo, hi) = z.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), z| {
+            (lo.min(z), hi.max(z))
+        });
+        let first = ((lo - min_z) / step - 0.5).floor().max(0.) as usize;
+        for (i, faces) in layer_faces.iter_mut().enumerate().skip(first) {
+            if f64::from(layer_z(i)) > hi {
+                break;
+            }
+            faces.push(fi);
+        }
+    }
+    layer_faces
+        .into_iter()
+        .enumerate()
+        .map(|(i, faces)| {
+            let z = layer_z(i);
+            let plane = Plane {
+                normal: [0., 0., 1.],
+                distance: z,
+            };
+            Layer {
+                z,
+                contours: slice_faces(mesh, &plane, faces),
+            }
+        })
+        .collect()
+}
+
+fn slice_faces<I>(mesh: &IndexedMesh, plane: &Plane, faces: I) -> Vec<Polyline>
+where
+    I: IntoIterator<Item = usize>,
+{
+    let normal = to_f64(&plane.normal);
+    let offset = dot(&normal, &normal) * f64::from(plane.distance);
+    let mut distances = HashMap::new();
+    let mut distance = |v: usize| {
+        *distances
+            .entry(v)
+            .or_insert_with(|| dot(&normal, &to_f64(&mesh.vertices[v])) - offset)
+    };
+    // Each face crossing the plane contributes a segment between two crossed edges, directed so
+    // that material lies to its left. Edges are keyed by their vertices, smaller index first,
+    // so that neighboring faces agree on crossings.
+    let mut segments = HashMap::new();
+    for fi in faces {
+        let v = mesh.faces[fi].vertices;
+        let above = [
+            distance(v[0]) >= 0.,
+            distance(v[1]) >= 0.,
+            distance(v[2]) >= 0.,
+        ];
+        let mut leaving = None;
+        let mut entering = None;
+        for i in 0..3 {
+            let j = (i + 1) % 3;
+            let key = (v[i].min(v[j]), v[i].max(v[j]));
+            match (above[i], above[j]) {
+                (true, false) => leaving = Some(key),
+                (false, true) => entering = Some(key),
+                _ => {}
+            }
+        }
+        if let (Some(from), Some(to)) = (leaving, entering) {
+            segments.insert(from, to);
+        }
+    }
+
+    let mut crossing = |(a, b): (usize, usize)| -> Vertex {
+        let (da, db) = (distance(a), distance(b));
+        if da == 0. {
+            mesh.vertices[a]
+        } else if db == 0. {
+            mesh.vertices[b]
+        } else {
+            let (pa, pb) = (to_f64(&mesh.vertices[a]), to_f64(&mesh.vertices[b]));
+            to_f32(&add(&pa, &scale(&sub(&pb, &pa), da / (da - db))))
+        }
+    };
+    let targets = segments
+        .values()
+        .cloned()
+        .collect::<::std::collections::HashSet<_>>();
+    // Start open chains at edges no segment leads to, then pick up the closed loops. Sorting
+    // makes the output independent of hash order.
+    let mut starts = segments.keys().cloned().collect::<Vec<_>>();
+    starts.sort_by_key(|key| (targets.contains(key), *key));
+    let mut polylines = Vec::new();
+    for start in starts {
+        let mut next = match segments.remove(&start) {
+            Some(next) => next,
+            None => continue,
+        };
+        let mut points = vec![crossing(start)];
+        let closed = loop {
+            push_distinct(&mut points, crossing(next));
+            if next == start {
+                break true;
+            }
+            next = match segments.remove(&next) {
+                Some(n) => n,
+                None => break false,
+            };
+        };
+        if closed {
+            // The start point was pushed again when the loop closed.
+            while points.len() > 1 && points.last() == points.first() {
+                points.pop();
+            }
+        }
+        if points.len() >= if closed { 3 } else { 2 } {
+            polylines.push(Polyline { points, closed });
+        }
+    }
+    polylines
+}
+
+// Skips repeated points, which occur where the contour runs through a vertex on the plane.
+fn push_distinct(points: &mut Vec<Vertex>, p: Vertex) {
+    if points.last() != Some(&p) {
+        points.push(p);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::cube;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    fn signed_area(points: &[Vertex]) -> f32 {
+        let n = points.len();
+        (0..n)
+            .map(|i| {
+                let (p, q) = (points[i], points[(i + 1) % n]);
+                p[0] * q[1] - q[0] * p[1]
+            })
+            .sum::<f32>()
+            / 2.
+    }
+
+    #[test]
+    fn slice_cube_counter_clockwise() {
+        let contours = slice(
+            &cube(0., 1.),
+            &Plane {
+                normal: [0., 0., 1.],
+                distance: 0.25,
+            },
+        );
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed);
+        assert_eq!(signed_area(&contours[0].points), 1.);
+        assert!(contours[0].points.iter().all(|p| p[2] == 0.25));
+    }
+
+    #[test]
+    fn slice_with_scaled_normal() {
+        let unit = Plane {
+            normal: [0., 0., 1.],
+            distance: 0.5,
+        };
+        let scaled = Plane {
+            normal: [0., 0., 2.],
+            distance: 0.25,
+        };
+        assert_eq!(slice(&cube(0., 1.), &scaled)[0].points[0][2], 0.5);
+        assert_eq!(slice(&cube(0., 1.), &scaled), slice(&cube(0., 1.), &unit));
+    }
+
+    #[test]
+    fn slice_through_vertices() {
+        let cube = cube(0., 1.);
+        let plane = |distance| Plane {
+            normal: [0., 0., 1.],
+            distance,
+        };
+        // The bottom and top faces lie in the plane. Touching from above or below must neither
+        // produce open nor degenerate contours.
+        assert!(slice(&cube, &plane(0.)).is_empty());
+        let top = slice(&cube, &plane(1.));
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].points.len(), 4);
+        assert_eq!(signed_area(&top[0].points), 1.);
+
+        // A diagonal plane through two opposite vertical edges and the vertices on them.
+        let diagonal = slice(
+            &cube,
+            &Plane {
+                normal: [1., -1., 0.],
+                distance: 0.,
+            },
+        );
+        assert_eq!(diagonal.len(), 1);
+        assert!(diagonal[0].closed);
+    }
+
+    #[test]
+    fn slice_open_mesh() {
+        let mut cube = cube(0., 1.);
+        cube.faces.truncate(10);
+        let contours = slice(
+            &cube,
+            &Plane {
+                normal: [0., 0., 1.],
+                distance: 0.5,
+            },
+        );
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].closed);
+        // Three sides of two triangles each, ending at the missing side x = 1.
+        assert_eq!(contours[0].points.len(), 7);
+        assert_eq!(contours[0].points[0], [1., 1., 0.5]);
+        assert_eq!(contours[0].points[6], [1., 0., 0.5]);
+    }
+
+    #[test]
+    fn slice_stack_layer_count() {
+        // A height of 2.5 steps gives layers at 0.5 and 1.5 steps, none at the top.
+        let layers = slice_stack(&cube(0., 1.), 0.4);
+        assert_eq!(layers.len(), 2);
+        assert!(layers.iter().all(|layer| layer.contours.len() == 1));
+        assert_eq!(slice_stack(&cube(0., 1.), 0.25).len(), 4);
+        assert_eq!(slice_stack(&cube(0., 1.), 1.5).len(), 1);
+        assert!(slice_stack(&cube(0., 1.), 3.).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "too many layers")]
+    fn slice_stack_tiny_step() {
+        slice_stack(&cube(0., 1.), 1e-30);
+    }
+
+    #[test]
+    fn slice_stack_bunny() {
+        let mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let layers = slice_stack(&mesh, 0.01);
+        assert!(!layers.is_empty());
+        for layer in &layers {
+            let single = slice(
+                &mesh,
+                &Plane {
+                    normal: [0., 0., 1.],
+                    distance: layer.z,
+                },
+            );
+            assert_eq!(layer.contours, single, "z = {}", layer.z);
+            for c in &layer.contours {
+                assert!(c.points.iter().all(|p| (p[2] - layer.z).abs() < 1e-6));
+            }
+        }
+    }
+}
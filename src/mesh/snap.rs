@@ -0,0 +1,115 @@
+use crate::{IndexedMesh, IndexedMeshBuilder, Triangle};
+
+impl IndexedMesh {
+    /// Rounds all vertex coordinates to the nearest multiple of cell_size, then merges vertices
+    /// that coincide afterwards and removes the faces that collapsed.
+    ///
+    /// This closes cracks narrower than about half a cell, e.g. between triangles whose shared
+    /// corners differ by floating point noise. Vertices are renumbered in order of first use
+    /// by the remaining faces, so unused vertices are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if cell_size is not positive.
+    ///
+    /// ```
+    /// let mut mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [1.0000001, 0., 0.]],
+    ///     faces: vec![
+    ///         stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] },
+    ///         stl_io::IndexedTriangle { normal: [0., 0., -1.], vertices: [0, 2, 3] },
+    ///     ],
+    /// };
+    /// mesh.snap_to_grid(0.001);
+    /// assert_eq!(mesh.vertices.len(), 3);
+    /// assert_eq!(mesh.faces[1].vertices, [0, 2, 1]);
+    /// ```
+    pub fn snap_to_grid(&mut self, cell_size: f32) {
+        assert!(
+            cell_size > 0.,
+            "cell_size must be positive, got {}",
+            cell_size
+        );
+        let cell = f64::from(cell_size);
+        let snapped = self
+            .vertices
+            .iter()
+            .map(|v| {
+                // Adding zero turns -0 into 0, so that both merge.
+                v.map(|c| ((f64::from(c) / cell).round() * cell) as f32 + 0.)
+            })
+            .collect::<Vec<_>>();
+        let mut builder = IndexedMeshBuilder::default();
+        for f in &self.faces {
+            let vertices = f.vertices.map(|v| snapped[v]);
+            if vertices[0] != vertices[1]
+                && vertices[1] != vertices[2]
+                && vertices[2] != vertices[0]
+            {
+                builder.push(&Triangle {
+                    normal: f.normal,
+                    vertices,
+                });
+            }
+        }
+        *self = builder.build();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{IndexedMesh, IndexedTriangle};
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn snap_closes_cracks() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        // Split every vertex into one slightly different copy per face.
+        let mut cracked = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        for (fi, f) in bunny.faces.iter().enumerate() {
+            let mut vertices = [0; 3];
+            for (v, &old) in vertices.iter_mut().zip(&f.vertices) {
+                let noise = (fi % 3) as f32 * 1e-7;
+                let p = bunny.vertices[old];
+                cracked.vertices.push([p[0] + noise, p[1] - noise, p[2]]);
+                *v = cracked.vertices.len() - 1;
+            }
+            cracked.faces.push(IndexedTriangle {
+                normal: f.normal,
+                vertices,
+            });
+        }
+        assert!(cracked.validate().is_err());
+        cracked.snap_to_grid(1e-4);
+        assert_eq!(cracked.vertices.len(), bunny.vertices.len());
+        assert_eq!(cracked.faces.len(), bunny.faces.len());
+        crate::testing::assert_meshes_eq(&cracked, &bunny, 1e-4);
+    }
+
+    #[test]
+    fn snap_removes_collapsed_faces() {
+        let mut mesh = IndexedMesh {
+            vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 0.1, 0.], [-0.01, 1., 0.]],
+            faces: vec![
+                IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [0, 1, 2],
+                },
+                IndexedTriangle {
+                    normal: [0., 0., 1.],
+                    vertices: [0, 1, 3],
+                },
+            ],
+        };
+        mesh.snap_to_grid(0.5);
+        assert_eq!(
+            mesh.vertices,
+            vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]
+        );
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(mesh.faces[0].vertices, [0, 1, 2]);
+    }
+}
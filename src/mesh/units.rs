@@ -0,0 +1,116 @@
+use crate::{BoundingBox, IndexedMesh};
+
+/// Length unit of the coordinates in a mesh. STL does not store it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// 0.001 m, the common default of 3D printing software.
+    Millimeter,
+    /// 0.01 m.
+    Centimeter,
+    /// 1 m.
+    Meter,
+    /// 25.4 mm.
+    Inch,
+    /// 304.8 mm.
+    Foot,
+}
+
+impl Unit {
+    /// Length of the unit in millimeters.
+    pub fn millimeters(self) -> f64 {
+        match self {
+            Unit::Millimeter => 1.,
+            Unit::Centimeter => 10.,
+            Unit::Meter => 1000.,
+            Unit::Inch => 25.4,
+            Unit::Foot => 304.8,
+        }
+    }
+}
+
+/// Guesses the unit of mesh from the largest side of its bounding box, assuming a part of
+/// printable size between 1 cm and 2 m.
+///
+/// A largest side from 10 to 2000 suggests millimeters, from 0.5 to 10 inches, and below 0.5
+/// meters. None is returned for empty meshes and larger extents, which fit no unit well.
+/// Centimeters and feet are never guessed, as they overlap with the other units.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [2., 0., 0.], [0., 1.5, 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// assert_eq!(stl_io::mesh::guess_units(&mesh), Some(stl_io::mesh::Unit::Inch));
+/// ```
+pub fn guess_units(mesh: &IndexedMesh) -> Option<Unit> {
+    let bbox = BoundingBox::from_vertices(mesh.vertices.iter().cloned())?;
+    let extent = (0..3)
+        .map(|i| f64::from(bbox.max[i]) - f64::from(bbox.min[i]))
+        .fold(0., f64::max);
+    match extent {
+        e if e <= 0. => None,
+        e if e < 0.5 => Some(Unit::Meter),
+        e if e < 10. => Some(Unit::Inch),
+        e if e <= 2000. => Some(Unit::Millimeter),
+        _ => None,
+    }
+}
+
+impl IndexedMesh {
+    /// Scales the vertices from one unit to another, e.g. from a guess by
+    /// [guess_units](fn.guess_units.html) to millimeters.
+    ///
+    /// ```
+    /// use stl_io::mesh::Unit;
+    /// let mut mesh = stl_io::IndexedMesh {
+    ///     vertices: vec![[0., 0., 0.], [2., 0., 0.], [0., 1.5, 0.]],
+    ///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+    /// };
+    /// mesh.convert_units(Unit::Inch, Unit::Millimeter);
+    /// assert_eq!(mesh.vertices[1], [50.8, 0., 0.]);
+    /// ```
+    pub fn convert_units(&mut self, from: Unit, to: Unit) {
+        if from == to {
+            return;
+        }
+        let factor = from.millimeters() / to.millimeters();
+        for v in &mut self.vertices {
+            for c in v.iter_mut() {
+                *c = (f64::from(*c) * factor) as f32;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("../testdata/bunny_99.stl");
+
+    #[test]
+    fn guess_and_convert_bunny() {
+        // The bunny is about 0.15 units large.
+        let mut bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        assert_eq!(guess_units(&bunny), Some(Unit::Meter));
+        bunny.convert_units(Unit::Meter, Unit::Millimeter);
+        assert_eq!(guess_units(&bunny), Some(Unit::Millimeter));
+        bunny.convert_units(Unit::Millimeter, Unit::Inch);
+        assert_eq!(guess_units(&bunny), Some(Unit::Inch));
+        bunny.convert_units(Unit::Inch, Unit::Meter);
+        let original = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        crate::testing::assert_meshes_eq(&bunny, &original, 1e-6);
+    }
+
+    #[test]
+    fn guess_nothing() {
+        let mut mesh = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        assert_eq!(guess_units(&mesh), None);
+        mesh.vertices.push([1., 2., 3.]);
+        assert_eq!(guess_units(&mesh), None);
+        mesh.vertices.push([5000., 2., 3.]);
+        assert_eq!(guess_units(&mesh), None);
+    }
+}
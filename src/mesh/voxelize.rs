@@ -0,0 +1,308 @@
+use super::Raycaster;
+use crate::math::{add, area_normal, dot, scale, sub, to_f32, to_f64, Vec3};
+use crate::{BoundingBox, IndexedMesh, IndexedMeshBuilder, Triangle, Vertex};
+
+// The cube between two grid samples as six tetrahedra around its diagonal, one for each order
+// of stepping along the axes. Neighboring cubes split their shared faces the same way.
+const TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 1, 5, 7],
+    [0, 2, 3, 7],
+    [0, 2, 6, 7],
+    [0, 4, 5, 7],
+    [0, 4, 6, 7],
+];
+
+/// Regular 3D grid of samples of a scalar field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalarGrid {
+    /// Position of the sample with index (0, 0, 0).
+    pub origin: Vertex,
+    /// Distance between neighboring samples.
+    pub spacing: f32,
+    /// Number of samples along x, y and z.
+    pub size: [usize; 3],
+    /// The samples, with x varying fastest and z slowest.
+    pub values: Vec<f32>,
+}
+
+impl ScalarGrid {
+    // Number of samples in a grid of the given size, if their values fit in memory.
+    pub(crate) fn samples(size: &[usize; 3]) -> Option<usize> {
+        let total = size
+            .iter()
+            .try_fold(1usize, |total, &n| total.checked_mul(n))?;
+        total
+            .checked_mul(::std::mem::size_of::<f32>())
+            .filter(|&bytes| bytes <= isize::MAX as usize)?;
+        Some(total)
+    }
+
+    /// The sample at index (x, y, z).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is outside of the grid.
+    pub fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        assert!(x < self.size[0] && y < self.size[1] && z < self.size[2]);
+        self.values[x + self.size[0] * (y + self.size[1] * z)]
+    }
+
+    /// Position of the sample at index (x, y, z).
+    pub fn position(&self, x: usize, y: usize, z: usize) -> Vertex {
+        [
+            self.origin[0] + x as f32 * self.spacing,
+            self.origin[1] + y as f32 * self.spacing,
+            self.origin[2] + z as f32 * self.spacing,
+        ]
+    }
+
+    /// Extracts the surface where the sampled field crosses level, by marching tetrahedra.
+    ///
+    /// Samples below level count as inside, and the triangles face away from them. The surface
+    /// is closed if all samples on the border of the grid lie above level.
+    pub fn isosurface(&self, level: f32) -> IndexedMesh {
+        let mut builder = IndexedMeshBuilder::default();
+        let [nx, ny, nz] = self.size;
+        let index = |x: usize, y: usize, z: usize| x + nx * (y + ny * z);
+        // Crossing on the edge between two samples, computed the same way for every cell.
+        let crossing = |a: usize, b: usize| -> Vec3 {
+            let (a, b) = (a.min(b), a.max(b));
+            let position = |i: usize| to_f64(&self.position(i % nx, i / nx % ny, i / nx / ny));
+            let (va, vb) = (
+                f64::from(self.values[a] - level),
+                f64::from(self.values[b] - level),
+            );
+            let (pa, pb) = (position(a), position(b));
+            add(&pa, &scale(&sub(&pb, &pa), va / (va - vb)))
+        };
+        for z in 1..nz {
+            for y in 1..ny {
+                for x in 1..nx {
+                    let corners: Vec<usize> = (0..8)
+                        .map(|c| index(x - 1 + (c & 1), y - 1 + (c >> 1 & 1), z - 1 + (c >> 2)))
+                        .collect();
+                    for t in &TETRAHEDRA {
+                        let t = t.map(|c| corners[c]);
+                        let (inside, outside): (Vec<usize>, Vec<usize>) =
+                            t.iter().partition(|&&i| self.values[i] < level);
+                        let points: Vec<Vec3> = match (inside.len(), outside.len()) {
+                            (1, 3) | (3, 1) => {
+                                let (lone, rest) = if inside.len() == 1 {
+                                    (inside[0], &outside)
+                                } else {
+                                    (outside[0], &inside)
+                                };
+                                rest.iter().map(|&i| crossing(lone, i)).collect()
+                            }
+                            (2, 2) => vec![
+                                crossing(inside[0], outside[0]),
+                                crossing(inside[0], outside[1]),
+                                crossing(inside[1], outside[1]),
+                                crossing(inside[1], outside[0]),
+                            ],
+                            _ => continue,
+                        };
+                        let center = |samples: &[usize]| {
+                            let sum = samples.iter().fold([0.; 3], |s, &i| {
+                                add(
+                                    &s,
+                                    &to_f64(&self.position(i % nx, i / nx % ny, i / nx / ny)),
+                                )
+                            });
+                            scale(&sum, 1. / samples.len() as f64)
+                        };
+                        let outward = sub(&center(&outside), &center(&inside));
+                        for k in 1..points.len() - 1 {
+                            let mut triangle = [points[0], points[k], points[k + 1]];
+                            if dot(&area_normal(&triangle), &outward) < 0. {
+                                triangle.swap(1, 2);
+                            }
+                            let vertices = triangle.map(|p| to_f32(&p));
+                            if vertices[0] != vertices[1]
+                                && vertices[1] != vertices[2]
+                                && vertices[2] != vertices[0]
+                            {
+                                builder.push(&Triangle {
+                                    normal: to_f32(&area_normal(&triangle)),
+                                    vertices,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        builder.build()
+    }
+}
+
+/// Samples the signed distance to the surface of mesh on a grid with the given spacing, which
+/// is negative inside the mesh. The grid extends one sample beyond the bounding box of the
+/// mesh, so that the surface is enclosed by positive samples.
+///
+/// Inside and outside are told apart by counting surface crossings, so the mesh should be
+/// closed. Thresholding the result at zero gives an occupancy grid.
+///
+/// Returns None if the mesh has no faces, spacing is not positive, or the values of the grid
+/// would not fit in memory.
+pub fn voxelize(mesh: &IndexedMesh, spacing: f32) -> Option<ScalarGrid> {
+    voxelize_padded(mesh, spacing, 1)
+}
+
+/// Offsets the surface of mesh by distance, outward for positive and inward for negative
+/// distances, by extracting the level set of its [voxelized](fn.voxelize.html) signed
+/// distance with the given grid spacing.
+///
+/// The result is closed, with rounded edges and corners when growing. Details smaller than
+/// spacing are lost, so the result is usually denser but less exact than the input. To hollow
+/// a model, combine it with the inward offset with its faces reversed.
+///
+/// Returns None if the mesh has no faces, spacing is not positive, or the values of the grid
+/// would not fit in memory.
+pub fn offset(mesh: &IndexedMesh, distance: f32, spacing: f32) -> Option<IndexedMesh> {
+    if spacing.is_nan() || spacing <= 0. || !distance.is_finite() {
+        return None;
+    }
+    // The grid must reach beyond the grown surface.
+    let padding = f64::from((distance.max(0.) / spacing).ceil()) + 1.;
+    if padding > usize::MAX as f64 {
+        return None;
+    }
+    let grid = voxelize_padded(mesh, spacing, padding as usize)?;
+    Some(grid.isosurface(distance))
+}
+
+fn voxelize_padded(mesh: &IndexedMesh, spacing: f32, padding: usize) -> Option<ScalarGrid> {
+    if mesh.faces.is_empty() || spacing.is_nan() || spacing <= 0. {
+        return None;
+    }
+    let raycaster = Raycaster::new(mesh);
+    let bbox: BoundingBox = raycaster.bvh().bounding_box()?;
+    let border = padding as f32 * spacing;
+    let origin = [
+        bbox.min[0] - border,
+        bbox.min[1] - border,
+        bbox.min[2] - border,
+    ];
+    let mut size = [0; 3];
+    for (i, s) in size.iter_mut().enumerate() {
+        let n = f64::from(((bbox.max[i] - origin[i]) / spacing).ceil()) + 1. + padding as f64;
+        if n.is_nan() || n > usize::MAX as f64 {
+            return None;
+        }
+        *s = n as usize;
+    }
+    let mut grid = ScalarGrid {
+        origin,
+        spacing,
+        size,
+        values: Vec::with_capacity(ScalarGrid::samples(&size)?),
+    };
+    for z in 0..size[2] {
+        for y in 0..size[1] {
+            for x in 0..size[0] {
+                let p = grid.position(x, y, z);
+                let distance = raycaster.bvh().closest_point(&p)?.distance;
+                grid.values.push(if raycaster.contains(&p) {
+                    -distance
+                } else {
+                    distance
+                });
+            }
+        }
+    }
+    Some(grid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assert_closed, octahedron, volume};
+
+    #[test]
+    fn voxelize_octahedron() {
+        let grid = voxelize(&octahedron(), 0.5).unwrap();
+        assert_eq!(grid.origin, [-1.5; 3]);
+        assert_eq!(grid.size, [7; 3]);
+        assert_eq!(grid.values.len(), 7 * 7 * 7);
+        assert_eq!(grid.position(3, 3, 3), [0.; 3]);
+        // The center is 1 / sqrt(3) away from every face.
+        assert!((grid.value(3, 3, 3) + 1. / 3f32.sqrt()).abs() < 1e-6);
+        // Vertices lie on the surface.
+        assert_eq!(grid.value(5, 3, 3), 0.);
+        assert_eq!(grid.value(6, 3, 3), 0.5);
+        for x in 0..7 {
+            for y in 0..7 {
+                for z in 0..7 {
+                    let p = grid.position(x, y, z);
+                    let inside = p.iter().map(|c| c.abs()).sum::<f32>() < 1.;
+                    assert_eq!(grid.value(x, y, z) < 0., inside, "{:?}", p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn isosurface_of_octahedron() {
+        let surface = voxelize(&octahedron(), 0.1).unwrap().isosurface(0.);
+        assert!(!surface.faces.is_empty());
+        assert_closed(&surface);
+        // The octahedron has a volume of 4 / 3.
+        assert!(
+            (volume(&surface) - 4. / 3.).abs() < 0.05,
+            "{}",
+            volume(&surface)
+        );
+    }
+
+    #[test]
+    fn offset_octahedron() {
+        // Growing by d adds the faces times d, the edges as cylinder wedges and a sphere.
+        let d = 0.2;
+        let area = 8. * 3f64.sqrt() / 2.;
+        let dihedral = (-1f64 / 3.).acos();
+        let edges = 12. * 2f64.sqrt() * (::std::f64::consts::PI - dihedral) / 2.;
+        let sphere = 4. / 3. * ::std::f64::consts::PI;
+        let grown = 4. / 3. + area * d + edges * d * d + sphere * d * d * d;
+        let outward = offset(&octahedron(), d as f32, 0.07).unwrap();
+        assert_closed(&outward);
+        assert!(
+            (volume(&outward) - grown).abs() < 0.03 * grown,
+            "{} {}",
+            volume(&outward),
+            grown
+        );
+        assert!(outward
+            .vertices
+            .iter()
+            .all(|v| v.iter().all(|c| c.abs() <= 1.25)));
+
+        // Shrinking by d moves each face inward, scaling the octahedron by 1 - d * sqrt(3).
+        let shrunk = 4. / 3. * (1. - d * 3f64.sqrt()).powi(3);
+        let inward = offset(&octahedron(), -d as f32, 0.07).unwrap();
+        assert_closed(&inward);
+        assert!(
+            (volume(&inward) - shrunk).abs() < 0.1 * shrunk,
+            "{} {}",
+            volume(&inward),
+            shrunk
+        );
+    }
+
+    #[test]
+    fn voxelize_invalid() {
+        assert!(voxelize(&octahedron(), 0.).is_none());
+        assert!(offset(&octahedron(), 0.1, -1.).is_none());
+        let empty = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        assert!(voxelize(&empty, 1.).is_none());
+        // Too many samples to address or to fit in memory.
+        assert!(voxelize(&octahedron(), 1e-7).is_none());
+        assert!(voxelize(&octahedron(), 1e-6).is_none());
+        assert!(offset(&octahedron(), 1e30, 0.1).is_none());
+        assert!(offset(&octahedron(), 1e5, 0.1).is_none());
+    }
+}
@@ -0,0 +1,128 @@
+//! Memory-mapped STL files. Requires the `mmap` feature.
+//!
+//! The operating system pages the file in as triangles are accessed, so opening even huge
+//! binary STL is nearly instant and only the parts that are used are read.
+//!
+//! Mapping a file is only sound as long as no other process modifies or truncates it while it
+//! is mapped, which the compiler cannot check, so the functions that map files are unsafe.
+
+use crate::{from_bytes, BinaryStlBytes, BinaryStlBytesIter, IndexedMesh};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::Result;
+use std::path::Path;
+
+/// Binary STL file mapped into memory.
+///
+/// ```no_run
+/// // Safety: the file is not modified while it is mapped.
+/// let stl = unsafe { stl_io::mmap::MappedStl::open("mesh.stl") }.unwrap();
+/// println!("{} triangles", stl.len());
+/// for t in stl.iter() {
+///     println!("{:?}", t.vertices);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MappedStl {
+    mmap: Mmap,
+}
+
+impl MappedStl {
+    /// Maps the binary STL file at path.
+    ///
+    /// Fails with UnexpectedEof if the file is shorter than the triangle count in its header
+    /// requires. Ascii STL is not detected, and will usually fail this way.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, for as long
+    /// as the returned value exists. Otherwise reading it is undefined behavior, and may
+    /// crash the process with SIGBUS.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<MappedStl> {
+        let file = File::open(path)?;
+        // Safety: guaranteed by the caller.
+        let mmap = unsafe { Mmap::map(&file)? };
+        from_bytes(&mmap)?;
+        Ok(MappedStl { mmap })
+    }
+
+    /// Access to the triangles of the mapped file.
+    pub fn stl(&self) -> BinaryStlBytes<'_> {
+        from_bytes(&self.mmap).expect("mapped STL was validated on open")
+    }
+
+    /// Number of triangles.
+    pub fn len(&self) -> usize {
+        self.stl().len()
+    }
+
+    /// Whether there are no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.stl().is_empty()
+    }
+
+    /// Iterates over all triangles, decoding them from the mapped file on the fly.
+    pub fn iter(&self) -> BinaryStlBytesIter<'_> {
+        self.stl().iter()
+    }
+
+    /// The mapped bytes of the whole file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+}
+
+/// Reads the ascii or binary STL file at path through a memory map, like
+/// [read_stl](../fn.read_stl.html) reads from a std::io::Read.
+///
+/// ```no_run
+/// // Safety: the file is not modified while it is read.
+/// let mesh = unsafe { stl_io::read_stl_mmap("mesh.stl") }.unwrap();
+/// ```
+///
+/// # Safety
+///
+/// The file must not be modified or truncated, by this or any other process, until this
+/// function returns. Otherwise reading it is undefined behavior, and may crash the process
+/// with SIGBUS.
+pub unsafe fn read_stl_mmap<P: AsRef<Path>>(path: P) -> Result<IndexedMesh> {
+    let file = File::open(path)?;
+    // Safety: guaranteed by the caller.
+    let mmap = unsafe { Mmap::map(&file)? };
+    crate::parse_stl_bytes(&mmap)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+    const BUNNY_99_ASCII: &[u8] = include_bytes!("testdata/bunny_99_ascii.stl");
+
+    fn temp_file(name: &str, contents: &[u8]) -> ::std::path::PathBuf {
+        let path =
+            ::std::env::temp_dir().join(format!("stl_io_mmap_{}_{}", ::std::process::id(), name));
+        ::std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    // Safety: the temporary files are only written before they are mapped.
+    #[test]
+    fn map_bunny() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let binary = temp_file("binary.stl", BUNNY_99);
+        let ascii = temp_file("ascii.stl", BUNNY_99_ASCII);
+
+        let mapped = unsafe { MappedStl::open(&binary) }.unwrap();
+        assert_eq!(mapped.len(), 99);
+        assert_eq!(mapped.as_bytes(), BUNNY_99);
+        let expected = from_bytes(BUNNY_99).unwrap().iter().collect::<Vec<_>>();
+        assert_eq!(mapped.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(unsafe { read_stl_mmap(&binary) }.unwrap(), bunny);
+        crate::testing::assert_meshes_eq(&unsafe { read_stl_mmap(&ascii) }.unwrap(), &bunny, 0.);
+
+        let error = unsafe { MappedStl::open(&ascii) }.unwrap_err();
+        assert_eq!(error.kind(), ::std::io::ErrorKind::UnexpectedEof);
+        ::std::fs::remove_file(binary).unwrap();
+        ::std::fs::remove_file(ascii).unwrap();
+    }
+}
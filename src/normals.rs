@@ -0,0 +1,160 @@
+use crate::math::{dot, normalize, to_f64};
+use crate::{Triangle, TriangleIterator};
+use std::io::Result;
+
+/// What [NormalChecker](struct.NormalChecker.html) does with a stored facet normal that
+/// disagrees with the winding of its vertices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NormalPolicy {
+    /// Keep the stored normal and record the mismatch.
+    Warn,
+    /// Replace the stored normal by the one computed from the vertices and record the
+    /// mismatch.
+    Overwrite,
+    /// Stop with an error.
+    Fail,
+}
+
+/// Checks the stored normal of each triangle read by another
+/// [TriangleIterator](trait.TriangleIterator.html) against the normal computed from the
+/// counter-clockwise winding of its vertices.
+///
+/// A stored normal mismatches if it is zero or deviates by more than a maximum angle. Faces
+/// without area have no computed normal and are never reported.
+///
+/// ```
+/// use stl_io::{NormalChecker, NormalPolicy, TriangleIterator};
+/// let mut reader = ::std::io::Cursor::new(b"solid flipped
+/// facet normal 0 0 -1
+///     outer loop
+///         vertex 0 0 0
+///         vertex 1 0 0
+///         vertex 0 1 0
+///     endloop
+/// endfacet
+/// endsolid flipped".to_vec());
+/// let stl = stl_io::create_stl_reader(&mut reader).unwrap();
+/// let mut checker = NormalChecker::new(stl, NormalPolicy::Overwrite, 0.1);
+/// let mesh = checker.to_indexed_triangles().unwrap();
+/// assert_eq!(mesh.faces[0].normal, [0., 0., 1.]);
+/// assert_eq!(checker.mismatches(), &[0]);
+/// ```
+pub struct NormalChecker<I> {
+    triangles: I,
+    policy: NormalPolicy,
+    cos_max_angle: f64,
+    index: usize,
+    mismatches: Vec<usize>,
+}
+
+impl<I: Iterator<Item = Result<Triangle>>> NormalChecker<I> {
+    /// Checks the normals of triangles according to policy, tolerating deviations up to
+    /// max_angle (in radians).
+    pub fn new(triangles: I, policy: NormalPolicy, max_angle: f32) -> NormalChecker<I> {
+        NormalChecker {
+            triangles,
+            policy,
+            cos_max_angle: f64::from(max_angle).cos(),
+            index: 0,
+            mismatches: Vec::new(),
+        }
+    }
+
+    /// Indices of the triangles read so far whose stored normal mismatched.
+    pub fn mismatches(&self) -> &[usize] {
+        &self.mismatches
+    }
+}
+
+impl<I: Iterator<Item = Result<Triangle>>> Iterator for NormalChecker<I> {
+    type Item = Result<Triangle>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut t = match self.triangles.next()? {
+            Ok(t) => t,
+            Err(e) => return Some(Err(e)),
+        };
+        let index = self.index;
+        self.index += 1;
+        let computed = match t.normal() {
+            Some(computed) => computed,
+            None => return Some(Ok(t)),
+        };
+        let agrees = normalize(&to_f64(&t.normal))
+            .is_some_and(|stored| dot(&stored, &to_f64(&computed)) >= self.cos_max_angle);
+        if !agrees {
+            match self.policy {
+                NormalPolicy::Warn => {}
+                NormalPolicy::Overwrite => t.normal = computed,
+                NormalPolicy::Fail => {
+                    return Some(Err(::std::io::Error::new(
+                        ::std::io::ErrorKind::InvalidData,
+                        format!(
+                            "normal {:?} of triangle #{} disagrees with its winding",
+                            t.normal, index
+                        ),
+                    )))
+                }
+            }
+            self.mismatches.push(index);
+        }
+        Some(Ok(t))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.triangles.size_hint()
+    }
+}
+
+impl<I: Iterator<Item = Result<Triangle>>> TriangleIterator for NormalChecker<I> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+
+    fn triangles() -> Vec<Result<Triangle>> {
+        let vertices = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+        [
+            [0., 0., 1.],
+            [0., 0.1, 1.],
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [0., 0., -1.],
+        ]
+        .iter()
+        .map(|&normal| Ok(Triangle { normal, vertices }))
+        .collect()
+    }
+
+    #[test]
+    fn check_normals() {
+        let mut warn = NormalChecker::new(triangles().into_iter(), NormalPolicy::Warn, 0.2);
+        let kept = warn.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(warn.mismatches(), &[2, 3, 4]);
+        assert_eq!(kept[4].normal, [0., 0., -1.]);
+
+        let mut overwrite =
+            NormalChecker::new(triangles().into_iter(), NormalPolicy::Overwrite, 0.05);
+        let fixed = overwrite.by_ref().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(overwrite.mismatches(), &[1, 2, 3, 4]);
+        assert!(fixed.iter().all(|t| t.normal == [0., 0., 1.]));
+
+        let fail = NormalChecker::new(triangles().into_iter(), NormalPolicy::Fail, 0.2);
+        let results = fail.collect::<Vec<_>>();
+        assert!(results[..2].iter().all(|r| r.is_ok()));
+        assert_eq!(
+            results[2].as_ref().unwrap_err().kind(),
+            ::std::io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn bunny_normals_agree() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let stl = crate::BinaryStlReader::create_triangle_iterator(&mut reader).unwrap();
+        let mut checker = NormalChecker::new(stl, NormalPolicy::Fail, 0.01);
+        assert_eq!(checker.to_indexed_triangles().unwrap().faces.len(), 99);
+        assert!(checker.mismatches().is_empty());
+    }
+}
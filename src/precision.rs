@@ -0,0 +1,117 @@
+//! Double precision meshes, for computations that should round to the single precision of STL
+//! only once, at a point the caller chooses.
+//!
+//! Everything in here only needs `core` and `alloc`, so it is available without the `std`
+//! feature.
+
+use crate::{IndexedMesh, IndexedTriangle};
+use alloc::vec::Vec;
+
+/// Double precision [Vertex](type.Vertex.html).
+pub type Vertex64 = [f64; 3];
+
+/// Double precision [IndexedTriangle](struct.IndexedTriangle.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedTriangle64 {
+    /// Normal vector of the Triangle.
+    pub normal: Vertex64,
+    /// The indices of the three vertices of the Triangle in its
+    /// [IndexedMesh64](struct.IndexedMesh64.html).
+    pub vertices: [usize; 3],
+}
+
+/// Double precision [IndexedMesh](struct.IndexedMesh.html).
+///
+/// Converting an IndexedMesh into an IndexedMesh64 is lossless, and
+/// [to_f32](struct.IndexedMesh64.html#method.to_f32) converts back, rounding once.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let mut precise = stl_io::IndexedMesh64::from(&mesh);
+/// for v in &mut precise.vertices {
+///     v[0] = v[0] / 3. * 3.;
+/// }
+/// assert_eq!(precise.to_f32(), mesh);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedMesh64 {
+    /// List of vertices.
+    pub vertices: Vec<Vertex64>,
+    /// List of triangles.
+    pub faces: Vec<IndexedTriangle64>,
+}
+
+impl IndexedMesh64 {
+    /// Rounds all coordinates and normals to the nearest f32. Coordinates beyond the range of
+    /// f32 become infinite.
+    ///
+    /// Vertices are not merged, even if distinct vertices round to the same position. Use
+    /// [snap_to_grid](struct.IndexedMesh.html#method.snap_to_grid) on the result for that.
+    pub fn to_f32(&self) -> IndexedMesh {
+        IndexedMesh {
+            vertices: self.vertices.iter().map(|v| v.map(|c| c as f32)).collect(),
+            faces: self
+                .faces
+                .iter()
+                .map(|f| IndexedTriangle {
+                    normal: f.normal.map(|c| c as f32),
+                    vertices: f.vertices,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&IndexedMesh> for IndexedMesh64 {
+    fn from(mesh: &IndexedMesh) -> IndexedMesh64 {
+        IndexedMesh64 {
+            vertices: mesh.vertices.iter().map(|v| v.map(f64::from)).collect(),
+            faces: mesh
+                .faces
+                .iter()
+                .map(|f| IndexedTriangle64 {
+                    normal: f.normal.map(f64::from),
+                    vertices: f.vertices,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<IndexedMesh> for IndexedMesh64 {
+    fn from(mesh: IndexedMesh) -> IndexedMesh64 {
+        IndexedMesh64::from(&mesh)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+
+    #[test]
+    fn bunny_round_trip_is_lossless() {
+        let bunny = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        let precise = IndexedMesh64::from(&bunny);
+        assert_eq!(precise.to_f32(), bunny);
+    }
+
+    #[test]
+    fn to_f32_rounds_to_nearest() {
+        let mesh = IndexedMesh64 {
+            vertices: vec![[0.1, 1. + 1e-9, -1e300], [1e-50, 16777217., 1e300]],
+            faces: vec![IndexedTriangle64 {
+                normal: [0.6, 0.8, 0.],
+                vertices: [0, 1, 0],
+            }],
+        };
+        let rounded = mesh.to_f32();
+        assert_eq!(rounded.vertices[0], [0.1, 1., f32::NEG_INFINITY]);
+        assert_eq!(rounded.vertices[1], [0., 16777216., f32::INFINITY]);
+        assert_eq!(rounded.faces[0].normal, [0.6, 0.8, 0.]);
+        assert_eq!(rounded.faces[0].vertices, [0, 1, 0]);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{IndexedMesh, IndexedMeshBuilder, Triangle};
+use byteorder::{ByteOrder, LittleEndian};
+use std::io::Result;
+
+/// A damaged part of an STL file skipped by [salvage_stl](fn.salvage_stl.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Corruption {
+    /// Byte offset of the damage from the start of the file.
+    pub offset: u64,
+    /// What is wrong there.
+    pub reason: String,
+}
+
+/// The result of [salvage_stl](fn.salvage_stl.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Salvaged {
+    /// All well-formed triangles.
+    pub mesh: IndexedMesh,
+    /// The damaged parts that were skipped, ordered by offset.
+    pub corruptions: Vec<Corruption>,
+}
+
+/// Reads the well-formed triangles of a possibly damaged ascii or binary STL file, and reports
+/// what had to be skipped instead of failing.
+///
+/// In binary STL, triangles with non-finite coordinates and an incomplete last triangle are
+/// skipped, and a triangle count in the header that does not match the data is reported. In
+/// ascii STL, every facet that cannot be parsed is skipped up to the next `facet` line.
+/// Only errors of reader itself are returned as Err.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"solid damaged
+/// facet normal 0 0 1
+///     outer loop
+///         vertex 0 0 0
+///         vertex 1 0 0
+/// facet normal 0 0 1
+///     outer loop
+///         vertex 0 0 0
+///         vertex 1 0 0
+///         vertex 0 1 0
+///     endloop
+/// endfacet
+/// endsolid damaged".to_vec());
+/// let salvaged = stl_io::salvage_stl(&mut reader).unwrap();
+/// assert_eq!(salvaged.mesh.faces.len(), 1);
+/// assert_eq!(salvaged.corruptions.len(), 1);
+/// // The first facet breaks off where the second one starts.
+/// assert_eq!(salvaged.corruptions[0].offset, 90);
+/// ```
+pub fn salvage_stl<R: ::std::io::Read>(read: &mut R) -> Result<Salvaged> {
+    let mut bytes = Vec::new();
+    read.read_to_end(&mut bytes)?;
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_lowercase();
+    // Binary headers may start with "solid" too, but are not followed by facets.
+    let mut salvaged = if head.trim_start().starts_with("solid") && head.contains("facet") {
+        salvage_ascii(&bytes)
+    } else {
+        salvage_binary(&bytes)
+    };
+    salvaged.corruptions.sort_by_key(|c| c.offset);
+    Ok(salvaged)
+}
+
+fn salvage_binary(bytes: &[u8]) -> Salvaged {
+    let mut builder = IndexedMeshBuilder::default();
+    let mut corruptions = Vec::new();
+    let mut corrupt = |offset: usize, reason: String| {
+        corruptions.push(Corruption {
+            offset: offset as u64,
+            reason,
+        })
+    };
+    if bytes.len() < 84 {
+        corrupt(
+            bytes.len(),
+            format!("binary STL header is {} bytes instead of 84", bytes.len()),
+        );
+        return Salvaged {
+            mesh: builder.build(),
+            corruptions,
+        };
+    }
+    let announced = LittleEndian::read_u32(&bytes[80..84]) as usize;
+    let data = &bytes[84..];
+    let complete = data.len() / 50;
+    if announced != complete || data.len() != complete * 50 {
+        corrupt(
+            80,
+            format!(
+                "header announces {} triangles, but the data has {} bytes",
+                announced,
+                data.len()
+            ),
+        );
+    }
+    // Data beyond the announced triangles is only trusted if the count looks broken.
+    let count = if announced <= complete && announced > 0 {
+        announced
+    } else {
+        complete
+    };
+    for (i, record) in data.chunks(50).enumerate().take(count + 1) {
+        let offset = 84 + 50 * i;
+        if i == count {
+            if count < complete || record.len() < 50 {
+                corrupt(
+                    offset,
+                    format!("{} bytes of trailing data", data.len() - 50 * i),
+                );
+            }
+            break;
+        }
+        let mut floats = [0.; 12];
+        LittleEndian::read_f32_into(&record[..48], &mut floats);
+        if let Some(c) = floats.iter().position(|f| !f.is_finite()) {
+            corrupt(
+                offset + 4 * c,
+                format!("non-finite coordinate {}", floats[c]),
+            );
+            continue;
+        }
+        builder.push(&Triangle {
+            normal: [floats[0], floats[1], floats[2]],
+            vertices: [
+                [floats[3], floats[4], floats[5]],
+                [floats[6], floats[7], floats[8]],
+                [floats[9], floats[10], floats[11]],
+            ],
+        });
+    }
+    Salvaged {
+        mesh: builder.build(),
+        corruptions,
+    }
+}
+
+fn salvage_ascii(bytes: &[u8]) -> Salvaged {
+    let mut builder = IndexedMeshBuilder::default();
+    let mut corruptions = Vec::new();
+    // Non-empty lines with their byte offsets, as lowercase tokens.
+    let mut offset = 0;
+    let mut lines = Vec::new();
+    for line in bytes.split(|&b| b == b'\n') {
+        let tokens = String::from_utf8_lossy(line)
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        if !tokens.is_empty() {
+            lines.push((offset, tokens));
+        }
+        offset += line.len() + 1;
+    }
+
+    let mut i = 0;
+    while i < lines.len() {
+        let first = lines[i].1[0].as_str();
+        if first == "solid" || first == "endsolid" {
+            i += 1;
+            continue;
+        }
+        match parse_facet(&lines[i..]) {
+            Ok(triangle) => {
+                builder.push(&triangle);
+                i += 7;
+            }
+            Err((bad, reason)) => {
+                corruptions.push(Corruption {
+                    offset: lines.get(i + bad).map_or(bytes.len(), |l| l.0) as u64,
+                    reason,
+                });
+                // Resume at the next facet, which may be the line that broke this one.
+                i += bad.max(1);
+                while i < lines.len() && lines[i].1[0] != "facet" {
+                    i += 1;
+                }
+            }
+        }
+    }
+    Salvaged {
+        mesh: builder.build(),
+        corruptions,
+    }
+}
+
+// Parses the 7 lines of a facet, or returns the index of the first bad line and the problem.
+fn parse_facet(lines: &[(usize, Vec<String>)]) -> ::std::result::Result<Triangle, (usize, String)> {
+    let line = |i: usize| {
+        lines
+            .get(i)
+            .map(|l| &l.1)
+            .ok_or_else(|| (i, "unexpected end of file".to_string()))
+    };
+    let numbers =
+        |i: usize, tokens: &[String]| -> ::std::result::Result<[f32; 3], (usize, String)> {
+            let mut v = [0.; 3];
+            for (c, t) in v.iter_mut().zip(tokens) {
+                *c = t
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .ok_or_else(|| (i, format!("invalid number {:?}", t)))?;
+            }
+            Ok(v)
+        };
+    let expect = |i: usize, keywords: &[&str]| {
+        let tokens = line(i)?;
+        if tokens
+            .iter()
+            .map(String::as_str)
+            .eq(keywords.iter().cloned())
+        {
+            Ok(())
+        } else {
+            Err((
+                i,
+                format!(
+                    "expected {:?}, got {:?}",
+                    keywords.join(" "),
+                    tokens.join(" ")
+                ),
+            ))
+        }
+    };
+    let header = line(0)?;
+    if header.len() != 5 || header[0] != "facet" || header[1] != "normal" {
+        return Err((
+            0,
+            format!("expected facet normal, got {:?}", header.join(" ")),
+        ));
+    }
+    let normal = numbers(0, &header[2..])?;
+    expect(1, &["outer", "loop"])?;
+    let mut vertices = [[0.; 3]; 3];
+    for (k, vertex) in vertices.iter_mut().enumerate() {
+        let tokens = line(2 + k)?;
+        if tokens.len() != 4 || tokens[0] != "vertex" {
+            return Err((
+                2 + k,
+                format!("expected vertex, got {:?}", tokens.join(" ")),
+            ));
+        }
+        *vertex = numbers(2 + k, &tokens[1..])?;
+    }
+    expect(5, &["endloop"])?;
+    expect(6, &["endfacet"])?;
+    Ok(Triangle { normal, vertices })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+    const BUNNY_99_ASCII: &[u8] = include_bytes!("testdata/bunny_99_ascii.stl");
+
+    fn salvage(bytes: &[u8]) -> Salvaged {
+        salvage_stl(&mut ::std::io::Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn salvage_intact_files() {
+        let mesh = crate::parse_stl_bytes(BUNNY_99).unwrap();
+        for bytes in &[BUNNY_99, BUNNY_99_ASCII] {
+            let salvaged = salvage(bytes);
+            assert_eq!(salvaged.corruptions, Vec::new());
+            assert_eq!(salvaged.mesh.faces.len(), mesh.faces.len());
+        }
+        assert_eq!(salvage(BUNNY_99).mesh, mesh);
+    }
+
+    #[test]
+    fn salvage_damaged_binary() {
+        let mut stl = BUNNY_99.to_vec();
+        // A NaN in the second triangle, and the last one cut off.
+        stl[84 + 50 + 16..84 + 50 + 20].copy_from_slice(&f32::NAN.to_le_bytes());
+        stl.truncate(stl.len() - 20);
+        let salvaged = salvage(&stl);
+        assert_eq!(salvaged.mesh.faces.len(), 97);
+        let offsets = salvaged
+            .corruptions
+            .iter()
+            .map(|c| c.offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![80, 84 + 50 + 16, 84 + 50 * 98]);
+
+        // A zeroed count is ignored.
+        let mut stl = BUNNY_99.to_vec();
+        stl[80..84].copy_from_slice(&[0; 4]);
+        let salvaged = salvage(&stl);
+        assert_eq!(salvaged.mesh.faces.len(), 99);
+        assert_eq!(salvaged.corruptions.len(), 1);
+
+        assert_eq!(salvage(&BUNNY_99[..10]).corruptions[0].offset, 10);
+    }
+
+    #[test]
+    fn salvage_damaged_ascii() {
+        let text = String::from_utf8(BUNNY_99_ASCII.to_vec()).unwrap();
+        // Break the vertex lines of one facet and the number of another.
+        let mut broken = text.replacen("vertex", "vortex", 1);
+        let third = broken.match_indices("endfacet").nth(2).unwrap().0;
+        broken.insert_str(third, "garbage\n");
+        let salvaged = salvage(broken.as_bytes());
+        assert_eq!(salvaged.mesh.faces.len(), 97);
+        assert_eq!(salvaged.corruptions.len(), 2);
+        let at = salvaged.corruptions[0].offset as usize;
+        assert!(broken[at..].trim_start().starts_with("vortex"));
+        assert!(salvaged.corruptions[1].reason.contains("garbage"));
+
+        // Damage at the end of the file.
+        let cut = &BUNNY_99_ASCII[..BUNNY_99_ASCII.len() - 100];
+        let salvaged = salvage(cut);
+        assert_eq!(salvaged.mesh.faces.len(), 98);
+        assert_eq!(salvaged.corruptions[0].reason, "unexpected end of file");
+    }
+}
@@ -0,0 +1,177 @@
+//! Lattice structures for lightweight prints.
+//!
+//! Lattices are implicit surfaces. They are combined with the
+//! [voxelized](../mesh/fn.voxelize.html) signed distance of a closed mesh, and the result is
+//! tessellated with [isosurface](../mesh/struct.ScalarGrid.html#method.isosurface), like
+//! [offset](../mesh/fn.offset.html) does.
+
+use crate::mesh::voxelize;
+use crate::{IndexedMesh, Vertex};
+use std::f32::consts::PI;
+
+/// Periodic lattice filling space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lattice {
+    /// Sheet gyroid, a smooth self supporting surface that splits space into two connected
+    /// channels. The thickness of the sheet is approximate.
+    Gyroid {
+        /// Size of a unit cell.
+        period: f32,
+        /// Thickness of the sheet.
+        thickness: f32,
+    },
+    /// Bars along the x, y and z axes, crossing at the corners of cubic cells.
+    Grid {
+        /// Distance between neighboring bars.
+        period: f32,
+        /// Diameter of the bars.
+        thickness: f32,
+    },
+}
+
+impl Lattice {
+    // Approximately the signed distance to the lattice surface, negative inside the material.
+    fn distance(&self, p: &Vertex) -> f32 {
+        match *self {
+            Lattice::Gyroid { period, thickness } => {
+                let k = 2. * PI / period;
+                let [x, y, z] = p.map(|c| c * k);
+                let g = x.sin() * y.cos() + y.sin() * z.cos() + z.sin() * x.cos();
+                // The gradient of g has a length of about 1.5 k near the surface.
+                g.abs() / (1.5 * k) - thickness / 2.
+            }
+            Lattice::Grid { period, thickness } => {
+                // Distance to the nearest multiple of period along each axis.
+                let [x, y, z] = p.map(|c| (c - (c / period).round() * period).abs());
+                let bars = [(y, z), (x, z), (x, y)];
+                bars.iter()
+                    .map(|&(a, b)| (a * a + b * b).sqrt())
+                    .fold(f32::INFINITY, f32::min)
+                    - thickness / 2.
+            }
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let (Lattice::Gyroid { period, thickness } | Lattice::Grid { period, thickness }) = *self;
+        period > 0. && period.is_finite() && thickness > 0. && thickness.is_finite()
+    }
+}
+
+/// Fills the interior of a closed mesh with a lattice, inside a solid outer wall of the given
+/// thickness, and returns the closed surface of the combined solid.
+///
+/// The signed distance of the mesh is sampled with the given grid spacing, which should be
+/// well below the lattice thickness. With a wall of 0 only the clipped lattice is returned.
+///
+/// Returns None if the mesh has no faces, spacing, period or thickness are not positive, or
+/// the grid of the given spacing would not fit in memory.
+///
+/// ```
+/// # let mut vertices = Vec::new();
+/// # for i in 0..8 {
+/// #     vertices.push([(i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2) as f32]);
+/// # }
+/// # let faces = vec![
+/// #     [0, 2, 1], [1, 2, 3], [4, 5, 6], [5, 7, 6], [0, 1, 4], [1, 5, 4],
+/// #     [2, 6, 3], [3, 6, 7], [0, 4, 2], [2, 4, 6], [1, 3, 5], [3, 7, 5],
+/// # ];
+/// let cube = stl_io::IndexedMesh::from_vertices(vertices, faces);
+/// let lattice = stl_io::structures::Lattice::Gyroid { period: 0.5, thickness: 0.1 };
+/// let infill = stl_io::structures::infill(&cube, &lattice, 0.05, 0.1).unwrap();
+/// assert!(!infill.faces.is_empty());
+/// ```
+pub fn infill(
+    mesh: &IndexedMesh,
+    lattice: &Lattice,
+    wall: f32,
+    spacing: f32,
+) -> Option<IndexedMesh> {
+    if !lattice.is_valid() || wall.is_nan() {
+        return None;
+    }
+    let mut grid = voxelize(mesh, spacing)?;
+    let [nx, ny, nz] = grid.size;
+    for z in 0..nz {
+        for y in 0..ny {
+            for x in 0..nx {
+                let p = grid.position(x, y, z);
+                let lattice = lattice.distance(&p);
+                let value = &mut grid.values[x + nx * (y + ny * z)];
+                // Intersect the interior with the union of the wall and the lattice.
+                let material = if wall > 0. {
+                    lattice.min(-*value - wall)
+                } else {
+                    lattice
+                };
+                *value = value.max(material);
+            }
+        }
+    }
+    Some(grid.isosurface(0.))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assert_even_edges, cube, volume};
+
+    #[test]
+    fn grid_infill_of_cube() {
+        let cube = cube(0., 1.);
+        let lattice = Lattice::Grid {
+            period: 0.5,
+            thickness: 0.1,
+        };
+        let bars = infill(&cube, &lattice, 0., 0.025).unwrap();
+        assert_even_edges(&bars);
+        // Bars at 0, 0.5 and 1 along each axis, of which only the central one per axis lies
+        // fully inside the cube. The others are clipped to halves and quarters.
+        let v = volume(&bars);
+        assert!(v > 0.03 && v < 0.15, "{}", v);
+        for p in &bars.vertices {
+            assert!(p.iter().all(|&c| c > -1e-3 && c < 1. + 1e-3));
+        }
+
+        let walled = infill(&cube, &lattice, 0.1, 0.025).unwrap();
+        assert_even_edges(&walled);
+        // The wall alone encloses 1 - 0.8³.
+        assert!(volume(&walled) > 1. - 0.512 && volume(&walled) < 1. - 0.512 + v);
+    }
+
+    #[test]
+    fn gyroid_infill_of_cube() {
+        let cube = cube(0., 1.);
+        let thin = Lattice::Gyroid {
+            period: 0.5,
+            thickness: 0.05,
+        };
+        let thick = Lattice::Gyroid {
+            period: 0.5,
+            thickness: 0.15,
+        };
+        let thin = infill(&cube, &thin, 0., 0.025).unwrap();
+        let thick = infill(&cube, &thick, 0., 0.025).unwrap();
+        assert_even_edges(&thin);
+        // The sheet has an area of about 3.1 per unit cell of volume 1, so the fraction of the
+        // cube it fills grows roughly in proportion to the thickness.
+        let (v_thin, v_thick) = (volume(&thin), volume(&thick));
+        assert!(v_thin > 0. && v_thick < 1.);
+        assert!(v_thick > 2. * v_thin, "{} {}", v_thin, v_thick);
+    }
+
+    #[test]
+    fn invalid_lattices() {
+        let lattice = Lattice::Gyroid {
+            period: 0.,
+            thickness: 0.1,
+        };
+        assert_eq!(infill(&cube(0., 1.), &lattice, 0.1, 0.1), None);
+        let lattice = Lattice::Grid {
+            period: 1.,
+            thickness: 0.1,
+        };
+        assert_eq!(infill(&cube(0., 1.), &lattice, 0.1, 0.), None);
+        assert_eq!(infill(&cube(0., 1.), &lattice, 0.1, 1e-7), None);
+    }
+}
@@ -0,0 +1,399 @@
+//! Helpers for testing code that produces or converts meshes.
+
+use crate::{IndexedMesh, Vertex};
+use std::collections::HashMap;
+use std::io::Result;
+
+/// Checks that two meshes describe the same geometry within tolerance.
+///
+/// Vertices match if no coordinate differs by more than tolerance. Faces match if they connect
+/// matching vertices in the same winding order, regardless of vertex and face order in the
+/// meshes or of which corner a face starts at. Normals and unreferenced vertices are ignored.
+/// The error describes the first mismatch that was found.
+///
+/// ```
+/// let a = stl_io::IndexedMesh {
+///     vertices: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// let b = stl_io::IndexedMesh {
+///     vertices: vec![[0., 1., 0.], [0., 0., 1e-7], [1., 0., 0.]],
+///     faces: vec![stl_io::IndexedTriangle { normal: [0., 0., 1.], vertices: [0, 1, 2] }],
+/// };
+/// assert!(stl_io::testing::compare_meshes(&a, &b, 1e-6).is_ok());
+/// assert!(stl_io::testing::compare_meshes(&a, &b, 0.).is_err());
+/// ```
+pub fn compare_meshes(a: &IndexedMesh, b: &IndexedMesh, tolerance: f32) -> Result<()> {
+    if a.faces.len() != b.faces.len() {
+        return Err(mismatch(format!(
+            "first mesh has {} faces, second mesh has {}",
+            a.faces.len(),
+            b.faces.len()
+        )));
+    }
+    // Identify vertices of b that are within tolerance of each other, so that meshes with
+    // duplicated vertices compare equal to their deduplicated versions.
+    let lookup = VertexLookup::new(&b.vertices, tolerance);
+    let b_canonical = b
+        .vertices
+        .iter()
+        .map(|v| lookup.find(v).unwrap())
+        .collect::<Vec<_>>();
+    let mut b_faces = HashMap::new();
+    for f in &b.faces {
+        let key = canonical_face([
+            b_canonical[f.vertices[0]],
+            b_canonical[f.vertices[1]],
+            b_canonical[f.vertices[2]],
+        ]);
+        *b_faces.entry(key).or_insert(0usize) += 1;
+    }
+    let mut a_to_b = HashMap::new();
+    for (fi, f) in a.faces.iter().enumerate() {
+        let mut mapped = [0; 3];
+        for (m, &v) in mapped.iter_mut().zip(&f.vertices) {
+            *m = match a_to_b.get(&v) {
+                Some(&m) => m,
+                None => {
+                    let m = lookup.find(&a.vertices[v]).ok_or_else(|| {
+                        mismatch(format!(
+                            "vertex #{} {:?} of first mesh has no match in second mesh",
+                            v, a.vertices[v]
+                        ))
+                    })?;
+                    a_to_b.insert(v, m);
+                    m
+                }
+            };
+        }
+        match b_faces.get_mut(&canonical_face(mapped)) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => {
+                return Err(mismatch(format!(
+                    "face #{} {:?} of first mesh has no match in second mesh",
+                    fi,
+                    [
+                        a.vertices[f.vertices[0]],
+                        a.vertices[f.vertices[1]],
+                        a.vertices[f.vertices[2]],
+                    ]
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Panics with a description of the first difference if
+/// [compare_meshes](fn.compare_meshes.html) fails.
+pub fn assert_meshes_eq(a: &IndexedMesh, b: &IndexedMesh, tolerance: f32) {
+    if let Err(e) = compare_meshes(a, b, tolerance) {
+        panic!("meshes differ: {}", e);
+    }
+}
+
+fn mismatch(message: String) -> ::std::io::Error {
+    ::std::io::Error::new(::std::io::ErrorKind::InvalidData, message)
+}
+
+// Rotates the corners of a face so that the smallest index comes first, keeping the winding.
+fn canonical_face(f: [usize; 3]) -> [usize; 3] {
+    let first = (0..3).min_by_key(|&i| f[i]).unwrap();
+    [f[first], f[(first + 1) % 3], f[(first + 2) % 3]]
+}
+
+// Finds, for a query point, the lowest vertex index within tolerance using a hash grid.
+struct VertexLookup<'a> {
+    vertices: &'a [Vertex],
+    tolerance: f32,
+    cells: HashMap<[i64; 3], Vec<usize>>,
+}
+
+impl<'a> VertexLookup<'a> {
+    fn new(vertices: &'a [Vertex], tolerance: f32) -> VertexLookup<'a> {
+        let mut lookup = VertexLookup {
+            vertices,
+            tolerance,
+            cells: HashMap::new(),
+        };
+        for (i, v) in vertices.iter().enumerate() {
+            let cell = lookup.cell(v);
+            lookup.cells.entry(cell).or_default().push(i);
+        }
+        lookup
+    }
+
+    fn cell(&self, v: &Vertex) -> [i64; 3] {
+        if self.tolerance > 0. {
+            let c = |x: f32| (f64::from(x) / f64::from(self.tolerance)).floor() as i64;
+            [c(v[0]), c(v[1]), c(v[2])]
+        } else {
+            [
+                i64::from(v[0].to_bits()),
+                i64::from(v[1].to_bits()),
+                i64::from(v[2].to_bits()),
+            ]
+        }
+    }
+
+    fn find(&self, v: &Vertex) -> Option<usize> {
+        let center = self.cell(v);
+        let range = if self.tolerance > 0. { -1..2 } else { 0..1 };
+        let mut best = None;
+        for dx in range.clone() {
+            for dy in range.clone() {
+                for dz in range.clone() {
+                    let cell = [center[0] + dx, center[1] + dy, center[2] + dz];
+                    for &i in self.cells.get(&cell).into_iter().flatten() {
+                        let close = self.vertices[i]
+                            .iter()
+                            .zip(v)
+                            .all(|(a, b)| (a - b).abs() <= self.tolerance);
+                        if close && !matches!(best, Some(b) if b <= i) {
+                            best = Some(i);
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Strategy generating random closed meshes that pass
+/// [validate](../struct.IndexedMesh.html#method.validate), for property tests of code that
+/// reads, writes or converts meshes. Requires the `proptest` feature.
+///
+/// The meshes are star-shaped: an octahedron subdivided into 8, 32 or 128 faces, whose vertices
+/// lie at random distances from a random center. Vertices and faces come in random order, each
+/// face starts at a random corner, and normals are computed from the winding.
+///
+/// ```
+/// use proptest::prelude::*;
+/// proptest!(|(mesh in stl_io::testing::arbitrary_mesh())| {
+///     let read = stl_io::parse_stl_bytes(&stl_io::mesh_to_stl_bytes(&mesh)).unwrap();
+///     stl_io::testing::assert_meshes_eq(&read, &mesh, 0.);
+/// });
+/// ```
+#[cfg(any(test, feature = "proptest"))]
+pub fn arbitrary_mesh() -> impl proptest::strategy::Strategy<Value = IndexedMesh> {
+    use proptest::prelude::*;
+    (0..3usize)
+        .prop_flat_map(|level| {
+            let (directions, faces) = subdivided_octahedron(level);
+            let vertex_order = (0..directions.len()).collect::<Vec<_>>();
+            let face_order = (0..faces.len()).collect::<Vec<_>>();
+            (
+                proptest::array::uniform3(-1000f64..1000.),
+                proptest::collection::vec(0.1f64..10., directions.len()),
+                Just(vertex_order).prop_shuffle(),
+                Just(face_order).prop_shuffle(),
+                proptest::collection::vec(0..3usize, faces.len()),
+                Just((directions, faces)),
+            )
+        })
+        .prop_map(
+            |(center, radii, vertex_order, face_order, starts, (directions, faces))| {
+                let mut vertices = vec![[0.; 3]; directions.len()];
+                for ((d, r), &new) in directions.iter().zip(&radii).zip(&vertex_order) {
+                    vertices[new] = [0, 1, 2].map(|i| (center[i] + d[i] * r) as f32);
+                }
+                let faces = face_order
+                    .iter()
+                    .zip(&starts)
+                    .map(|(&f, &start)| {
+                        let v = faces[f].map(|v| vertex_order[v]);
+                        let v = [v[start], v[(start + 1) % 3], v[(start + 2) % 3]];
+                        let corners = v.map(|v| crate::math::to_f64(&vertices[v]));
+                        let normal = crate::math::normalize(&crate::math::area_normal(&corners));
+                        crate::IndexedTriangle {
+                            normal: normal.map_or([0.; 3], |n| crate::math::to_f32(&n)),
+                            vertices: v,
+                        }
+                    })
+                    .collect();
+                IndexedMesh { vertices, faces }
+            },
+        )
+}
+
+// Unit directions and outward faces of an octahedron whose faces are split into four level
+// times.
+#[cfg(any(test, feature = "proptest"))]
+pub(crate) fn subdivided_octahedron(level: usize) -> (Vec<crate::math::Vec3>, Vec<[usize; 3]>) {
+    let mut directions = vec![
+        [1., 0., 0.],
+        [-1., 0., 0.],
+        [0., 1., 0.],
+        [0., -1., 0.],
+        [0., 0., 1.],
+        [0., 0., -1.],
+    ];
+    let mut faces = vec![
+        [0, 2, 4],
+        [2, 1, 4],
+        [1, 3, 4],
+        [3, 0, 4],
+        [2, 0, 5],
+        [1, 2, 5],
+        [3, 1, 5],
+        [0, 3, 5],
+    ];
+    crate::mesh::primitives::subdivide(&mut directions, &mut faces, level);
+    (directions, faces)
+}
+
+// The 99 triangle Stanford bunny from src/testdata, read as binary STL.
+#[cfg(test)]
+pub(crate) fn bunny() -> IndexedMesh {
+    crate::parse_stl_bytes(include_bytes!("testdata/bunny_99.stl")).unwrap()
+}
+
+// Closed octahedron with vertices on the axes at distance 1.
+#[cfg(test)]
+pub(crate) fn octahedron() -> IndexedMesh {
+    let (directions, faces) = subdivided_octahedron(0);
+    IndexedMesh::from_vertices(directions.iter().map(crate::math::to_f32), faces)
+}
+
+// Axis aligned cube from min to min + |size| along each axis, inside out for negative size.
+#[cfg(test)]
+pub(crate) fn cube(min: f32, size: f32) -> IndexedMesh {
+    let mut mesh = crate::mesh::primitives::cuboid([size; 3]);
+    for v in &mut mesh.vertices {
+        *v = v.map(|c| c + min + size.abs() / 2.);
+    }
+    mesh
+}
+
+// Both meshes in one, with the faces of a before those of b.
+#[cfg(test)]
+pub(crate) fn combine(a: &IndexedMesh, b: &IndexedMesh) -> IndexedMesh {
+    let mut mesh = a.clone();
+    let offset = a.vertices.len();
+    mesh.vertices.extend(&b.vertices);
+    mesh.faces
+        .extend(b.faces.iter().map(|f| crate::IndexedTriangle {
+            normal: f.normal,
+            vertices: f.vertices.map(|v| v + offset),
+        }));
+    mesh
+}
+
+// Volume enclosed by a closed mesh, by the divergence theorem. Negative for inside out shells.
+#[cfg(test)]
+pub(crate) fn volume(mesh: &IndexedMesh) -> f64 {
+    use crate::math::{cross, dot, to_f64};
+    mesh.faces
+        .iter()
+        .map(|f| {
+            let v = f.vertices.map(|i| to_f64(&mesh.vertices[i]));
+            dot(&v[0], &cross(&v[1], &v[2])) / 6.
+        })
+        .sum()
+}
+
+// Every edge is shared by two faces and used once in each direction, so the winding is
+// consistent.
+#[cfg(test)]
+pub(crate) fn assert_closed(mesh: &IndexedMesh) {
+    let edges = crate::mesh::edge_faces(mesh);
+    assert!(edges.values().all(|faces| faces.len() == 2));
+    let mut directed = mesh
+        .faces
+        .iter()
+        .flat_map(|f| (0..3).map(move |i| (f.vertices[i], f.vertices[(i + 1) % 3])))
+        .collect::<Vec<_>>();
+    directed.sort_unstable();
+    directed.dedup();
+    assert_eq!(directed.len(), 3 * mesh.faces.len());
+}
+
+// Every edge is shared by an even number of faces, for surfaces that may touch themselves
+// along edges.
+#[cfg(test)]
+pub(crate) fn assert_even_edges(mesh: &IndexedMesh) {
+    let edges = crate::mesh::edge_faces(mesh);
+    assert!(edges.values().all(|faces| faces.len() % 2 == 0));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::IndexedTriangle;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+    const BUNNY_99_ASCII: &[u8] = include_bytes!("testdata/bunny_99_ascii.stl");
+
+    fn read(bytes: &[u8]) -> IndexedMesh {
+        crate::create_stl_reader(&mut ::std::io::Cursor::new(bytes))
+            .unwrap()
+            .to_indexed_triangles()
+            .unwrap()
+    }
+
+    #[test]
+    fn compare_ascii_and_binary_bunny() {
+        assert_meshes_eq(&read(BUNNY_99), &read(BUNNY_99_ASCII), 0.);
+    }
+
+    #[test]
+    fn compare_permuted_and_duplicated() {
+        let a = read(BUNNY_99);
+        // Expand into one vertex per face corner, in reverse face order.
+        let mut b = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        for f in a.faces.iter().rev() {
+            let n = b.vertices.len();
+            b.vertices.extend(f.vertices.iter().map(|&v| a.vertices[v]));
+            b.faces.push(IndexedTriangle {
+                normal: f.normal,
+                vertices: [n + 1, n + 2, n],
+            });
+        }
+        assert_meshes_eq(&a, &b, 0.);
+    }
+
+    #[test]
+    fn compare_detects_differences() {
+        let a = read(BUNNY_99);
+        let mut moved = a.clone();
+        moved.vertices[0][0] += 1e-3;
+        assert!(compare_meshes(&a, &moved, 1e-4).is_err());
+        assert_meshes_eq(&a, &moved, 2e-3);
+
+        let mut flipped = a.clone();
+        flipped.faces[5].vertices.swap(0, 1);
+        assert!(compare_meshes(&a, &flipped, 0.).is_err());
+
+        let mut removed = a.clone();
+        removed.faces.pop();
+        assert!(compare_meshes(&a, &removed, 0.).is_err());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn arbitrary_meshes_are_valid(mesh in arbitrary_mesh()) {
+            mesh.validate().unwrap();
+            let mut oriented = mesh.clone();
+            proptest::prop_assert_eq!(oriented.orient_outward(), 0);
+        }
+
+        #[test]
+        fn binary_round_trip(mesh in arbitrary_mesh()) {
+            let mut bytes = Vec::new();
+            let triangles = mesh.triangles();
+            crate::write_stl(&mut bytes, triangles.iter()).unwrap();
+            assert_meshes_eq(&read(&bytes), &mesh, 0.);
+        }
+
+        #[test]
+        fn ascii_round_trip(mesh in arbitrary_mesh()) {
+            let mut bytes = Vec::new();
+            let triangles = mesh.triangles();
+            crate::write_ascii_stl(&mut bytes, triangles.iter()).unwrap();
+            assert_meshes_eq(&read(&bytes), &mesh, 0.);
+        }
+    }
+}
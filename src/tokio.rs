@@ -0,0 +1,149 @@
+//! Asynchronous STL reading and writing on top of tokio, enabled by the `async` feature.
+//!
+//! ```
+//! # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! # runtime.block_on(async {
+//! let mesh = [stl_io::Triangle { normal: [1.0, 0.0, 0.0],
+//!                                vertices: [[0.0, -1.0, 0.0],
+//!                                           [0.0, 1.0, 0.0],
+//!                                           [0.0, 0.0, 0.5]]}];
+//! let mut binary_stl = Vec::<u8>::new();
+//! stl_io::tokio::write_stl(&mut binary_stl, mesh.iter()).await.unwrap();
+//! let indexed_mesh = stl_io::tokio::read_stl(&mut &binary_stl[..]).await.unwrap();
+//! assert_eq!(indexed_mesh.faces.len(), 1);
+//! # });
+//! ```
+
+use crate::bytes::{decode_triangle, encode_triangle, HEADER_SIZE, TRIANGLE_SIZE};
+use crate::{AsciiStlReader, IndexedMesh, IndexedMeshBuilder, Triangle, Winding};
+use ::tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+use byteorder::{ByteOrder, LittleEndian};
+use std::io::Result;
+
+/// Attempts to read either ascii or binary STL from an AsyncRead.
+///
+/// Binary STL is decoded triangle by triangle as it arrives. Ascii STL is buffered completely
+/// before it is parsed.
+pub async fn read_stl<R>(read: &mut R) -> Result<IndexedMesh>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut read = BufReader::new(read);
+    let mut header = [0u8; HEADER_SIZE];
+    let header_len = read_up_to(&mut read, &mut header).await?;
+    if header[..header_len].starts_with(b"solid ") {
+        let mut text = header[..header_len].to_vec();
+        read.read_to_end(&mut text).await?;
+        return AsciiStlReader::create_triangle_iterator(&mut ::std::io::Cursor::new(text))?
+            .to_indexed_triangles();
+    }
+    if header_len < HEADER_SIZE {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::UnexpectedEof,
+            "binary STL is shorter than its 84 byte header",
+        ));
+    }
+    let num_faces = LittleEndian::read_u32(&header[80..HEADER_SIZE]);
+    // Do not reserve memory based on num_faces, because we might have just read bogus data.
+    let mut builder = IndexedMeshBuilder::default();
+    let mut record = [0u8; TRIANGLE_SIZE];
+    for _ in 0..num_faces {
+        read.read_exact(&mut record).await?;
+        builder.push(&decode_triangle(&record));
+    }
+    Ok(builder.build())
+}
+
+/// Writes binary STL to an AsyncWrite, like [write_stl](../fn.write_stl.html).
+pub async fn write_stl<'a, W, I>(writer: &mut W, mesh: I) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    I: ::std::iter::ExactSizeIterator<Item = &'a Triangle>,
+{
+    write_stl_with_winding(writer, mesh, Winding::CounterClockwise).await
+}
+
+/// Writes binary STL to an AsyncWrite, like
+/// [write_stl_with_winding](../fn.write_stl_with_winding.html).
+pub async fn write_stl_with_winding<'a, W, I>(
+    writer: &mut W,
+    mesh: I,
+    winding: Winding,
+) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    I: ::std::iter::ExactSizeIterator<Item = &'a Triangle>,
+{
+    let mut writer = BufWriter::new(writer);
+
+    // Write 80 byte header
+    writer.write_all(&[0u8; 80]).await?;
+    writer.write_u32_le(mesh.len() as u32).await?;
+    let mut record = [0u8; TRIANGLE_SIZE];
+    for t in mesh {
+        encode_triangle(t, winding, &mut record);
+        writer.write_all(&record).await?;
+    }
+    writer.flush().await
+}
+
+// Reads until buf is full or EOF is reached, returning the number of bytes read.
+async fn read_up_to<R>(read: &mut R, buf: &mut [u8]) -> Result<usize>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = 0;
+    while len < buf.len() {
+        match read.read(&mut buf[len..]).await? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+    const BUNNY_99_ASCII: &[u8] = include_bytes!("testdata/bunny_99_ascii.stl");
+
+    fn block_on<F: ::std::future::Future>(future: F) -> F::Output {
+        ::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn read_binary_and_ascii_stl_bunny() {
+        let binary_mesh = block_on(read_stl(&mut &BUNNY_99[..])).unwrap();
+        let ascii_mesh = block_on(read_stl(&mut &BUNNY_99_ASCII[..])).unwrap();
+        let expected = crate::read_stl(&mut ::std::io::Cursor::new(BUNNY_99)).unwrap();
+        assert_eq!(binary_mesh, expected);
+        assert_eq!(ascii_mesh.faces.len(), 99);
+    }
+
+    #[test]
+    fn write_stl_matches_sync_writer() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99_ASCII);
+        let bunny_mesh = AsciiStlReader::create_triangle_iterator(&mut reader)
+            .unwrap()
+            .map(|t| t.unwrap())
+            .collect::<Vec<_>>();
+        let mut binary_bunny_stl = Vec::<u8>::new();
+        block_on(write_stl(&mut binary_bunny_stl, bunny_mesh.iter())).unwrap();
+        assert_eq!(BUNNY_99.to_vec(), binary_bunny_stl);
+    }
+
+    #[test]
+    fn read_truncated_binary_stl() {
+        let stl = block_on(read_stl(&mut &BUNNY_99[..BUNNY_99.len() - 10]));
+        assert_eq!(
+            stl.as_ref().err().unwrap().kind(),
+            ::std::io::ErrorKind::UnexpectedEof,
+            "{:?}",
+            stl
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::math::{
+    add, area_normal, dot, norm, normalize, ray_triangle, scale, sub, to_f32, to_f64,
+};
+use crate::mesh::{Plane, Ray};
+use crate::{Normal, Triangle, Vertex};
+
+impl Triangle {
+    /// Unit normal computed from the counter-clockwise winding of the vertices, regardless of
+    /// the stored [normal](#structfield.normal). Returns None if the triangle has no area.
+    ///
+    /// ```
+    /// let t = stl_io::Triangle {
+    ///     normal: [0., 0., 0.],
+    ///     vertices: [[0., 0., 0.], [2., 0., 0.], [0., 2., 0.]],
+    /// };
+    /// assert_eq!(t.normal(), Some([0., 0., 1.]));
+    /// assert_eq!(t.area(), 2.);
+    /// ```
+    pub fn normal(&self) -> Option<Normal> {
+        normalize(&area_normal(&self.vertices.map(|v| to_f64(&v)))).map(|n| to_f32(&n))
+    }
+
+    /// Area of the triangle.
+    pub fn area(&self) -> f32 {
+        (norm(&area_normal(&self.vertices.map(|v| to_f64(&v)))) / 2.) as f32
+    }
+
+    /// Average of the three vertices.
+    pub fn centroid(&self) -> Vertex {
+        let [a, b, c] = self.vertices.map(|v| to_f64(&v));
+        to_f32(&scale(&add(&add(&a, &b), &c), 1. / 3.))
+    }
+
+    /// Whether the area of the triangle is at most epsilon, including triangles with repeated
+    /// vertices and slivers with all vertices on a line.
+    pub fn is_degenerate(&self, epsilon: f32) -> bool {
+        self.area() <= epsilon
+    }
+
+    /// Distance along ray to where it hits the triangle, in units of the length of its
+    /// direction. Both sides of the triangle are hit, edges included.
+    ///
+    /// ```
+    /// let t = stl_io::Triangle {
+    ///     normal: [0., 0., 1.],
+    ///     vertices: [[0., 0., 0.], [2., 0., 0.], [0., 2., 0.]],
+    /// };
+    /// let ray = stl_io::mesh::Ray { origin: [0.5, 0.5, 3.], direction: [0., 0., -1.] };
+    /// assert_eq!(t.intersect_ray(&ray), Some(3.));
+    /// ```
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        ray_triangle(
+            &to_f64(&ray.origin),
+            &to_f64(&ray.direction),
+            &self.vertices.map(|v| to_f64(&v)),
+        )
+        .map(|t| t as f32)
+    }
+
+    /// Segment in which plane crosses the triangle, directed like the contours of
+    /// [slice](mesh/fn.slice.html): for closed meshes, material lies to its left when seen
+    /// from the side plane.normal points to. Vertices on the plane count as lying above it,
+    /// so triangles that only touch the plane from above or lie within it return None.
+    ///
+    /// ```
+    /// let t = stl_io::Triangle {
+    ///     normal: [0., -1., 0.],
+    ///     vertices: [[0., 0., 0.], [2., 0., 0.], [0., 0., 2.]],
+    /// };
+    /// let plane = stl_io::mesh::Plane { normal: [0., 0., 1.], distance: 1. };
+    /// assert_eq!(t.intersect_plane(&plane), Some([[0., 0., 1.], [1., 0., 1.]]));
+    /// ```
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<[Vertex; 2]> {
+        let normal = to_f64(&plane.normal);
+        let offset = dot(&normal, &normal) * f64::from(plane.distance);
+        let distance = self.vertices.map(|v| dot(&normal, &to_f64(&v)) - offset);
+        let crossing = |i: usize, j: usize| -> Vertex {
+            // Interpolate from the lexicographically smaller vertex, so that triangles sharing
+            // the edge agree on the crossing.
+            let (a, b) = if self.vertices[j] < self.vertices[i] {
+                (j, i)
+            } else {
+                (i, j)
+            };
+            if distance[a] == 0. {
+                self.vertices[a]
+            } else if distance[b] == 0. {
+                self.vertices[b]
+            } else {
+                let (pa, pb) = (to_f64(&self.vertices[a]), to_f64(&self.vertices[b]));
+                let t = distance[a] / (distance[a] - distance[b]);
+                to_f32(&add(&pa, &scale(&sub(&pb, &pa), t)))
+            }
+        };
+        let mut leaving = None;
+        let mut entering = None;
+        for i in 0..3 {
+            let j = (i + 1) % 3;
+            match (distance[i] >= 0., distance[j] >= 0.) {
+                (true, false) => leaving = Some(crossing(i, j)),
+                (false, true) => entering = Some(crossing(i, j)),
+                _ => {}
+            }
+        }
+        Some([leaving?, entering?])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mesh::{Plane, Ray};
+    use crate::Triangle;
+    const BUNNY_99: &[u8] = include_bytes!("testdata/bunny_99.stl");
+
+    fn triangle(vertices: [[f32; 3]; 3]) -> Triangle {
+        Triangle {
+            normal: [0., 0., 0.],
+            vertices,
+        }
+    }
+
+    #[test]
+    fn degenerate_triangles() {
+        let repeated = triangle([[1., 2., 3.], [1., 2., 3.], [0., 0., 0.]]);
+        let sliver = triangle([[0., 0., 0.], [1., 1., 1.], [3., 3., 3.]]);
+        let small = triangle([[0., 0., 0.], [1e-3, 0., 0.], [0., 1e-3, 0.]]);
+        for t in &[&repeated, &sliver] {
+            assert_eq!(t.normal(), None);
+            assert_eq!(t.area(), 0.);
+            assert!(t.is_degenerate(0.));
+        }
+        assert!(small.normal().is_some());
+        assert!(!small.is_degenerate(0.));
+        assert!(small.is_degenerate(1e-6));
+        assert_eq!(sliver.centroid(), [4. / 3.; 3]);
+    }
+
+    #[test]
+    fn bunny_triangles_agree_with_stored_normals() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        for t in crate::create_stl_reader(&mut reader).unwrap() {
+            let t = t.unwrap();
+            let n = t.normal().unwrap();
+            let agreement = (0..3).map(|i| n[i] * t.normal[i]).sum::<f32>();
+            assert!(agreement > 0.99);
+            // A ray from above the centroid against the normal hits the triangle.
+            let c = t.centroid();
+            let ray = Ray {
+                origin: [0, 1, 2].map(|i| c[i] + n[i]),
+                direction: n.map(|x| -x),
+            };
+            assert!((t.intersect_ray(&ray).unwrap() - 1.).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn intersect_plane_like_slice() {
+        let t = triangle([[0., 0., 0.], [2., 0., 0.], [0., 2., 2.]]);
+        let plane = |distance| Plane {
+            normal: [0., 0., 1.],
+            distance,
+        };
+        assert_eq!(
+            t.intersect_plane(&plane(1.)),
+            Some([[0., 1., 1.], [1., 1., 1.]])
+        );
+        // Touching from above and missing give nothing, touching from below a single point.
+        assert_eq!(t.intersect_plane(&plane(0.)), None);
+        assert_eq!(t.intersect_plane(&plane(-1.)), None);
+        assert_eq!(
+            t.intersect_plane(&plane(2.)),
+            Some([[0., 2., 2.], [0., 2., 2.]])
+        );
+    }
+}
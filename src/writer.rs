@@ -0,0 +1,395 @@
+use crate::{bytes, Triangle, Winding};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::{BufWriter, Result, Write};
+
+/// Encoding written by an [StlWriter](struct.StlWriter.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StlFormat {
+    /// Binary STL, as written by [write_stl](fn.write_stl.html). This is the default.
+    #[default]
+    Binary,
+    /// Ascii STL, as written by [write_ascii_stl](fn.write_ascii_stl.html).
+    Ascii,
+}
+
+/// Which facet normals an [StlWriter](struct.StlWriter.html) writes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormalSource {
+    /// The normals stored in the triangles. This is the default.
+    #[default]
+    Stored,
+    /// Unit normals computed from the counter-clockwise winding of the vertices, or zero for
+    /// triangles without area.
+    Computed,
+}
+
+/// Writes STL with configurable format, header, solid name, normals and rounding.
+///
+/// Create one with [StlWriter::builder](struct.StlWriter.html#method.builder). The default
+/// writer produces the same bytes as [write_stl](fn.write_stl.html).
+///
+/// ```
+/// use stl_io::{NormalSource, StlFormat, StlWriter};
+/// let mesh = [stl_io::Triangle { normal: [0.0, 0.0, 0.0],
+///                                vertices: [[0.0, 0.0, 0.0],
+///                                           [1.0, 0.0, 0.0],
+///                                           [0.0, 0.3333333, 0.0]]}];
+/// let writer = StlWriter::builder()
+///     .format(StlFormat::Ascii)
+///     .solid_name("part")
+///     .normals(NormalSource::Computed)
+///     .decimals(3)
+///     .build()
+///     .unwrap();
+/// let mut ascii_stl = Vec::<u8>::new();
+/// writer.write(&mut ascii_stl, mesh.iter()).unwrap();
+/// let text = String::from_utf8(ascii_stl).unwrap();
+/// assert!(text.starts_with("solid part\nfacet normal 0e0 0e0 1e0\n"));
+/// assert!(text.contains("vertex 0e0 3.33e-1 0e0"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct StlWriter {
+    format: StlFormat,
+    header: [u8; 80],
+    solid_name: String,
+    normals: NormalSource,
+    winding: Winding,
+    decimals: Option<u8>,
+}
+
+/// Configures an [StlWriter](struct.StlWriter.html).
+#[derive(Clone, Debug)]
+pub struct StlWriterBuilder {
+    writer: StlWriter,
+    header: Vec<u8>,
+}
+
+/// Statistics returned by [StlWriter::write_with_stats](struct.StlWriter.html#method.write_with_stats).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WriteStats {
+    /// Number of bytes written.
+    pub bytes: u64,
+    /// Number of triangles written.
+    pub triangles: usize,
+    /// CRC-32 of all written bytes, as used by zip, gzip and PNG.
+    pub crc32: u32,
+}
+
+// Passes writes through to inner, counting and checksumming the bytes it accepted.
+struct Checksummed<W> {
+    inner: W,
+    bytes: u64,
+    hasher: crc32fast::Hasher,
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl StlWriter {
+    /// Starts configuring a writer, with the defaults of [write_stl](fn.write_stl.html).
+    pub fn builder() -> StlWriterBuilder {
+        StlWriterBuilder {
+            writer: StlWriter {
+                format: StlFormat::Binary,
+                header: [0; 80],
+                solid_name: "mesh".to_string(),
+                normals: NormalSource::Stored,
+                winding: Winding::CounterClockwise,
+                decimals: None,
+            },
+            header: Vec::new(),
+        }
+    }
+
+    /// Writes mesh to writer.
+    pub fn write<'a, W, I>(&self, writer: &mut W, mesh: I) -> Result<()>
+    where
+        W: Write,
+        I: ExactSizeIterator<Item = &'a Triangle>,
+    {
+        self.write_triangles(&mut BufWriter::new(writer), mesh)
+            .map(|_| ())
+    }
+
+    /// Like [write](#method.write), but also returns statistics about the written data, e.g.
+    /// to store integrity metadata alongside a generated file without reading it again.
+    ///
+    /// ```
+    /// let mesh = [stl_io::Triangle { normal: [0.0, 0.0, 1.0],
+    ///                                vertices: [[0.0, 0.0, 0.0],
+    ///                                           [1.0, 0.0, 0.0],
+    ///                                           [0.0, 1.0, 0.0]]}];
+    /// let mut binary_stl = Vec::<u8>::new();
+    /// let writer = stl_io::StlWriter::builder().build().unwrap();
+    /// let stats = writer.write_with_stats(&mut binary_stl, mesh.iter()).unwrap();
+    /// assert_eq!(stats.bytes, 84 + 50);
+    /// assert_eq!(stats.triangles, 1);
+    /// ```
+    pub fn write_with_stats<'a, W, I>(&self, writer: &mut W, mesh: I) -> Result<WriteStats>
+    where
+        W: Write,
+        I: ExactSizeIterator<Item = &'a Triangle>,
+    {
+        let mut writer = BufWriter::new(Checksummed {
+            inner: writer,
+            bytes: 0,
+            hasher: crc32fast::Hasher::new(),
+        });
+        let triangles = self.write_triangles(&mut writer, mesh)?;
+        let checksummed = writer.get_ref();
+        Ok(WriteStats {
+            bytes: checksummed.bytes,
+            triangles,
+            crc32: checksummed.hasher.clone().finalize(),
+        })
+    }
+
+    // Writes and flushes mesh, returning the number of triangles.
+    fn write_triangles<'a, W, I>(&self, writer: &mut W, mesh: I) -> Result<usize>
+    where
+        W: Write,
+        I: ExactSizeIterator<Item = &'a Triangle>,
+    {
+        let mut triangles = 0;
+        match self.format {
+            StlFormat::Binary => {
+                writer.write_all(&self.header)?;
+                writer.write_u32::<LittleEndian>(mesh.len() as u32)?;
+                let mut record = [0u8; bytes::TRIANGLE_SIZE];
+                for t in mesh {
+                    bytes::encode_triangle(&self.prepare(t), self.winding, &mut record);
+                    writer.write_all(&record)?;
+                    triangles += 1;
+                }
+            }
+            StlFormat::Ascii => {
+                writeln!(writer, "solid {}", self.solid_name)?;
+                for t in mesh {
+                    let t = self.prepare(t);
+                    let [x, y, z] = t.normal;
+                    writeln!(writer, "facet normal {:e} {:e} {:e}", x, y, z)?;
+                    writeln!(writer, "  outer loop")?;
+                    for &v in &self.winding.order() {
+                        let [x, y, z] = t.vertices[v];
+                        writeln!(writer, "    vertex {:e} {:e} {:e}", x, y, z)?;
+                    }
+                    writeln!(writer, "  endloop")?;
+                    writeln!(writer, "endfacet")?;
+                    triangles += 1;
+                }
+                writeln!(writer, "endsolid {}", self.solid_name)?;
+            }
+        }
+        writer.flush()?;
+        Ok(triangles)
+    }
+
+    // Applies the rounding and the normal source to t.
+    fn prepare(&self, t: &Triangle) -> Triangle {
+        let mut t = t.clone();
+        let scale = self.decimals.map(|d| 10f64.powi(i32::from(d)));
+        // Adding zero turns -0 into 0.
+        let round = |c: f32| match scale {
+            Some(scale) => ((f64::from(c) * scale).round() / scale) as f32 + 0.,
+            None => c,
+        };
+        for v in &mut t.vertices {
+            *v = v.map(round);
+        }
+        if self.normals == NormalSource::Computed {
+            t.normal = t.normal().unwrap_or([0.; 3]);
+        }
+        t.normal = t.normal.map(round);
+        t
+    }
+}
+
+impl StlWriterBuilder {
+    /// Binary or ascii STL.
+    pub fn format(mut self, format: StlFormat) -> StlWriterBuilder {
+        self.writer.format = format;
+        self
+    }
+
+    /// Content of the 80 byte header of binary STL, padded with zeros. Defaults to all zeros.
+    pub fn header<H: AsRef<[u8]>>(mut self, header: H) -> StlWriterBuilder {
+        self.header = header.as_ref().to_vec();
+        self
+    }
+
+    /// Name after `solid` and `endsolid` in ascii STL. Defaults to "mesh".
+    pub fn solid_name<S: Into<String>>(mut self, name: S) -> StlWriterBuilder {
+        self.writer.solid_name = name.into();
+        self
+    }
+
+    /// Whether to write stored or computed normals. Defaults to stored normals.
+    pub fn normals(mut self, normals: NormalSource) -> StlWriterBuilder {
+        self.writer.normals = normals;
+        self
+    }
+
+    /// Order of the vertices in each triangle. Defaults to counter-clockwise, which is what
+    /// STL specifies.
+    pub fn winding(mut self, winding: Winding) -> StlWriterBuilder {
+        self.writer.winding = winding;
+        self
+    }
+
+    /// Rounds coordinates and normals to the given number of decimal places before writing,
+    /// e.g. to make ascii STL shorter or to hide floating point noise. Off by default.
+    /// Computed normals are computed from the rounded vertices.
+    pub fn decimals(mut self, decimals: u8) -> StlWriterBuilder {
+        self.writer.decimals = Some(decimals);
+        self
+    }
+
+    /// Checks the configuration and creates the writer.
+    ///
+    /// Fails with InvalidInput if the header is longer than 80 bytes or starts with "solid ",
+    /// which would make readers mistake binary STL for ascii, or if the solid name contains a
+    /// line break.
+    pub fn build(self) -> Result<StlWriter> {
+        let invalid_input = |message: String| {
+            Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                message,
+            ))
+        };
+        if self.header.len() > 80 {
+            return invalid_input(format!(
+                "binary STL header must fit into 80 bytes, got {}",
+                self.header.len()
+            ));
+        }
+        if self.header.starts_with(b"solid ") {
+            return invalid_input("binary STL header must not start with \"solid \"".to_string());
+        }
+        if self.writer.solid_name.contains(['\n', '\r']) {
+            return invalid_input(format!(
+                "solid name {:?} contains a line break",
+                self.writer.solid_name
+            ));
+        }
+        let mut writer = self.writer;
+        writer.header[..self.header.len()].copy_from_slice(&self.header);
+        Ok(writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn defaults_match_write_stl() {
+        let mesh = crate::testing::bunny().triangles();
+        let mut expected = Vec::new();
+        crate::write_stl(&mut expected, mesh.iter()).unwrap();
+        let mut binary = Vec::new();
+        let writer = StlWriter::builder().build().unwrap();
+        writer.write(&mut binary, mesh.iter()).unwrap();
+        assert_eq!(binary, expected);
+
+        let mut expected = Vec::new();
+        crate::write_ascii_stl(&mut expected, mesh.iter()).unwrap();
+        let mut ascii = Vec::new();
+        let writer = StlWriter::builder()
+            .format(StlFormat::Ascii)
+            .build()
+            .unwrap();
+        writer.write(&mut ascii, mesh.iter()).unwrap();
+        assert_eq!(ascii, expected);
+    }
+
+    #[test]
+    fn header_and_rounding() {
+        let mesh = crate::testing::bunny().triangles();
+        let writer = StlWriter::builder()
+            .header("exported by test")
+            .normals(NormalSource::Computed)
+            .decimals(2)
+            .winding(Winding::Clockwise)
+            .build()
+            .unwrap();
+        let mut binary = Vec::new();
+        writer.write(&mut binary, mesh.iter()).unwrap();
+        assert_eq!(&binary[..16], b"exported by test");
+        assert!(binary[16..80].iter().all(|&b| b == 0));
+
+        let read = crate::BinaryStlReader::create_triangle_iterator(&mut &binary[..])
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        for (r, t) in read.iter().zip(&mesh) {
+            // Clockwise winding swaps the last two vertices.
+            for (a, b) in r
+                .vertices
+                .iter()
+                .zip(&[t.vertices[0], t.vertices[2], t.vertices[1]])
+            {
+                for (x, y) in a.iter().zip(b) {
+                    assert!((x - y).abs() <= 0.005 + 1e-7);
+                    assert_eq!(*x, ((f64::from(*x) * 100.).round() / 100.) as f32);
+                }
+            }
+            // Normals were computed from the rounded counter-clockwise vertices.
+            let ccw = Triangle {
+                normal: [0.; 3],
+                vertices: [r.vertices[0], r.vertices[2], r.vertices[1]],
+            };
+            let computed = ccw.normal().unwrap();
+            for (x, y) in r.normal.iter().zip(&computed) {
+                assert_eq!(*x, ((f64::from(*y) * 100.).round() / 100.) as f32 + 0.);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_configurations() {
+        let errors = [
+            StlWriter::builder().header([1u8; 81]).build(),
+            StlWriter::builder().header("solid binary").build(),
+            StlWriter::builder().solid_name("two\nlines").build(),
+        ];
+        for e in &errors {
+            assert_eq!(
+                e.as_ref().unwrap_err().kind(),
+                ::std::io::ErrorKind::InvalidInput
+            );
+        }
+        assert!(StlWriter::builder().header([1u8; 80]).build().is_ok());
+    }
+
+    #[test]
+    fn stats_describe_written_bytes() {
+        let mesh = crate::testing::bunny().triangles();
+        for &format in &[StlFormat::Binary, StlFormat::Ascii] {
+            let writer = StlWriter::builder().format(format).build().unwrap();
+            let mut out = Vec::new();
+            let stats = writer.write_with_stats(&mut out, mesh.iter()).unwrap();
+            assert_eq!(stats.bytes, out.len() as u64);
+            assert_eq!(stats.triangles, 99);
+            assert_eq!(stats.crc32, crc32fast::hash(&out));
+        }
+        // The standard CRC-32 check value.
+        let mut checksummed = Checksummed {
+            inner: Vec::new(),
+            bytes: 0,
+            hasher: crc32fast::Hasher::new(),
+        };
+        checksummed.write_all(b"123456789").unwrap();
+        assert_eq!(checksummed.bytes, 9);
+        assert_eq!(checksummed.hasher.finalize(), 0xcbf4_3926);
+    }
+}